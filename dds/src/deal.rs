@@ -266,6 +266,13 @@ pub struct DDSDealPBNRepr(
 /// Hands: 0 North, 1 East, 2 South, 3 West
 pub trait AsDDSDeal {
     fn to_dds_deal(&self) -> DDSDealRepr;
+
+    /// The cards already played to the current trick, in order, which are not part of
+    /// [`AsDDSDeal::to_dds_deal`]. By default the deal is at the start of a trick.
+    #[inline]
+    fn current_trick(&self) -> [Option<(DdsRank, DdsSuit)>; 3] {
+        [None; 3]
+    }
 }
 
 /// This helps us build a [`DdsDeal`]. Rough edges right now, should be refactored or improved
@@ -389,6 +396,8 @@ impl DDSDealBuilder {
         self
     }
 
+    /// Sets the cards already played to the current trick. DDS wants ranks from 2 to 14
+    /// and zeroes for the cards not played yet.
     #[inline]
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
@@ -398,9 +407,9 @@ impl DDSDealBuilder {
             .map(|card| {
                 if let Some(card) = card {
                     let (rank, suit) = card.as_card();
-                    (rank.0, suit as i32)
+                    (rank.0.trailing_zeros() as i32, suit as i32)
                 } else {
-                    (-1, -1)
+                    (0, 0)
                 }
             })
             .unzip();
@@ -440,6 +449,19 @@ impl DDSDealBuilder {
 }
 
 impl DdsDeal {
+    /// The deal to solve for `contract`, with the cards already played to the current trick.
+    #[allow(clippy::expect_used)]
+    fn from_parts<D: AsDDSDeal, C: AsDDSContract>(deal: &D, contract: &C) -> Self {
+        let (trump, first) = contract.as_dds_contract();
+        DDSDealBuilder::new()
+            .trump(trump)
+            .first(first)
+            .remain_cards(deal.to_dds_deal())
+            .current_trick(&deal.current_trick())
+            .build()
+            .expect("every part of the deal is set")
+    }
+
     #[inline]
     #[must_use]
     pub fn new() -> Self {
@@ -555,16 +577,7 @@ impl Boards {
         let deals = deals
             .iter()
             .zip(contracts.iter())
-            .map(|(deal, contract)| {
-                let (trump, first) = contract.as_dds_contract();
-                DdsDeal {
-                    trump: trump as i32,
-                    first: first as i32,
-                    current_trick_suit: [0i32; 3],
-                    current_trick_rank: [0i32; 3],
-                    remain_cards: deal.to_dds_deal().as_slice(),
-                }
-            })
+            .map(|(deal, contract)| DdsDeal::from_parts(deal, contract))
             .cycle()
             .take(MAXNOOFBOARDS)
             .collect_vec()
@@ -612,16 +625,7 @@ impl Boards {
         let deals = deals
             .iter()
             .zip(contracts.iter())
-            .map(|(deal, contract)| {
-                let (trump, first) = contract.as_dds_contract();
-                DdsDeal {
-                    trump: trump as i32,
-                    first: first as i32,
-                    current_trick_suit: [0i32; 3],
-                    current_trick_rank: [0i32; 3],
-                    remain_cards: deal.to_dds_deal().as_slice(),
-                }
-            })
+            .map(|(deal, contract)| DdsDeal::from_parts(deal, contract))
             .cycle()
             .take(MAXNOOFBOARDS)
             .collect_vec()
@@ -7,6 +7,7 @@ use crate::bindings::{
 };
 use crate::ddserror::DdsError;
 use crate::deal::DdsHandEncoding;
+use crate::doubledummy::DoubleDummySolver;
 use crate::tables::{DdTableResults, ParResults, Populated, VulnerabilityEncoding};
 use crate::utils::if_no_fault_return;
 
@@ -121,6 +122,8 @@ pub trait ParCalculator {
     }
 }
 
+impl ParCalculator for DoubleDummySolver {}
+
 #[repr(C)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Copy, Clone)]
@@ -34,6 +34,13 @@ pub trait AsDDSCard {
     fn as_card(&self) -> (DdsRank, DdsSuit);
 }
 
+impl AsDDSCard for (DdsRank, DdsSuit) {
+    #[inline]
+    fn as_card(&self) -> (DdsRank, DdsSuit) {
+        *self
+    }
+}
+
 pub trait AsDDSPlayTrace<I, C>
 where
     I: IntoIterator,
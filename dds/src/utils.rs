@@ -412,6 +412,7 @@ impl RankSeq {
 /// - Trump
 /// - Leader
 /// - Cards
+/// - Cards played to the current trick
 ///
 /// # Errors
 /// Will error if the trump or the player are not valid values following
@@ -427,6 +428,7 @@ pub(crate) fn build_c_deal<C: AsDDSContract, D: AsDDSDeal>(
         .trump(trump)
         .first(first)
         .remain_cards(deal.to_dds_deal())
+        .current_trick(&deal.current_trick())
         .build()
 }
 
@@ -0,0 +1,648 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Auctions: bids, their legality and the contract they reach.
+
+use crate::prelude::*;
+use std::num::{NonZeroU8, ParseIntError};
+
+/// Structure that represents a bidding sequence
+/// made of [`Bid`]s.
+///
+/// Bids are checked when pushed: contract bids must be sufficient, a double is allowed only
+/// over an opponent's undoubled contract and a redouble only over an opponent's double.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let bidding = Bidding::from_str("1S 2H 4S P P X P P XX P P P")?;
+/// assert!(bidding.is_finished());
+/// let contract = bidding.contract(Seat::East, Vulnerability::EW).unwrap();
+/// assert_eq!(contract, Contract::from_str("4SEXX", Vulnerable::Yes)?);
+/// assert!(Bidding::from_str("1S P X").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Bidding {
+    bidding: Vec<Bid>,
+    alerts: Vec<Option<String>>,
+}
+
+impl IntoIterator for Bidding {
+    type Item = Bid;
+    type IntoIter = <Vec<Bid> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.bidding.into_iter()
+    }
+}
+
+impl fmt::Display for Bidding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bid in &self.iter().chunks(4) {
+            writeln!(f, "{}", bid.into_iter().format("-"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Enum modelling the possible kinds of errors we
+/// can encounter while parsing a bidding sequence:
+/// either the bid is not a starting bid (e.g. bidding starts with a double),
+/// insufficient, an illegal double or redouble, a bid after the end of the auction
+/// or simply we were unable to parse the last [`Bid`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum BuildBiddingErrorKind {
+    Insufficient,
+    NonStarter,
+    IllegalDouble,
+    IllegalRedouble,
+    Finished,
+    NonExistent(BuildBidError),
+}
+
+impl std::fmt::Display for BuildBiddingErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BuildBiddingErrorKind::Insufficient => write!(f, "bidding is insufficient"),
+            BuildBiddingErrorKind::NonStarter => {
+                write!(f, "bidding cannot be started with a Double or a Redouble")
+            }
+            BuildBiddingErrorKind::IllegalDouble => {
+                write!(
+                    f,
+                    "only an undoubled contract of the opponents can be doubled"
+                )
+            }
+            BuildBiddingErrorKind::IllegalRedouble => {
+                write!(f, "only a double of the opponents can be redoubled")
+            }
+            BuildBiddingErrorKind::Finished => write!(f, "the auction is over"),
+            BuildBiddingErrorKind::NonExistent(ref bid) => write!(f, "{bid}"),
+        }
+    }
+}
+impl std::error::Error for BuildBiddingErrorKind {}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BuildBiddingError {
+    bid: Option<Bid>,
+    kind: BuildBiddingErrorKind,
+}
+
+impl std::fmt::Display for BuildBiddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(bid) = self.bid {
+            write!(f, "unable to add to auction `{bid}`: {}", self.kind)
+        } else {
+            write!(f, "unable to parse bid: {}", match &self.kind {
+                BuildBiddingErrorKind::NonExistent(err) => &err.bid,
+                _=> unreachable!("tried to extract a string for failed bid parsing from a non BuildBiddingErrorKind::NonExistent")
+            })
+        }
+    }
+}
+
+impl BuildBiddingError {
+    /// Error for a bid we were not able to parse.
+    pub(crate) fn unparsable(bid: &str) -> Self {
+        Self {
+            bid: None,
+            kind: BuildBiddingErrorKind::NonExistent(BuildBidError {
+                bid: bid.to_owned(),
+                kind: BuildBidErrorKind::Strain,
+            }),
+        }
+    }
+
+    fn illegal(bid: Bid, kind: BuildBiddingErrorKind) -> Self {
+        Self {
+            bid: Some(bid),
+            kind,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &BuildBiddingErrorKind {
+        &self.kind
+    }
+}
+
+impl From<BuildBidError> for BuildBiddingError {
+    fn from(value: BuildBidError) -> Self {
+        Self {
+            bid: None,
+            kind: BuildBiddingErrorKind::NonExistent(value),
+        }
+    }
+}
+
+impl std::error::Error for BuildBiddingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.kind {
+            BuildBiddingErrorKind::NonExistent(ref e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl Bidding {
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            bidding: Vec::new(),
+            alerts: Vec::new(),
+        }
+    }
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bidding.len()
+    }
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0usize
+    }
+    #[must_use]
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&Bid> {
+        self.bidding.get(index)
+    }
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, Bid> {
+        self.bidding.iter()
+    }
+    /// The explanation of the alert of the `index`-th bid, if alerted.
+    #[must_use]
+    #[inline]
+    pub fn alert(&self, index: usize) -> Option<&str> {
+        self.alerts.get(index)?.as_deref()
+    }
+    /// Iterates over the bids together with the explanation of their alert, if alerted.
+    pub fn iter_with_alerts(&self) -> impl Iterator<Item = (&Bid, Option<&str>)> {
+        self.bidding
+            .iter()
+            .zip(self.alerts.iter().map(Option::as_deref))
+    }
+    /// Alerts the `index`-th bid with an explanation.
+    /// Returns false if there is no such bid.
+    #[inline]
+    pub fn set_alert(&mut self, index: usize, explanation: impl Into<String>) -> bool {
+        if let Some(alert) = self.alerts.get_mut(index) {
+            *alert = Some(explanation.into());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The last call other than a pass, with its index.
+    fn last_call(&self) -> Option<(usize, Bid)> {
+        self.bidding
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, bid)| **bid != Bid::Pass)
+            .map(|(index, bid)| (index, *bid))
+    }
+
+    /// The last contract bid, with its index.
+    fn last_contract(&self) -> Option<(usize, Bid)> {
+        self.bidding
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, bid)| bid.is_contract())
+            .map(|(index, bid)| (index, *bid))
+    }
+
+    /// Whether the call at `index` was made by an opponent of the player to bid.
+    fn by_opponents(&self, index: usize) -> bool {
+        (self.len() - index) % 2 == 1
+    }
+
+    /// Pushes a bid at the end of the auction.
+    ///
+    /// # Errors
+    /// - If the `Bid` is insufficient
+    /// - If we are at the first `Bid` and find a Double or Redouble
+    /// - If a Double is not over an undoubled contract of the opponents
+    /// - If a Redouble is not over a Double of the opponents
+    /// - If the auction is already over
+    #[inline]
+    pub fn push(&mut self, bid: Bid) -> Result<(), BuildBiddingError> {
+        self.check(bid)?;
+        self.bidding.push(bid);
+        self.alerts.push(None);
+        Ok(())
+    }
+
+    /// Whether `bid` can be pushed at the end of the auction.
+    #[must_use]
+    pub fn is_legal(&self, bid: Bid) -> bool {
+        self.check(bid).is_ok()
+    }
+
+    fn check(&self, bid: Bid) -> Result<(), BuildBiddingError> {
+        if self.is_finished() {
+            return Err(BuildBiddingError::illegal(
+                bid,
+                BuildBiddingErrorKind::Finished,
+            ));
+        }
+        if self.is_empty() && !(bid.is_contract() || bid == Bid::Pass) {
+            return Err(BuildBiddingError::illegal(
+                bid,
+                BuildBiddingErrorKind::NonStarter,
+            ));
+        }
+        match bid {
+            Bid::Pass => {}
+            Bid::Contract(..) => {
+                if let Some((_, last)) = self.last_contract() {
+                    if !bid.can_bid_over(&last) {
+                        return Err(BuildBiddingError::illegal(
+                            bid,
+                            BuildBiddingErrorKind::Insufficient,
+                        ));
+                    }
+                }
+            }
+            Bid::Double => match self.last_call() {
+                Some((index, Bid::Contract(..))) if self.by_opponents(index) => {}
+                _ => {
+                    return Err(BuildBiddingError::illegal(
+                        bid,
+                        BuildBiddingErrorKind::IllegalDouble,
+                    ))
+                }
+            },
+            Bid::Redouble => match self.last_call() {
+                Some((index, Bid::Double)) if self.by_opponents(index) => {}
+                _ => {
+                    return Err(BuildBiddingError::illegal(
+                        bid,
+                        BuildBiddingErrorKind::IllegalRedouble,
+                    ))
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Whether the auction is over: three passes after a call, or four passes.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.len() >= 4
+            && self
+                .bidding
+                .iter()
+                .rev()
+                .take(3)
+                .all(|bid| *bid == Bid::Pass)
+    }
+
+    /// Whether the auction is over with four passes.
+    #[must_use]
+    pub fn is_passed_out(&self) -> bool {
+        self.is_finished() && self.last_contract().is_none()
+    }
+
+    /// The seat of the player to call next.
+    #[must_use]
+    pub fn next_to_bid(&self, dealer: Seat) -> Seat {
+        dealer + self.len()
+    }
+
+    /// The contract reached so far, which is the final contract once the auction is over.
+    /// The declarer is the player of the declaring side who first named the strain.
+    /// Returns `None` if no contract has been bid.
+    #[must_use]
+    pub fn contract(&self, dealer: Seat, vulnerability: Vulnerability) -> Option<Contract> {
+        let (index, Bid::Contract(level, strain)) = self.last_contract()? else {
+            return None;
+        };
+        let declarer = self
+            .bidding
+            .iter()
+            .enumerate()
+            .find(|(first, bid)| {
+                first % 2 == index % 2 && matches!(bid, Bid::Contract(_, s) if *s == strain)
+            })
+            .map_or(index, |(first, _)| first);
+        let doubled = match self.last_call() {
+            Some((_, Bid::Double)) => Doubled::Doubled,
+            Some((_, Bid::Redouble)) => Doubled::Redoubled,
+            _ => Doubled::NotDoubled,
+        };
+        let declarer = dealer + declarer;
+        Some(Contract::new(
+            level.get(),
+            strain,
+            declarer,
+            vulnerability.is_vulnerable(declarer),
+            doubled,
+        ))
+    }
+}
+
+impl FromStr for Bidding {
+    type Err = BuildBiddingError;
+
+    /// Reads calls separated by spaces, dashes or commas, in any of the forms read by
+    /// [`Bid::from_str`]. A call followed by `!` is alerted and `AP` stands for the passes
+    /// that end the auction.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bidding = Self::new();
+        for call in s
+            .split(|c: char| c.is_whitespace() || c == '-' || c == ',')
+            .filter(|call| !call.is_empty())
+        {
+            if call.eq_ignore_ascii_case("AP") {
+                while !bidding.is_finished() {
+                    bidding.push(Bid::Pass)?;
+                }
+                continue;
+            }
+            let (call, alerted) = match call.strip_suffix('!') {
+                Some(call) => (call, true),
+                None => (call, false),
+            };
+            bidding.push(Bid::from_str(call)?)?;
+            if alerted {
+                bidding.set_alert(bidding.len() - 1, "");
+            }
+        }
+        Ok(bidding)
+    }
+}
+
+impl<'a> IntoIterator for &'a Bidding {
+    type Item = &'a Bid;
+    type IntoIter = std::slice::Iter<'a, Bid>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// We model bids as an Enum, with possible contracts as tuple
+/// variants containing [`NonZeroU8`] and a [`Strain`]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Bid {
+    Pass,
+    Double,
+    Redouble,
+    Contract(NonZeroU8, Strain),
+}
+
+impl std::fmt::Display for Bid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bid::Contract(level, strain) => write!(f, "{level}{strain}"),
+            Bid::Pass => write!(f, "Pass"),
+            Bid::Redouble => write!(f, "Redouble"),
+            Bid::Double => write!(f, "Double"),
+        }
+    }
+}
+
+impl FromStr for Bid {
+    type Err = BuildBidError;
+
+    /// Reads a bid, case insensitive: `P` or `Pass`, `X`, `D`, `Dbl` or `Double`, `XX`, `R`,
+    /// `Rdbl` or `Redouble`, and contracts like `1NT`, `3N`, `4S` or `2♥`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |kind| BuildBidError {
+            bid: s.to_owned(),
+            kind,
+        };
+        let bid = s.trim();
+        match bid.to_ascii_uppercase().as_str() {
+            "P" | "PASS" => return Ok(Bid::Pass),
+            "X" | "D" | "DBL" | "DOUBLE" => return Ok(Bid::Double),
+            "XX" | "R" | "RDBL" | "REDOUBLE" => return Ok(Bid::Redouble),
+            _ => {}
+        }
+        let (level, strain) = bid.split_at(bid.chars().next().map_or(0, char::len_utf8));
+        let level = level
+            .parse::<NonZeroU8>()
+            .map_err(|e| error(BuildBidErrorKind::Level(e)))?;
+        if level.get() > 7 {
+            return Err(error(BuildBidErrorKind::LevelRange));
+        }
+        let strain = parse_strain(strain).ok_or_else(|| error(BuildBidErrorKind::Strain))?;
+        Ok(Bid::Contract(level, strain))
+    }
+}
+
+/// Reads the strain of a bid, case insensitive: `C`, `D`, `H`, `S`, `N` or `NT`, or a suit
+/// symbol.
+pub(crate) fn parse_strain(strain: &str) -> Option<Strain> {
+    match strain.to_ascii_uppercase().as_str() {
+        "C" | "♣" => Some(Strain::Clubs),
+        "D" | "♦" => Some(Strain::Diamonds),
+        "H" | "♥" => Some(Strain::Hearts),
+        "S" | "♠" => Some(Strain::Spades),
+        "N" | "NT" => Some(Strain::NoTrumps),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BuildBidError {
+    pub bid: String,
+    pub kind: BuildBidErrorKind,
+}
+
+/// Enum representing the various kind of errors
+/// we could encounter while parsing a single bid:
+/// either we are unable to parse the integer part of the bid,
+/// the level is not between 1 and 7
+/// or we are unable to parse the strain of the bid.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum BuildBidErrorKind {
+    Level(ParseIntError),
+    LevelRange,
+    Strain,
+}
+impl std::fmt::Display for BuildBidErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildBidErrorKind::Level(_e) => write!(f, "unable to parse level"),
+            BuildBidErrorKind::LevelRange => write!(f, "level must be between 1 and 7"),
+            BuildBidErrorKind::Strain => write!(f, "unable to parse strain"),
+        }
+    }
+}
+
+impl std::error::Error for BuildBidError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.kind {
+            BuildBidErrorKind::Level(ref e) => Some(e),
+            BuildBidErrorKind::LevelRange | BuildBidErrorKind::Strain => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BuildBidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bid: {} cannot be parsed", self.bid)
+    }
+}
+
+impl Bid {
+    /// Whether the bid can follow `before`. Only the last call is known here, so this is
+    /// just a first check: [`Bidding::push`] checks the whole auction.
+    #[must_use]
+    pub fn can_bid_over(&self, before: &Self) -> bool {
+        match before {
+            Bid::Contract(level, strain) => match self {
+                Bid::Contract(self_level, self_strain) => {
+                    level < self_level || (level == self_level && strain < self_strain)
+                }
+                Bid::Pass | Bid::Double => true,
+                Bid::Redouble => false,
+            },
+            Bid::Pass => true,
+            Bid::Double => self != &Bid::Double,
+            Bid::Redouble => self != &Bid::Double && self != &Bid::Redouble,
+        }
+    }
+
+    #[must_use]
+    pub fn is_contract(&self) -> bool {
+        matches!(self, Bid::Contract(_, _))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bid(s: &str) -> Bid {
+        Bid::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn parses_bids_test() {
+        assert_eq!(bid("p"), Bid::Pass);
+        assert_eq!(bid("Pass"), Bid::Pass);
+        assert_eq!(bid("X"), Bid::Double);
+        assert_eq!(bid("xx"), Bid::Redouble);
+        assert_eq!(bid("1NT"), Bid::Contract(NonZeroU8::MIN, Strain::NoTrumps));
+        assert_eq!(bid("3n"), bid("3NT"));
+        assert_eq!(bid("2♥"), bid("2H"));
+        assert!(matches!(
+            Bid::from_str("8S").unwrap_err().kind,
+            BuildBidErrorKind::LevelRange
+        ));
+        assert!(matches!(
+            Bid::from_str("0S").unwrap_err().kind,
+            BuildBidErrorKind::Level(_)
+        ));
+        assert!(matches!(
+            Bid::from_str("1Z").unwrap_err().kind,
+            BuildBidErrorKind::Strain
+        ));
+    }
+
+    #[test]
+    fn legality_test() {
+        let kind = |s: &str| Bidding::from_str(s).unwrap_err().kind;
+        assert!(matches!(
+            kind("1S P 1H"),
+            BuildBiddingErrorKind::Insufficient
+        ));
+        assert!(matches!(
+            kind("1S P 1S"),
+            BuildBiddingErrorKind::Insufficient
+        ));
+        assert!(matches!(kind("X"), BuildBiddingErrorKind::NonStarter));
+        assert!(matches!(kind("P X"), BuildBiddingErrorKind::IllegalDouble));
+        assert!(matches!(
+            kind("1S P X"),
+            BuildBiddingErrorKind::IllegalDouble
+        ));
+        assert!(matches!(
+            kind("1S X X"),
+            BuildBiddingErrorKind::IllegalDouble
+        ));
+        assert!(matches!(
+            kind("1S X P X"),
+            BuildBiddingErrorKind::IllegalDouble
+        ));
+        assert!(matches!(
+            kind("1S XX"),
+            BuildBiddingErrorKind::IllegalRedouble
+        ));
+        assert!(matches!(
+            kind("1S X P XX"),
+            BuildBiddingErrorKind::IllegalRedouble
+        ));
+        assert!(matches!(
+            kind("1S X XX XX"),
+            BuildBiddingErrorKind::IllegalRedouble
+        ));
+        assert!(matches!(
+            kind("1S P P P P"),
+            BuildBiddingErrorKind::Finished
+        ));
+        assert!(Bidding::from_str("1S P P X XX P P P").is_ok());
+        assert!(Bidding::from_str("1S X P P XX").is_ok());
+        assert!(Bidding::from_str("1S X 2C X").is_ok());
+    }
+
+    #[test]
+    fn end_of_auction_test() {
+        let passed = Bidding::from_str("P P P").unwrap();
+        assert!(!passed.is_finished());
+        assert_eq!(passed.next_to_bid(Seat::West), Seat::South);
+        let passed = Bidding::from_str("P P P P").unwrap();
+        assert!(passed.is_finished());
+        assert!(passed.is_passed_out());
+        assert_eq!(passed.contract(Seat::North, Vulnerability::None), None);
+
+        let bidding = Bidding::from_str("P 1C! P 1H P 2H AP").unwrap();
+        assert!(bidding.is_finished());
+        assert!(!bidding.is_passed_out());
+        assert_eq!(bidding.len(), 9);
+        assert_eq!(bidding.alert(1), Some(""));
+        assert_eq!(
+            Bidding::from_str(&bidding.to_string()).unwrap().len(),
+            bidding.len()
+        );
+    }
+
+    #[test]
+    fn contract_test() {
+        let contract = |s: &str, dealer| {
+            Bidding::from_str(s)
+                .unwrap()
+                .contract(dealer, Vulnerability::NS)
+                .unwrap()
+        };
+        // North opens 1H, South raises: North declares.
+        assert_eq!(
+            contract("1H P 2H P 4H AP", Seat::North),
+            Contract::from_str("4HN", Vulnerable::Yes).unwrap()
+        );
+        // East opens 1C, West bids notrumps first.
+        assert_eq!(
+            contract("1C P 1NT P 2C P 3NT X P P XX AP", Seat::East),
+            Contract::from_str("3NWXX", Vulnerable::No).unwrap()
+        );
+        // South opened 1S, but West was the first of their side to name spades.
+        assert_eq!(
+            contract("1S 2S X 4S 5H P P 5S AP", Seat::South),
+            Contract::from_str("5SW", Vulnerable::No).unwrap()
+        );
+    }
+}
@@ -0,0 +1,474 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! A simple bidder driven by rules: after a given auction, with a hand matching a
+//! [`HandDescriptor`], make a given call. Bid thousands of deals to see how often a system
+//! reaches each contract and, with the `dds` feature, how far it lands from the double dummy
+//! par.
+
+use crate::bidding_system::read_entries;
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// A rule of an [`AutoBidder`]: after exactly the auction `prefix`, a player whose hand
+/// matches `hand` calls `bid`. When more rules apply, the one with the highest priority
+/// fires, and among rules with the same priority the one added first.
+#[derive(Debug, Clone)]
+pub struct BiddingRule {
+    prefix: Bidding,
+    hand: HandDescriptor,
+    bid: Bid,
+    priority: i32,
+    name: Option<String>,
+}
+
+impl BiddingRule {
+    /// A rule with priority 0.
+    #[must_use]
+    pub fn new(prefix: Bidding, hand: HandDescriptor, bid: Bid) -> Self {
+        Self {
+            prefix,
+            hand,
+            bid,
+            priority: 0,
+            name: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Names the rule, to recognise it in the reports.
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn prefix(&self) -> &Bidding {
+        &self.prefix
+    }
+
+    #[must_use]
+    pub fn hand(&self) -> &HandDescriptor {
+        &self.hand
+    }
+
+    #[must_use]
+    pub fn bid(&self) -> Bid {
+        self.bid
+    }
+
+    #[must_use]
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Whether the rule can fire after `auction` for `hand`.
+    fn applies(&self, auction: &Bidding, hand: Hand) -> bool {
+        self.prefix.iter().eq(auction.iter()) && auction.is_legal(self.bid) && self.hand.check(hand)
+    }
+}
+
+/// A bidder that makes, for every call, the one of the highest priority rule that applies,
+/// or passes if none does.
+///
+/// Rules can be read from text, one for each line in the form
+/// `auction => bid @priority: hand type | hand type | ...`, where the auction (empty for the
+/// opening bids) is written as in [`Bidding::from_str`], the priority is optional and the
+/// hand types are written as in [`crate::bidding_system`].
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let bidder = AutoBidder::from_str(
+///     "=> 1NT @1: balanced hcp 15-17
+///      => 1S: longest S hcp 12-21
+///      1NT P => 3NT: hcp 10-15",
+/// )?;
+/// let dealer = DealerBuilder::new()
+///     .with_hand_descriptor(Seat::North, HandTypeBuilder::balanced(15, 17).build().into())
+///     .with_hand_descriptor(Seat::South, HandType::builder().with_range(10, 15).build().into())
+///     .build()?;
+/// let report = bidder.run(&dealer, 10)?;
+/// for auction in report.auctions() {
+///     let (_, bid, rule) = auction.calls().next().unwrap();
+///     assert_eq!(bid, Bid::from_str("1NT")?);
+///     assert_eq!(bidder.rules()[rule.unwrap()].name(), Some("=> 1NT @1"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct AutoBidder {
+    rules: Vec<BiddingRule>,
+}
+
+impl AutoBidder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_rule(mut self, rule: BiddingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn add_rule(&mut self, rule: BiddingRule) {
+        self.rules.push(rule);
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &[BiddingRule] {
+        &self.rules
+    }
+
+    /// The index of the rule that fires after `auction` for `hand`, if any.
+    #[must_use]
+    pub fn choose(&self, auction: &Bidding, hand: Hand) -> Option<usize> {
+        let mut chosen: Option<(usize, i32)> = None;
+        for (index, rule) in self.rules.iter().enumerate() {
+            if chosen.is_some_and(|(_, priority)| priority >= rule.priority) {
+                continue;
+            }
+            if rule.applies(auction, hand) {
+                chosen = Some((index, rule.priority));
+            }
+        }
+        chosen.map(|(index, _)| index)
+    }
+
+    /// Bids `deal` from its dealer until the auction is over.
+    #[must_use]
+    pub fn bid(&self, deal: &Deal) -> AutoAuction {
+        let mut auction = Bidding::new();
+        let mut rules = Vec::new();
+        while !auction.is_finished() {
+            let seat = auction.next_to_bid(deal.dealer());
+            let rule = self.choose(&auction, deal.hands()[seat as usize]);
+            let call = rule.map_or(Bid::Pass, |rule| self.rules[rule].bid);
+            if auction.push(call).is_err() {
+                break;
+            }
+            rules.push(rule);
+        }
+        AutoAuction {
+            deal: deal.clone(),
+            auction,
+            rules,
+        }
+    }
+
+    /// Bids `deals` deals from `dealer`.
+    ///
+    /// # Errors
+    /// If the dealer is unable to deal.
+    pub fn run(&self, dealer: &impl Dealer, deals: usize) -> Result<BiddingReport, DealerError> {
+        let auctions = (0..deals)
+            .map(|_| Ok(self.bid(&dealer.deal()?)))
+            .collect::<Result<_, DealerError>>()?;
+        Ok(BiddingReport { auctions })
+    }
+}
+
+impl FromStr for AutoBidder {
+    type Err = DealerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bidder = Self::new();
+        for entry in read_entries(s, "bidding rules")? {
+            let Some((prefix, call)) = entry.head.split_once("=>") else {
+                return Err(entry.error("expected `auction => bid`"));
+            };
+            let prefix = Bidding::from_str(prefix).map_err(|e| entry.error(e))?;
+            let mut words = call.split_whitespace();
+            let bid =
+                Bid::from_str(words.next().unwrap_or_default()).map_err(|e| entry.error(e))?;
+            let priority = match words.next() {
+                Some(priority) => priority
+                    .strip_prefix('@')
+                    .and_then(|priority| priority.parse().ok())
+                    .ok_or_else(|| entry.error(format!("invalid priority {priority}")))?,
+                None => 0,
+            };
+            if let Some(word) = words.next() {
+                return Err(entry.error(format!("unexpected {word}")));
+            }
+            bidder.add_rule(
+                BiddingRule::new(prefix, entry.description.clone(), bid)
+                    .with_priority(priority)
+                    .with_name(entry.head),
+            );
+        }
+        Ok(bidder)
+    }
+}
+
+/// The auction an [`AutoBidder`] produced for a deal, with the rule that fired for every
+/// call.
+#[derive(Debug, Clone)]
+pub struct AutoAuction {
+    deal: Deal,
+    auction: Bidding,
+    rules: Vec<Option<usize>>,
+}
+
+impl AutoAuction {
+    #[must_use]
+    pub fn deal(&self) -> &Deal {
+        &self.deal
+    }
+
+    #[must_use]
+    pub fn auction(&self) -> &Bidding {
+        &self.auction
+    }
+
+    /// The index of the rule that fired for the `index`-th call, `None` for the passes made
+    /// because no rule applied.
+    #[must_use]
+    pub fn rule(&self, index: usize) -> Option<usize> {
+        self.rules.get(index).copied().flatten()
+    }
+
+    /// The calls with the seat that made them and the rule that fired.
+    pub fn calls(&self) -> impl Iterator<Item = (Seat, Bid, Option<usize>)> + '_ {
+        self.auction
+            .iter()
+            .zip(&self.rules)
+            .enumerate()
+            .map(|(index, (bid, rule))| (self.deal.dealer() + index, *bid, *rule))
+    }
+
+    /// The contract reached, `None` if passed out.
+    #[must_use]
+    pub fn contract(&self) -> Option<Contract> {
+        self.auction
+            .contract(self.deal.dealer(), self.deal.vulnerability())
+    }
+
+    /// Compares the double dummy result of the contract reached with the par of the deal.
+    ///
+    /// # Errors
+    /// If the double dummy solver fails.
+    #[cfg(feature = "dds")]
+    pub fn par_comparison(&self) -> Result<ParComparison, SqueezerError> {
+        use dds::{
+            doubledummy::DoubleDummySolver, par::ParCalculator, tables::DdTableCalculator,
+            traits::ContractScorer,
+        };
+
+        let solver = DoubleDummySolver::new();
+        let mut table = solver.calculate_complete_table(&self.deal)?;
+        let score = self.contract().map_or(0, |contract| {
+            let tricks = table.res_table[contract.strain() as usize][contract.declarer() as usize];
+            let score = contract.score(u8::try_from(tricks).unwrap_or_default());
+            if matches!(contract.declarer(), Seat::North | Seat::South) {
+                score
+            } else {
+                -score
+            }
+        });
+        let par = DoubleDummySolver::dealer_par_bin(
+            &mut table,
+            self.deal.dealer().into(),
+            self.deal.vulnerability().into(),
+        )?;
+        Ok(ParComparison {
+            score,
+            par_score: par.score,
+        })
+    }
+}
+
+/// The double dummy score of an auction and the par score of its deal, both from the point
+/// of view of North-South.
+#[cfg(feature = "dds")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParComparison {
+    score: i32,
+    par_score: i32,
+}
+
+#[cfg(feature = "dds")]
+impl ParComparison {
+    /// The double dummy score of the contract reached.
+    #[must_use]
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    #[must_use]
+    pub fn par_score(&self) -> i32 {
+        self.par_score
+    }
+
+    /// How much North-South gained over par: negative when the auction cost them points.
+    #[must_use]
+    pub fn difference(&self) -> i32 {
+        self.score - self.par_score
+    }
+}
+
+/// The auctions produced by [`AutoBidder::run`].
+#[derive(Debug, Clone, Default)]
+pub struct BiddingReport {
+    auctions: Vec<AutoAuction>,
+}
+
+impl BiddingReport {
+    #[must_use]
+    pub fn auctions(&self) -> &[AutoAuction] {
+        &self.auctions
+    }
+
+    /// How many times every contract was reached, `None` counting the passed out deals.
+    #[must_use]
+    pub fn contract_frequencies(&self) -> HashMap<Option<Contract>, usize> {
+        let mut frequencies = HashMap::new();
+        for auction in &self.auctions {
+            *frequencies.entry(auction.contract()).or_default() += 1;
+        }
+        frequencies
+    }
+
+    /// How many times every rule fired, by index.
+    #[must_use]
+    pub fn rule_frequencies(&self) -> HashMap<usize, usize> {
+        let mut frequencies = HashMap::new();
+        for rule in self
+            .auctions
+            .iter()
+            .flat_map(|auction| auction.rules.iter().flatten())
+        {
+            *frequencies.entry(*rule).or_default() += 1;
+        }
+        frequencies
+    }
+
+    /// Compares every auction with the par of its deal.
+    ///
+    /// # Errors
+    /// If the double dummy solver fails.
+    #[cfg(feature = "dds")]
+    pub fn par_comparisons(&self) -> Result<Vec<ParComparison>, SqueezerError> {
+        self.auctions
+            .iter()
+            .map(AutoAuction::par_comparison)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RULES: &str = "
+        => 1NT @1: balanced hcp 15-17
+        => 1S: longest S hcp 12-21
+        => 1H: longest H hcp 12-21
+        1S P => 4S: 4+xxx hcp 13-15
+        1S P => 2S: 3+xxx hcp 6-10
+        1S P 2S P => 4S: hcp 17-21
+        1NT P => 3NT: hcp 10-15
+        1NT P => 2C @1: 4xxx hcp 8+ | x4xx hcp 8+
+    ";
+
+    #[test]
+    fn fires_rules_test() {
+        let bidder = AutoBidder::from_str(RULES).unwrap();
+        assert_eq!(bidder.rules().len(), 8);
+        assert_eq!(bidder.rules()[7].priority(), 1);
+        let deal = Deal::from_hands(
+            [
+                "AKQ32 A32 32 432",
+                "T98 KQ4 AT9 AT98",
+                "J54 JT5 Q876 KQJ",
+                "76 9876 KJ54 765",
+            ]
+            .map(|hand| Hand::from_str(hand).unwrap()),
+        );
+        let auction = bidder.bid(&deal);
+        assert!(auction.auction().is_finished());
+        let fired: Vec<_> = auction.calls().map(|(_, _, rule)| rule).collect();
+        assert_eq!(fired, [Some(1), None, Some(4), None, None, None]);
+        assert_eq!(auction.calls().nth(2).unwrap().0, Seat::South);
+        assert_eq!(
+            auction.contract(),
+            Some(Contract::from_str("2SN", Vulnerable::No).unwrap())
+        );
+    }
+
+    #[test]
+    fn priority_test() {
+        let bidder = AutoBidder::from_str(RULES).unwrap();
+        let opening = Bidding::new();
+        let strong_spades = Hand::from_str("AKJ32 A32 K2 Q32").unwrap();
+        assert_eq!(bidder.choose(&opening, strong_spades), Some(0));
+        let weak = Hand::from_str("J5432 432 32 432").unwrap();
+        assert_eq!(bidder.choose(&opening, weak), None);
+    }
+
+    #[test]
+    fn report_test() {
+        let bidder = AutoBidder::from_str(RULES).unwrap();
+        let dealer = DealerBuilder::new()
+            .with_hand_descriptor(
+                Seat::North,
+                HandTypeBuilder::balanced(15, 17).build().into(),
+            )
+            .build()
+            .unwrap();
+        let report = bidder.run(&dealer, 50).unwrap();
+        assert_eq!(report.auctions().len(), 50);
+        assert_eq!(report.rule_frequencies()[&0], 50);
+        assert_eq!(report.contract_frequencies().values().sum::<usize>(), 50);
+    }
+
+    #[cfg(feature = "dds")]
+    #[test]
+    fn par_comparison_test() {
+        let deal = Deal::from_hands(
+            [
+                "AKQJ AKQ AKQ AKQ",
+                "T98 JT9 JT9 JT98",
+                "765 8765 8765 76",
+                "432 432 432 5432",
+            ]
+            .map(|hand| Hand::from_str(hand).unwrap()),
+        );
+        let passed = AutoBidder::new().bid(&deal);
+        assert!(passed.auction().is_passed_out());
+        let comparison = passed.par_comparison().unwrap();
+        assert_eq!(comparison.score(), 0);
+        assert_eq!(comparison.par_score(), 1520);
+        assert_eq!(comparison.difference(), -1520);
+
+        let bidder = AutoBidder::from_str("=> 7NT: hcp 37").unwrap();
+        let comparison = bidder.bid(&deal).par_comparison().unwrap();
+        assert_eq!(comparison.score(), 1520);
+        assert_eq!(comparison.difference(), 0);
+    }
+
+    #[test]
+    fn rules_errors_test() {
+        assert!(AutoBidder::from_str("1NT: hcp 15-17").is_err());
+        assert!(AutoBidder::from_str("=> 1Z: hcp 15-17").is_err());
+        assert!(AutoBidder::from_str("=> 1NT 1: hcp 15-17").is_err());
+        assert!(AutoBidder::from_str("=> 1NT @1 @2: hcp 15-17").is_err());
+    }
+}
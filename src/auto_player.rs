@@ -0,0 +1,219 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+use dds::{
+    doubledummy::MultiThreadDoubleDummySolver,
+    future_tricks::FutureTricks,
+    solver::BridgeSolver,
+    utils::{Mode, Solutions, Target},
+};
+
+/// How an [`AutoPlayer`] chooses among cards that are equally good double dummy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Any of the cards, at random.
+    #[default]
+    Random,
+    /// The lowest ranked card.
+    Lowest,
+    /// The highest ranked card.
+    Highest,
+}
+
+impl TieBreak {
+    /// Chooses one of `cards`, `None` if there are none.
+    #[must_use]
+    pub fn choose(self, mut cards: Cards) -> Option<Card> {
+        match self {
+            TieBreak::Random => cards.pick(1)?.into_iter().next(),
+            TieBreak::Lowest => cards.into_iter().min_by_key(|card| card.rank()),
+            TieBreak::Highest => cards.into_iter().max_by_key(|card| card.rank()),
+        }
+    }
+}
+
+/// A bot playing the cards of all four players double dummy: every card it plays keeps the
+/// best result for the side on turn, found with DDS.
+///
+/// It can be used to produce complete play records for deals, as a baseline opponent or to
+/// finish the play of a deal from any point.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let deal = Deal::new();
+/// let contract = Contract::from_str("3NS", Vulnerable::No)?;
+/// let mut play = PlayState::new(&deal, contract);
+/// AutoPlayer::new()
+///     .with_tie_break(TieBreak::Lowest)
+///     .play_to_end(&mut play)?;
+/// assert_eq!(play.played().len(), 52);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoPlayer {
+    tie_break: TieBreak,
+}
+
+impl AutoPlayer {
+    /// A player choosing at random among equally good cards.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    #[must_use]
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+
+    /// The cards of the player on turn that keep the best double dummy result for their side.
+    ///
+    /// # Errors
+    /// If the double dummy solver fails.
+    pub fn optimal_cards(&self, state: &PlayState) -> Result<Cards, SqueezerError> {
+        let legal = state.legal_cards();
+        if legal.len() <= 1 {
+            return Ok(legal);
+        }
+        let solver = MultiThreadDoubleDummySolver::new();
+        let future_tricks = solver.solve_with_params(
+            state,
+            state,
+            Mode::default(),
+            Solutions::AllOptimal,
+            Target::MaxTricks,
+        )?;
+        let best = future_tricks
+            .score()
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or_default();
+        let optimal = scored_cards(&future_tricks)?
+            .into_iter()
+            .filter(|&(_, score)| score == best)
+            .fold(Cards::EMPTY, |optimal, (card, _)| optimal.insert(card));
+        Ok(optimal & legal)
+    }
+
+    /// The card the player on turn plays.
+    ///
+    /// # Errors
+    /// If the play is over or the double dummy solver fails.
+    pub fn choose(&self, state: &PlayState) -> Result<Card, SqueezerError> {
+        self.tie_break
+            .choose(self.optimal_cards(state)?)
+            .ok_or_else(|| {
+                PlayError::new(state.next_to_play(), None, PlayErrorKind::Finished).into()
+            })
+    }
+
+    /// Plays the cards of every player until the end of the deal.
+    ///
+    /// # Errors
+    /// If the play is already over or the double dummy solver fails.
+    pub fn play_to_end(&self, state: &mut PlayState) -> Result<(), SqueezerError> {
+        loop {
+            let card = self.choose(state)?;
+            state.play(card)?;
+            if state.is_finished() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Plays `contract` on `deal` from the opening lead to the last trick.
+    ///
+    /// # Errors
+    /// If the double dummy solver fails.
+    pub fn play_out(&self, deal: &Deal, contract: Contract) -> Result<PlaySequence, SqueezerError> {
+        let mut state = PlayState::new(deal, contract);
+        self.play_to_end(&mut state)?;
+        Ok(state.to_play_sequence())
+    }
+}
+
+/// The cards evaluated by the double dummy solver with their score, the lower cards
+/// equivalent to a returned card included.
+///
+/// # Errors
+/// If the solver returns an unknown suit.
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+pub(crate) fn scored_cards(
+    future_tricks: &FutureTricks,
+) -> Result<Vec<(Card, i32)>, SqueezerError> {
+    let mut cards = Vec::with_capacity(RANKS as usize);
+    for index in 0..future_tricks.cards as usize {
+        let suit = Suit::try_from(future_tricks.suit[index])?;
+        let rank = future_tricks.rank[index] as u8;
+        let score = future_tricks.score[index];
+        // Lower cards equivalent to the one returned score the same.
+        let equals = (2..rank).filter(|equal| future_tricks.equals[index] & (1 << equal) != 0);
+        cards.extend(
+            std::iter::once(rank)
+                .chain(equals)
+                .map(|rank| (Card::new(suit, rank), score)),
+        );
+    }
+    Ok(cards)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tie_break_test() {
+        let cards = Cards::from_str("SQ3 HK CT").unwrap();
+        assert_eq!(TieBreak::Lowest.choose(cards), Some(Card::S3));
+        assert_eq!(TieBreak::Highest.choose(cards), Some(Card::HK));
+        for _ in 0..10 {
+            assert!(cards.contains(TieBreak::Random.choose(cards).unwrap()));
+        }
+        assert_eq!(TieBreak::Random.choose(Cards::EMPTY), None);
+    }
+
+    #[test]
+    fn forced_ending_test() {
+        // A one card ending needs no solving: every card is forced.
+        let hands = ["SA", "S2", "HA", "D2"].map(|cards| Cards::from_str(cards).unwrap());
+        let contract = Contract::from_str("1NS", Vulnerable::No).unwrap();
+        let mut play = PlayState::with_hands(hands, contract);
+        AutoPlayer::new().play_to_end(&mut play).unwrap();
+        assert_eq!(play.played(), [Card::D2, Card::SA, Card::S2, Card::HA]);
+        assert_eq!(play.trick_winners(), [Seat::West]);
+        assert!(AutoPlayer::new().choose(&play).is_err());
+    }
+
+    #[test]
+    fn play_out_test() {
+        let deal = Deal::from_hands([
+            Hand::from_str("AKQJ T98 765 432").unwrap(),
+            Hand::from_str("T98 765 432 AKQJ").unwrap(),
+            Hand::from_str("765 432 AKQJ T98").unwrap(),
+            Hand::from_str("432 AKQJ T98 765").unwrap(),
+        ]);
+        let contract = Contract::from_str("1NS", Vulnerable::No).unwrap();
+        let player = AutoPlayer::new().with_tie_break(TieBreak::Highest);
+        let mut play = PlayState::new(&deal, contract);
+        player.play_to_end(&mut play).unwrap();
+        assert_eq!(play.played().len(), 52);
+        // Playing double dummy, the declarer takes exactly the double dummy tricks.
+        let tricks = MultiThreadDoubleDummySolver::new()
+            .dd_tricks(&deal, &contract)
+            .unwrap();
+        assert_eq!(play.declarer_tricks(), tricks);
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Bidding systems written as data: every auction prefix is mapped to the hands the player
+//! who made its last call can hold.
+//!
+//! A system is a text file with one line for every prefix, in the form
+//! `auction: hand type | hand type | ...`, where the auction is written as in
+//! [`Bidding::from_str`] and a hand type is a list of words:
+//! - a shape pattern, like `(4432)` or `5-5-5-6`, adds the shapes;
+//! - `!` followed by a pattern, like `!(5x)(2+2+)`, removes them;
+//! - `balanced` adds the balanced shapes;
+//! - `longest` followed by a suit (`S`, `H`, `D`, `C`) accepts the hands where that suit is
+//!   the longest one, before any other shape is added or removed;
+//! - `hcp` followed by a range, like `11-14`, `18+` or `7`, sets the high card points.
+//!
+//! Empty lines and everything after a `#` are ignored.
+//!
+//! ```text
+//! # Polish Club
+//! 1C:     (4432) (4333) (4414) (5332) !(5x)(2+2+) hcp 11-14
+//!       | xxxx hcp 18+
+//!       | longest C !(4x)x5 !(5-5-5-)6+ hcp 11-14
+//! 1C P 1D: hcp 0-6
+//! ```
+//!
+//! A line starting with `|` continues the hand types of the line before.
+
+use crate::prelude::*;
+
+/// A node of the tree of auction prefixes: the hands shown by the last call and the calls
+/// that can follow.
+#[derive(Debug, Default, Clone)]
+struct SystemNode {
+    description: Option<HandDescriptor>,
+    children: Vec<(Bid, SystemNode)>,
+}
+
+impl SystemNode {
+    fn child(&self, bid: Bid) -> Option<&Self> {
+        self.children
+            .iter()
+            .find(|(child, _)| *child == bid)
+            .map(|(_, node)| node)
+    }
+
+    fn child_mut(&mut self, bid: Bid) -> &mut Self {
+        let index = if let Some(index) = self.children.iter().position(|(child, _)| *child == bid) {
+            index
+        } else {
+            self.children.push((bid, Self::default()));
+            self.children.len() - 1
+        };
+        &mut self.children[index].1
+    }
+}
+
+/// A bidding system, as a tree of auction prefixes mapped to the [`HandDescriptor`] of the
+/// player who made the last call. Read it from the text format described in
+/// [`crate::bidding_system`] with [`BiddingSystem::from_str`], or build it with
+/// [`BiddingSystem::insert`].
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let system = BiddingSystem::from_str(
+///     "1NT: balanced hcp 15-17
+///      1NT P 2C: hcp 8+ | longest S hcp 0-7 | longest H hcp 0-7",
+/// )?;
+/// let auction = Bidding::from_str("1NT P 2C")?;
+/// let [north, _, south, _] = system.hand_descriptors(&auction, Seat::North);
+/// let dealer = DealerBuilder::new()
+///     .with_hand_descriptor(Seat::North, north.unwrap())
+///     .with_hand_descriptor(Seat::South, south.unwrap())
+///     .build()?;
+/// let deal = dealer.deal()?;
+/// assert!((15..=17).contains(&deal.north().hcp()));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct BiddingSystem {
+    root: SystemNode,
+}
+
+impl BiddingSystem {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the auction `prefix` to the hands that its last call shows, replacing the
+    /// previous description, if any.
+    pub fn insert(&mut self, prefix: &Bidding, description: HandDescriptor) {
+        let node = prefix
+            .iter()
+            .fold(&mut self.root, |node, &bid| node.child_mut(bid));
+        node.description = Some(description);
+    }
+
+    /// The hands shown by the last call of `prefix`, if the system describes it.
+    #[must_use]
+    pub fn description(&self, prefix: &Bidding) -> Option<&HandDescriptor> {
+        prefix
+            .iter()
+            .try_fold(&self.root, |node, &bid| node.child(bid))?
+            .description
+            .as_ref()
+    }
+
+    /// The calls the system describes after `prefix`.
+    #[must_use]
+    pub fn continuations(&self, prefix: &Bidding) -> Vec<Bid> {
+        prefix
+            .iter()
+            .try_fold(&self.root, |node, &bid| node.child(bid))
+            .map(|node| node.children.iter().map(|(bid, _)| *bid).collect())
+            .unwrap_or_default()
+    }
+
+    /// The hands of the four players after `auction`, indexed by [`Seat`]: every player's
+    /// description is the intersection of the descriptions of the calls they made. Calls the
+    /// system does not describe show nothing, so players without described calls get `None`.
+    ///
+    /// The result can be given to [`DealerBuilder::with_hand_descriptors_all`].
+    #[must_use]
+    pub fn hand_descriptors(
+        &self,
+        auction: &Bidding,
+        dealer: Seat,
+    ) -> [Option<HandDescriptor>; NUMBER_OF_HANDS] {
+        let mut descriptors: [Option<HandDescriptor>; NUMBER_OF_HANDS] = Default::default();
+        let mut node = Some(&self.root);
+        for (index, &bid) in auction.iter().enumerate() {
+            node = node.and_then(|node| node.child(bid));
+            let Some(description) = node.and_then(|node| node.description.as_ref()) else {
+                continue;
+            };
+            let descriptor = &mut descriptors[(dealer + index) as usize];
+            *descriptor = Some(match descriptor.take() {
+                Some(shown) => shown.intersection(description),
+                None => description.clone(),
+            });
+        }
+        descriptors
+    }
+}
+
+impl FromStr for BiddingSystem {
+    type Err = DealerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut system = Self::new();
+        for entry in read_entries(s, "bidding system")? {
+            let prefix = Bidding::from_str(entry.head).map_err(|e| entry.error(e))?;
+            if prefix.is_empty() {
+                return Err(entry.error("empty auction"));
+            }
+            system.insert(&prefix, entry.description);
+        }
+        Ok(system)
+    }
+}
+
+/// A line of a file in the format of [`crate::bidding_system`]: what comes before the colon
+/// and the hand types after it.
+pub(crate) struct Entry<'a> {
+    pub(crate) head: &'a str,
+    pub(crate) description: HandDescriptor,
+    line: usize,
+    file: &'static str,
+}
+
+impl Entry<'_> {
+    /// An error for this line.
+    pub(crate) fn error(&self, reason: impl fmt::Display) -> DealerError {
+        DealerError::new(format!("line {} of the {}: {reason}", self.line, self.file))
+    }
+}
+
+/// Reads the lines `head: hand type | hand type | ...` of `s`, with comments and
+/// continuation lines as described in [`crate::bidding_system`]. `file` names the kind of
+/// file in the errors.
+pub(crate) fn read_entries<'a>(
+    s: &'a str,
+    file: &'static str,
+) -> Result<Vec<Entry<'a>>, DealerError> {
+    let mut lines: Vec<(usize, &str, Vec<&str>)> = Vec::new();
+    for (number, line) in s.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let error =
+            |reason: &str| DealerError::new(format!("line {} of the {file}: {reason}", number + 1));
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(hand_types) = line.strip_prefix('|') {
+            let Some((_, _, previous)) = lines.last_mut() else {
+                return Err(error("nothing to continue"));
+            };
+            previous.extend(hand_types.split('|'));
+            continue;
+        }
+        let Some((head, hand_types)) = line.split_once(':') else {
+            return Err(error("expected a colon before the hand types"));
+        };
+        lines.push((number + 1, head.trim(), hand_types.split('|').collect()));
+    }
+    lines
+        .into_iter()
+        .map(|(line, head, hand_types)| {
+            let mut entry = Entry {
+                head,
+                description: HandDescriptor::default(),
+                line,
+                file,
+            };
+            entry.description = HandDescriptor::new(
+                hand_types
+                    .into_iter()
+                    .map(parse_hand_type)
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| entry.error(e))?,
+            );
+            Ok(entry)
+        })
+        .collect()
+}
+
+/// Reads a hand type written as in [`crate::bidding_system`].
+fn parse_hand_type(text: &str) -> Result<HandType, DealerError> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut builder = HandTypeBuilder::new();
+    // `longest` replaces the shapes, so it goes first.
+    if let Some(index) = words.iter().position(|word| *word == "longest") {
+        let suit = match words.get(index + 1).copied() {
+            Some("S" | "s" | "♠") => Suit::Spades,
+            Some("H" | "h" | "♥") => Suit::Hearts,
+            Some("D" | "d" | "♦") => Suit::Diamonds,
+            Some("C" | "c" | "♣") => Suit::Clubs,
+            _ => return Err(DealerError::new("`longest` must be followed by a suit")),
+        };
+        builder.with_longest(suit);
+    }
+    let mut words = words.into_iter();
+    while let Some(word) = words.next() {
+        match word {
+            "longest" => {
+                words.next();
+            }
+            "balanced" => {
+                for pattern in ["(4333)", "(4432)", "(5332)"] {
+                    builder.add_shape(pattern)?;
+                }
+            }
+            "hcp" => {
+                let range = words
+                    .next()
+                    .ok_or_else(|| DealerError::new("`hcp` must be followed by a range"))?;
+                let (min, max) = parse_hcp_range(range)?;
+                builder.with_range(min, max);
+            }
+            pattern => {
+                if let Some(pattern) = pattern.strip_prefix('!') {
+                    builder
+                        .remove_shape(pattern)
+                        .map_err(|e| DealerError::new(e.to_string()))?;
+                } else {
+                    builder.add_shape(pattern)?;
+                }
+            }
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Reads a range of high card points: `11-14`, `18+` or `7`.
+fn parse_hcp_range(range: &str) -> Result<(u8, u8), DealerError> {
+    let error = || DealerError::new(format!("invalid hcp range: {range}"));
+    let hcp = |hcp: &str| hcp.parse::<u8>().map_err(|_| error());
+    let (min, max) = if let Some(min) = range.strip_suffix('+') {
+        (hcp(min)?, MAX_HCP_IN_HAND)
+    } else if let Some((min, max)) = range.split_once('-') {
+        (hcp(min)?, hcp(max)?)
+    } else {
+        (hcp(range)?, hcp(range)?)
+    };
+    if min > max || max > MAX_HCP_IN_HAND {
+        return Err(error());
+    }
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POLISH_CLUB: &str = "
+        # Polish Club
+        1C:     (4432) (4333) (4414) (5332) !(5x)(2+2+) hcp 11-14
+              | xxxx hcp 18+
+              | longest C !(4x)x5 !(5-5-5-)6+ hcp 11-14
+        1C P 1D: hcp 0-6   # negative
+        1C P 1D P 1NT: balanced hcp 18-19
+    ";
+
+    #[test]
+    fn polish_club_as_data_test() {
+        let system = BiddingSystem::from_str(POLISH_CLUB).unwrap();
+        let from_data = system
+            .description(&Bidding::from_str("1C").unwrap())
+            .unwrap();
+        let from_code = polish_club_hand_descriptor();
+        for _ in 0..1000 {
+            let deal = Deal::new();
+            for hand in deal.hands() {
+                assert_eq!(from_data.check(*hand), from_code.check(*hand), "{hand}");
+            }
+        }
+        assert_eq!(
+            system.continuations(&Bidding::from_str("1C P").unwrap()),
+            [Bid::from_str("1D").unwrap()]
+        );
+    }
+
+    #[test]
+    fn hand_descriptors_test() {
+        let system = BiddingSystem::from_str(POLISH_CLUB).unwrap();
+        let auction = Bidding::from_str("1C P 1D P 1NT").unwrap();
+        let descriptors = system.hand_descriptors(&auction, Seat::South);
+        assert!(descriptors[Seat::West as usize].is_none());
+        assert!(descriptors[Seat::East as usize].is_none());
+        let dealer = DealerBuilder::new()
+            .with_hand_descriptors_all(descriptors)
+            .build()
+            .unwrap();
+        for _ in 0..20 {
+            let deal = dealer.deal().unwrap();
+            let opener = deal.south();
+            assert!((18..=19).contains(&opener.hcp()), "{deal}");
+            assert!(deal.north().hcp() <= 6, "{deal}");
+        }
+    }
+
+    #[test]
+    fn system_errors_test() {
+        assert!(BiddingSystem::from_str("1C hcp 11-14").is_err());
+        assert!(BiddingSystem::from_str("1C 1C: hcp 11-14").is_err());
+        assert!(BiddingSystem::from_str(": hcp 11-14").is_err());
+        assert!(BiddingSystem::from_str("1C: hcp 14-11").is_err());
+        assert!(BiddingSystem::from_str("1C: hcp").is_err());
+        assert!(BiddingSystem::from_str("1C: longest").is_err());
+        assert!(BiddingSystem::from_str("1C: 44").is_err());
+        assert!(BiddingSystem::from_str("| hcp 11-14").is_err());
+    }
+}
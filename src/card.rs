@@ -29,6 +29,17 @@ impl std::fmt::Display for Card {
     }
 }
 
+#[cfg(feature = "dds")]
+impl dds::traits::AsDDSCard for Card {
+    #[allow(clippy::missing_panics_doc)]
+    fn as_card(&self) -> (dds::deal::DdsRank, dds::deal::DdsSuit) {
+        (
+            dds::deal::DdsRank::new(1 << self.rank()).expect("ranks go from 2 to 14"),
+            dds::deal::DdsSuit::try_from(self.suit() as u8).expect("suits go from 0 to 3"),
+        )
+    }
+}
+
 impl Card {
     #[must_use]
     #[inline]
@@ -760,71 +771,9 @@ impl std::str::FromStr for Card {
 impl std::str::FromStr for Cards {
     type Err = DealerError;
 
+    /// Reads cards in the [`CardNotation::ENGLISH`] notation.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut cards = Cards::EMPTY;
-        let mut suit = Suit::Spades;
-        for c in s.chars() {
-            match c {
-                '♣' | 'C' | 'c' => {
-                    suit = Suit::Clubs;
-                }
-                '♦' | 'D' | 'd' => {
-                    suit = Suit::Diamonds;
-                }
-                '♥' | 'H' | 'h' => {
-                    suit = Suit::Hearts;
-                }
-                '♠' | 'S' | 's' => {
-                    suit = Suit::Spades;
-                }
-                ' ' | '.' => {
-                    suit = suit.next().ok_or(DealerError::new(
-                        "more than 4 suits, check input string for spaces",
-                    ))?;
-                }
-                '2' => {
-                    cards = cards.insert(Card::new(suit, 2));
-                }
-                '3' => {
-                    cards = cards.insert(Card::new(suit, 3));
-                }
-                '4' => {
-                    cards = cards.insert(Card::new(suit, 4));
-                }
-                '5' => {
-                    cards = cards.insert(Card::new(suit, 5));
-                }
-                '6' => {
-                    cards = cards.insert(Card::new(suit, 6));
-                }
-                '7' => {
-                    cards = cards.insert(Card::new(suit, 7));
-                }
-                '8' => {
-                    cards = cards.insert(Card::new(suit, 8));
-                }
-                '9' => {
-                    cards = cards.insert(Card::new(suit, 9));
-                }
-                'T' | '1' => {
-                    cards = cards.insert(Card::new(suit, 10));
-                }
-                'J' => {
-                    cards = cards.insert(Card::new(suit, 11));
-                }
-                'Q' => {
-                    cards = cards.insert(Card::new(suit, 12));
-                }
-                'K' => {
-                    cards = cards.insert(Card::new(suit, 13));
-                }
-                'A' => {
-                    cards = cards.insert(Card::new(suit, 14));
-                }
-                _ => (),
-            }
-        }
-        Ok(cards)
+        CardNotation::ENGLISH.parse_cards(s)
     }
 }
 impl From<u64> for Cards {
@@ -184,18 +184,12 @@ impl PartialOrd for Strain {
 impl Ord for Strain {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         use std::cmp::Ordering;
-        if let Strain::NoTrumps = self {
-            match other {
-                Strain::NoTrumps => Ordering::Equal,
-                _ => Ordering::Greater,
-            }
-        } else {
-            let comp = (*self as u8).cmp(&(*other as u8));
-            match comp {
-                Ordering::Less => Ordering::Greater,
-                Ordering::Greater => Ordering::Less,
-                Ordering::Equal => comp,
-            }
+        match (self, other) {
+            (Strain::NoTrumps, Strain::NoTrumps) => Ordering::Equal,
+            (Strain::NoTrumps, _) => Ordering::Greater,
+            (_, Strain::NoTrumps) => Ordering::Less,
+            // Spades have the lowest discriminant and are the highest suit.
+            _ => (*other as u8).cmp(&(*self as u8)),
         }
     }
 }
@@ -245,6 +239,12 @@ impl Contract {
         self.level
     }
 
+    /// Returns whether the contract is doubled or redoubled.
+    #[must_use]
+    pub fn doubled(&self) -> Doubled {
+        self.doubled
+    }
+
     /// Returns the declarer of the contract.
     #[must_use]
     pub fn declarer(&self) -> Seat {
@@ -362,7 +362,7 @@ impl TryFrom<char> for Strain {
 }
 
 impl Strain {
-    fn not_unicode_str(self) -> String {
+    pub(crate) fn not_unicode_str(self) -> String {
         match self {
             Self::Spades => String::from("S"),
             Self::Hearts => String::from("H"),
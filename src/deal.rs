@@ -1,7 +1,7 @@
 // Copyright (C) 2024 Alvaro Gaiotti
 // See end of file for license information
 
-use std::{cell::Cell, num::NonZeroU8};
+use std::{cell::Cell, num::NonZeroU32, ops::RangeInclusive};
 
 use crate::prelude::*;
 
@@ -152,7 +152,26 @@ impl From<Seat> for dds::deal::DdsHandEncoding {
         }
     }
 }
+
+#[cfg(feature = "dds")]
+impl From<Vulnerability> for dds::tables::VulnerabilityEncoding {
+    fn from(value: Vulnerability) -> Self {
+        match value {
+            Vulnerability::None => Self::None,
+            Vulnerability::NS => Self::Ns,
+            Vulnerability::EW => Self::Ew,
+            Vulnerability::All => Self::Both,
+        }
+    }
+}
 impl Seat {
+    /// The dealer of the board `number`: North deals board 1, East board 2 and so on.
+    #[must_use]
+    #[inline]
+    pub fn dealer_for_board(number: u32) -> Seat {
+        Seat::from(number.wrapping_sub(1))
+    }
+
     ///Returns the next seat in a cyclic manner in this order: North, East, South, West
     #[must_use]
     #[inline]
@@ -318,6 +337,13 @@ impl Vulnerability {
     pub const fn from_number(board_number: u8) -> Self {
         Self::VULNERABILITY_TABLE[((board_number - 1) % 16) as usize]
     }
+    #[inline]
+    #[must_use]
+    /// Like [`Vulnerability::from_number`], for any board number.
+    /// Board 0 is treated as board 16.
+    pub const fn for_board(board_number: u32) -> Self {
+        Self::VULNERABILITY_TABLE[(board_number.wrapping_sub(1) % 16) as usize]
+    }
 }
 
 /// Iterator over the Vulnerability state.
@@ -554,19 +580,35 @@ impl DealerBuilder {
         self
     }
 
+    /// Exact distribution of shapes and HCP of the hand that will be dealt to `seat`,
+    /// taking into account the cards predealt so far. See [`HandDistribution`].
+    /// Cross hand constraints and hand descriptors are not considered.
+    /// # Errors
+    /// When the deck doesn't contain enough cards to complete the hand.
+    #[inline]
+    pub fn hand_distribution(&self, seat: Seat) -> Result<HandDistribution, DealerError> {
+        let predealt = self.predealt_hands[seat as usize].unwrap_or(Cards::EMPTY);
+        HandDistribution::with_predeal(predealt, self.deck)
+    }
+
     /// Builds the Dealer.
     /// # Errors
     /// This will method will return an error if you try to predeal the same card twice.
     #[inline]
     pub fn build(self) -> Result<impl Dealer, DealerError> {
-        Ok(StandardDealer {
+        Ok(self.build_standard())
+    }
+
+    /// Builds the Dealer, keeping its concrete type.
+    pub(crate) fn build_standard(self) -> StandardDealer {
+        StandardDealer {
             predeal: self.predealt_hands,
             vulnerability: self.vulnerability,
             deck_starting_state: self.deck,
             accept_function: self.accept,
             hand_constraints: self.hand_descriptors,
             ..Default::default()
-        })
+        }
     }
 }
 
@@ -574,11 +616,21 @@ pub trait Dealer {
     /// # Errors
     /// Errors if is unable to deal a [`Deal`]
     fn deal(&self) -> Result<Deal, DealerError>;
+
+    /// Deals a set of boards, numbered as in `numbers`, each one with the dealer and the
+    /// vulnerability of its number.
+    /// # Errors
+    /// Errors if is unable to deal a [`Deal`]
+    fn deal_boards(&self, numbers: RangeInclusive<u32>) -> Result<Vec<Deal>, DealerError> {
+        numbers
+            .map(|number| Ok(self.deal()?.with_number(number)))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
 pub enum BoardNumbering {
-    Sequential(Cell<NonZeroU8>),
+    Sequential(Cell<NonZeroU32>),
     OutputAlwaysOne,
 }
 
@@ -656,23 +708,16 @@ impl Dealer for StandardDealer {
             let hands = Hands { hands };
             !((self.accept_function)(&hands) && self.constraints_respected(hands.hands()))
         } {}
-        Ok(Deal {
-            hands,
-            number: match self.output_as_subsequent {
-                BoardNumbering::Sequential(ref num) => {
-                    let actual = num.get().get();
-                    num.set(match actual {
-                        // SAFETY: Just checked
-                        1..=127 => unsafe { NonZeroU8::new_unchecked(actual + 1) },
-                        // SAFETY: Literal 1.
-                        _ => unsafe { NonZeroU8::new_unchecked(1) },
-                    });
-                    actual
-                }
-                BoardNumbering::OutputAlwaysOne => 1,
-            },
-            ..Default::default()
-        })
+        let mut deal = Deal::from_hands(hands);
+        match self.output_as_subsequent {
+            BoardNumbering::Sequential(ref num) => {
+                let actual = num.get();
+                num.set(actual.checked_add(1).unwrap_or(NonZeroU32::MIN));
+                deal.set_number(actual.get());
+            }
+            BoardNumbering::OutputAlwaysOne => deal.set_vuln(self.vulnerability),
+        }
+        Ok(deal)
     }
 }
 
@@ -692,40 +737,50 @@ impl StandardDealer {
     }
 }
 
-/// State tracker for the deal print output.
-#[derive(Default, Debug, Copy, Clone)]
-#[non_exhaustive]
-pub enum Printer {
-    Pbn,
-    Lin,
-    #[default]
-    Short,
-    Long,
-}
-
 ///The central struct of the module: represents a bridge deal, with
-///cards, vulnerability, ecc.
-/// TODO: Should have a number, a dealer, a contract, ecc.
+///cards, board number, dealer, vulnerability and, if played, contract and auction.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Deal {
     vulnerability: Vulnerability,
     hands: [Hand; NUMBER_OF_HANDS],
+    number: u32,
+    dealer: Seat,
+    contract: Option<Contract>,
     #[cfg_attr(feature = "serde", serde(skip))]
-    printer: Printer,
-    number: u8,
+    auction: Option<Bidding>,
+}
+
+/// The cards of `hands` as read by the double dummy solver: for every seat and suit, a mask
+/// with the bit of the rank of every card held.
+#[cfg(feature = "dds")]
+pub(crate) fn dds_cards(hands: [Cards; NUMBER_OF_HANDS]) -> [[u32; SUITS]; NUMBER_OF_HANDS] {
+    let mut cards = [[0; SUITS]; NUMBER_OF_HANDS];
+    for (seat, hand) in hands.into_iter().enumerate() {
+        for &suit in Suit::ALL {
+            cards[seat][suit as usize] = hand
+                .in_suit(suit)
+                .into_iter()
+                .map(|card| 1 << card.rank())
+                .sum();
+        }
+    }
+    cards
 }
 
 #[cfg(feature = "dds")]
 impl dds::deal::AsDDSDeal for Deal {
     fn to_dds_deal(&self) -> dds::deal::DDSDealRepr {
-        let mut remain_cards = [[0; 4]; 4];
-        for (seat, hand) in self.into_iter().enumerate() {
-            for (index, suit) in hand.into_iter().enumerate() {
-                remain_cards[seat][index] = suit.into_iter().map(|card| 1 << card.rank()).sum();
-            }
+        dds_cards(self.hands.map(|hand| hand.as_cards())).into()
+    }
+}
+
+#[cfg(feature = "dds")]
+impl From<&Deal> for dds::tables::DdTableDeal {
+    fn from(deal: &Deal) -> Self {
+        Self {
+            cards: dds_cards(deal.hands.map(|hand| hand.as_cards())),
         }
-        remain_cards.into()
     }
 }
 
@@ -743,8 +798,10 @@ impl Deal {
         Self {
             vulnerability: Vulnerability::None,
             hands: Self::deal(),
-            printer: Printer::Short,
             number: 1,
+            dealer: Seat::North,
+            contract: None,
+            auction: None,
         }
     }
 
@@ -770,6 +827,22 @@ impl Deal {
         [north, east, south, west]
     }
 
+    /// A new `Deal` with the given hands, in the order North, East, South, West.
+    /// It's board 1: use [`Deal::with_number`] to change it.
+    #[must_use]
+    pub fn from_hands(hands: [Hand; NUMBER_OF_HANDS]) -> Self {
+        Self {
+            hands,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn hands(&self) -> &[Hand; NUMBER_OF_HANDS] {
+        &self.hands
+    }
+
     #[must_use]
     #[inline]
     pub fn check<T: Fn(&Deal) -> bool>(&self, f: T) -> bool {
@@ -783,189 +856,127 @@ impl Deal {
 
     #[must_use]
     #[inline]
-    pub fn west(&self) -> Hand {
-        self.hands[3]
+    pub fn vulnerability(&self) -> Vulnerability {
+        self.vulnerability
     }
 
+    /// The number of the board.
     #[must_use]
     #[inline]
-    pub fn north(&self) -> Hand {
-        self.hands[0]
+    pub fn number(&self) -> u32 {
+        self.number
     }
 
+    /// Sets the board number, together with the dealer and the vulnerability
+    /// it implies following the standard 16 boards table.
+    #[inline]
+    pub fn set_number(&mut self, number: u32) {
+        self.number = number;
+        self.dealer = Seat::dealer_for_board(number);
+        self.vulnerability = Vulnerability::for_board(number);
+    }
+
+    /// The deal as board `number`, see [`Deal::set_number`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// let deal = Deal::new().with_number(22);
+    /// assert_eq!(deal.number(), 22);
+    /// assert_eq!(deal.dealer(), Seat::East);
+    /// assert_eq!(deal.vulnerability(), Vulnerability::EW);
+    /// ```
     #[must_use]
     #[inline]
-    pub fn east(&self) -> Hand {
-        self.hands[1]
+    pub fn with_number(mut self, number: u32) -> Self {
+        self.set_number(number);
+        self
     }
 
     #[must_use]
     #[inline]
-    pub fn south(&self) -> Hand {
-        self.hands[2]
+    pub fn dealer(&self) -> Seat {
+        self.dealer
     }
 
-    fn set_print_style(&mut self, style: Printer) {
-        self.printer = style;
+    #[inline]
+    pub fn set_dealer(&mut self, dealer: Seat) {
+        self.dealer = dealer;
     }
 
+    /// The contract played, if known.
+    #[must_use]
     #[inline]
-    pub fn long(&mut self) {
-        self.set_print_style(Printer::Long);
+    pub fn contract(&self) -> Option<Contract> {
+        self.contract
     }
 
     #[inline]
-    pub fn pbn(&mut self) {
-        self.set_print_style(Printer::Pbn);
+    pub fn set_contract(&mut self, contract: Option<Contract>) {
+        self.contract = contract;
     }
 
+    /// The auction, starting from the dealer, if known.
+    #[must_use]
     #[inline]
-    pub fn short(&mut self) {
-        self.set_print_style(Printer::Short);
+    pub fn auction(&self) -> Option<&Bidding> {
+        self.auction.as_ref()
     }
 
     #[inline]
-    pub fn lin(&mut self) {
-        self.set_print_style(Printer::Lin);
+    pub fn set_auction(&mut self, auction: Option<Bidding>) {
+        self.auction = auction;
     }
 
     #[must_use]
     #[inline]
-    pub fn as_string(&self) -> String {
-        match self.printer {
-            Printer::Pbn => self.as_pbn(),
-            Printer::Lin => self.as_lin(self.number),
-            Printer::Short => self.as_short(),
-            Printer::Long => self.as_long(),
-        }
+    pub fn west(&self) -> Hand {
+        self.hands[3]
     }
 
     #[must_use]
     #[inline]
-    pub fn as_pbn(&self) -> String {
-        let mut pbn = format!("[Board \"{}\"]\n[Deal \"N:", self.number);
-        pbn = format!(
-            "{}",
-            format_args!(
-                "{}{}\"]",
-                pbn,
-                self.into_iter()
-                    .map(|hand| {
-                        hand.into_iter()
-                            .map(|holding| holding.into_iter().map(Card::rankchar).rev().format(""))
-                            .format(".")
-                            .to_string()
-                    })
-                    .format(" ")
-            )
-        );
-        pbn
+    pub fn north(&self) -> Hand {
+        self.hands[0]
     }
 
     #[must_use]
     #[inline]
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn as_lin(&self, board_n: u8) -> String {
-        let board_n = if board_n % (MAX_N_OF_BOARDS + 1) == 0 {
-            1
-        } else {
-            board_n % (MAX_N_OF_BOARDS + 1)
-        };
-        let mut stringa = format!(
-            "st||md|{}",
-            (((board_n % NUMBER_OF_HANDS as u8) + 1) % NUMBER_OF_HANDS as u8) + 1
-        ); // Dealer for the deal. LIN is a weird format.
-        for (position, hand) in self.into_iter().enumerate() {
-            if position != 0 {
-                stringa.push(','); // TODO: Write this and next block with iterators
-            }
-            for (index, holding) in hand.into_iter().enumerate() {
-                stringa.push(match index {
-                    0 => 'S',
-                    1 => 'H',
-                    2 => 'D',
-                    3 => 'C',
-                    _ => unreachable!(),
-                });
-                stringa = format!(
-                    "{}",
-                    format_args!(
-                        "{}{}",
-                        stringa,
-                        holding.into_iter().map(Card::rankchar).rev().format("")
-                    )
-                );
-            }
-        }
-        let data1 = (board_n - 1) / NUMBER_OF_HANDS as u8; // Round of board
-        let data2 = (board_n - 1) % NUMBER_OF_HANDS as u8; // Dealer
-        let data3 = match (data1 + data2) % NUMBER_OF_HANDS as u8 {
-            0 => "o",
-            1 => "n",
-            2 => "e",
-            3 => "b",
-            _ => unreachable!(),
-        };
-        format!("{stringa}|sv|{data3}|rh||ah|Board {board_n}|")
+    pub fn east(&self) -> Hand {
+        self.hands[1]
     }
 
-    fn as_short(&self) -> String {
-        let west = self.hands[Seat::West as usize].to_string();
-        let east = self.hands[Seat::East as usize].to_string();
-        let north = self.hands[Seat::North as usize].to_string();
-        let south = self.hands[Seat::South as usize].to_string();
-        let width = west.chars().count() + east.chars().count() + north.chars().count() / 2;
-        format!(
-            "{north:^0$}\n{west:<1$}{east:>1$}\n{south:^0$}",
-            width + 1,
-            width / 2,
-        )
+    #[must_use]
+    #[inline]
+    pub fn south(&self) -> Hand {
+        self.hands[2]
     }
 
-    fn as_long(&self) -> String {
-        let west_len = self.extract_long_strlen(Seat::West);
-        let east_len = self.extract_long_strlen(Seat::East);
-        let north_len = self.extract_long_strlen(Seat::North);
-        let south_len = self.extract_long_strlen(Seat::South);
-        let ns_len = if south_len < north_len {
-            north_len
-        } else {
-            south_len
-        };
-        let width = west_len + east_len + ns_len - 2;
-        let mut stringa = String::with_capacity(210);
-        for line in self.hands[Seat::North as usize].long_str().split('\n') {
-            stringa = format!("{stringa}{line:^0$}\n", width - 2 - ns_len + line.len());
-        }
-        for (line_w, line_e) in self.hands[Seat::West as usize]
-            .long_str()
-            .split('\n')
-            .zip(self.hands[Seat::East as usize].long_str().split('\n'))
-        {
-            stringa = format!(
-                "{stringa}{line_w:<0$}{line_e:<1$}\n",
-                width - east_len,
-                east_len
-            );
-        }
-        stringa = format!(
-            "{stringa}{}",
-            self.hands[Seat::South as usize]
-                .long_str()
-                .split('\n')
-                .map(|string| format!("{string:^0$}", width - ns_len - 2 + string.len()))
-                .format("\n"),
-        );
-        stringa
+    /// The deal as a pbn game, with the `Board`, `Dealer`, `Vulnerable` and `Deal` tags.
+    #[must_use]
+    #[inline]
+    pub fn as_pbn(&self) -> String {
+        format!(
+            "[Board \"{}\"]\n[Dealer \"{}\"]\n[Vulnerable \"{}\"]\n[Deal \"N:{}\"]",
+            self.number,
+            self.dealer,
+            pbn_vulnerability(self.vulnerability),
+            self.into_iter().copied().map(hand_code).format(" ")
+        )
     }
 
-    fn extract_long_strlen(&self, seat: Seat) -> usize {
-        self.hands[seat as usize]
-            .long_str()
-            .split('\n')
-            .map(str::len)
-            .max()
-            .unwrap()
+    /// The deal as a lin file, with the board number, the dealer and the vulnerability of
+    /// the deal.
+    #[must_use]
+    #[inline]
+    pub fn as_lin(&self) -> String {
+        format!(
+            "st||md|{}|sv|{}|rh||ah|Board {}|",
+            lin_md(self.dealer, &Hands::new_from(self.hands)),
+            lin_sv(self.vulnerability),
+            self.number
+        )
     }
 
     #[inline]
@@ -997,7 +1008,7 @@ impl std::ops::Index<usize> for Deal {
 
 impl fmt::Display for Deal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.as_string())
+        write!(f, "{}", self.display_with(&Format::default()))
     }
 }
 
@@ -1010,6 +1021,85 @@ impl IntoIterator for Deal {
     }
 }
 
+/// The value of the `md` tag: the dealer followed by the hands, starting from South.
+pub(crate) fn lin_md(dealer: Seat, hands: &Hands) -> String {
+    format!(
+        "{}{}",
+        // Dealer is a number starting from 1 for South.
+        (dealer + 2u8) as u8 + 1,
+        [Seat::South, Seat::West, Seat::North, Seat::East]
+            .iter()
+            .map(|&seat| lin_hand(hands.hands()[seat as usize]))
+            .join(",")
+    )
+}
+
+/// A hand as written in a lin file, e.g. `S34JH258TQKD2JQC7`.
+fn lin_hand(hand: Hand) -> String {
+    hand.into_iter()
+        .zip(["S", "H", "D", "C"])
+        .map(|(holding, suit)| {
+            format!(
+                "{suit}{}",
+                holding.into_iter().map(Card::rankchar).rev().format("")
+            )
+        })
+        .join("")
+}
+
+/// The value of the `sv` tag.
+pub(crate) fn lin_sv(vulnerability: Vulnerability) -> &'static str {
+    match vulnerability {
+        Vulnerability::None => "o",
+        Vulnerability::NS => "n",
+        Vulnerability::EW => "e",
+        Vulnerability::All => "b",
+    }
+}
+
+/// The value of the `Vulnerable` tag of a pbn file.
+pub(crate) fn pbn_vulnerability(vulnerability: Vulnerability) -> &'static str {
+    match vulnerability {
+        Vulnerability::None => "None",
+        Vulnerability::NS => "NS",
+        Vulnerability::EW => "EW",
+        Vulnerability::All => "All",
+    }
+}
+
+/// A card as suit letter and rank, like `SA` or `HT`.
+pub(crate) fn card_code(card: Card) -> String {
+    format!("{:?}{}", card.suit(), card.rankchar())
+}
+
+/// A hand as pbn holdings, from spades to clubs separated by dots.
+pub(crate) fn hand_code(hand: Hand) -> String {
+    hand.into_iter()
+        .map(|holding| holding.into_iter().map(Card::rankchar).rev().format(""))
+        .join(".")
+}
+
+/// A call as `P`, `X`, `XX` or a bid like `3N`.
+pub(crate) fn bid_code(bid: Bid) -> String {
+    match bid {
+        Bid::Pass => String::from("P"),
+        Bid::Double => String::from("X"),
+        Bid::Redouble => String::from("XX"),
+        Bid::Contract(level, strain) => format!("{level}{}", strain_code(strain)),
+    }
+}
+
+/// A strain as a single letter, `N` for no trumps.
+pub(crate) fn strain_code(strain: Strain) -> char {
+    match strain {
+        Strain::NoTrumps => 'N',
+        Strain::Spades => 'S',
+        Strain::Hearts => 'H',
+        Strain::Diamonds => 'D',
+        Strain::Clubs => 'C',
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::prelude::*;
@@ -1033,6 +1123,57 @@ mod test {
         assert_eq!(deal.north().as_cards(), hand);
     }
 
+    #[test]
+    fn board_metadata_test() {
+        for number in 1..=u8::MAX {
+            assert_eq!(
+                Vulnerability::for_board(u32::from(number)),
+                Vulnerability::from_number(number)
+            );
+        }
+        assert_eq!(Vulnerability::for_board(0), Vulnerability::for_board(16));
+        assert_eq!(Vulnerability::for_board(1000), Vulnerability::for_board(8));
+        assert_eq!(Seat::dealer_for_board(1), Seat::North);
+        assert_eq!(Seat::dealer_for_board(0), Seat::West);
+        assert_eq!(Seat::dealer_for_board(1003), Seat::South);
+        let mut deal = Deal::new();
+        assert_eq!(
+            (deal.number(), deal.dealer(), deal.vulnerability()),
+            (1, Seat::North, Vulnerability::None)
+        );
+        deal.set_number(300);
+        assert_eq!(
+            (deal.number(), deal.dealer(), deal.vulnerability()),
+            (300, Seat::West, Vulnerability::NS)
+        );
+        assert!(deal.contract().is_none());
+        let contract = Contract::new(
+            3,
+            Strain::NoTrumps,
+            Seat::South,
+            Vulnerable::Yes,
+            Doubled::NotDoubled,
+        );
+        deal.set_contract(Some(contract));
+        assert_eq!(deal.clone().contract(), Some(contract));
+    }
+
+    #[test]
+    fn as_pbn_and_as_lin_test() {
+        let mut deal = Deal::from_index(0).unwrap();
+        deal.set_number(300);
+        // Board 300 is dealt by West, with North-South vulnerable.
+        deal.set_dealer(Seat::East);
+        deal.set_vuln(Vulnerability::All);
+        let pbn = deal.as_pbn();
+        assert!(
+            pbn.starts_with("[Board \"300\"]\n[Dealer \"E\"]\n[Vulnerable \"All\"]\n[Deal \"N:")
+        );
+        let lin = deal.as_lin();
+        assert!(lin.starts_with("st||md|4S"));
+        assert!(lin.ends_with("|sv|b|rh||ah|Board 300|"));
+    }
+
     #[test]
     fn dealer_builder_test() {
         let dealer_builder = DealerBuilder::new();
@@ -1110,4 +1251,17 @@ mod test {
         let deal = dealer.deal().unwrap();
         assert!(deal.north().slen() + deal.south().slen() > 8);
     }
+
+    #[test]
+    fn dealer_builder_hand_distribution_test() {
+        let hand = Cards::from_str("SAKQHAKQDAKQCAKQJ").unwrap();
+        let builder = DealerBuilder::new().predeal(Seat::North, hand).unwrap();
+        let north = builder.hand_distribution(Seat::North).unwrap();
+        assert_eq!(north.total(), 1);
+        let south = builder.hand_distribution(Seat::South).unwrap();
+        // North holds 37 HCP, so South can hold at most 3.
+        let hcp = south.hcp_distribution();
+        assert!(hcp[4..].iter().all(|&probability| probability == 0.0));
+        assert!(hcp[3] > 0.0);
+    }
 }
@@ -0,0 +1,301 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Files for dealing machines, like the Duplimate.
+//!
+//! Every format is a sequence of fixed length records, one for each board:
+//! - `.bri`: 128 bytes. The cards of North, East and South (West gets the rest) as 13 numbers
+//!   of two digits each, in ascending order, where `01` is the ♠A, `13` the ♠2, `14` the ♥A
+//!   and so on up to `52`, the ♣2. Then 32 spaces and 18 NUL bytes.
+//! - `.dge`: 128 bytes. The hands of North, East, South and West, every suit introduced by its
+//!   symbol in code page 437 (`0x06` ♠, `0x03` ♥, `0x04` ♦, `0x05` ♣) followed by its ranks
+//!   from the ace down, with `T` for the ten. Then spaces up to 128 bytes.
+//! - `.dup`: 156 bytes. The `.bri` cards (78 bytes), the `.dge` hands (68 bytes), `YN`, the
+//!   board number in two digits and 6 spaces.
+//!
+//! `.bri` and `.dge` files have no board numbers: boards are numbered in order.
+
+use crate::numbering::CARD_ORDER;
+use crate::prelude::*;
+
+/// Length of the cards of North, East and South in a `.bri` record.
+const BRI_CARDS_LEN: usize = 78;
+
+/// Length of the four hands in a `.dge` record.
+const DGE_HANDS_LEN: usize = 68;
+
+/// Symbols of the suits in code page 437, in the order spades, hearts, diamonds, clubs.
+const DGE_SUITS: [u8; 4] = [0x06, 0x03, 0x04, 0x05];
+
+/// The suits, in the order of [`DGE_SUITS`].
+const DGE_SUIT_ORDER: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+/// Ranks of a suit from the ace down, as written in a `.dge` record.
+const DGE_RANKS: &[u8; 13] = b"AKQJT98765432";
+
+/// The file formats of dealing machines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MachineFormat {
+    Bri,
+    Dge,
+    Dup,
+}
+
+impl MachineFormat {
+    /// Extension of the files in this format, without the dot.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Bri => "bri",
+            Self::Dge => "dge",
+            Self::Dup => "dup",
+        }
+    }
+
+    /// Length in bytes of the record of a board.
+    #[must_use]
+    pub const fn record_len(self) -> usize {
+        match self {
+            Self::Bri | Self::Dge => 128,
+            Self::Dup => 156,
+        }
+    }
+
+    /// Writes `deals` in this format, in the order they are given.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>>{
+    /// let dealer = DealerBuilder::new().build()?;
+    /// let boards = dealer.deal_boards(1..=16)?;
+    /// let file = MachineFormat::Dup.write(&boards)?;
+    /// assert_eq!(file.len(), 16 * MachineFormat::Dup.record_len());
+    /// let read = MachineFormat::Dup.read(&file, 1)?;
+    /// assert_eq!(read[15].number(), 16);
+    /// assert_eq!(read[15].hands(), boards[15].hands());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// If writing a `.dup` file with a board number greater than 99.
+    pub fn write(self, deals: &[Deal]) -> Result<Vec<u8>, DealerError> {
+        let mut file = Vec::with_capacity(deals.len() * self.record_len());
+        for deal in deals {
+            match self {
+                Self::Bri => {
+                    file.extend(bri_cards(deal));
+                    file.extend([b' '; 32]);
+                    file.extend([0; 18]);
+                }
+                Self::Dge => {
+                    file.extend(dge_hands(deal));
+                    file.extend([b' '; 60]);
+                }
+                Self::Dup => {
+                    if deal.number() > 99 {
+                        return Err(DealerError::new(format!(
+                            "board {} cannot be written in a dup file: board numbers go up to 99",
+                            deal.number()
+                        )));
+                    }
+                    file.extend(bri_cards(deal));
+                    file.extend(dge_hands(deal));
+                    file.extend(format!("YN{:02}", deal.number()).bytes());
+                    file.extend([b' '; 6]);
+                }
+            }
+        }
+        Ok(file)
+    }
+
+    /// Reads the boards of a file in this format. Boards without a number in the file are
+    /// numbered starting from `first_board`. Dealer and vulnerability follow the board number.
+    ///
+    /// # Errors
+    /// If the length of the file is not a multiple of the record length, or if a record
+    /// is malformed.
+    pub fn read(self, file: &[u8], first_board: u32) -> Result<Vec<Deal>, DealerError> {
+        if !file.len().is_multiple_of(self.record_len()) {
+            return Err(DealerError::new(format!(
+                "the length of a {} file must be a multiple of {} bytes",
+                self.extension(),
+                self.record_len()
+            )));
+        }
+        file.chunks(self.record_len())
+            .zip(first_board..)
+            .enumerate()
+            .map(|(index, (record, number))| {
+                let error = |reason: &str| {
+                    DealerError::new(format!(
+                        "record {} of the {} file: {reason}",
+                        index + 1,
+                        self.extension()
+                    ))
+                };
+                let (hands, number) = match self {
+                    Self::Bri => (read_bri_cards(&record[..BRI_CARDS_LEN]), number),
+                    Self::Dge => (read_dge_hands(&record[..DGE_HANDS_LEN]), number),
+                    Self::Dup => {
+                        let digits = &record[BRI_CARDS_LEN + DGE_HANDS_LEN + 2..][..2];
+                        let number = std::str::from_utf8(digits)
+                            .ok()
+                            .and_then(|digits| digits.parse().ok())
+                            .ok_or_else(|| error("invalid board number"))?;
+                        (read_bri_cards(&record[..BRI_CARDS_LEN]), number)
+                    }
+                };
+                let hands = hands.ok_or_else(|| error("invalid cards"))?;
+                Ok(Deal::from_hands(hands).with_number(number))
+            })
+            .collect()
+    }
+}
+
+/// The index of `card` in the numbering of the `.bri` format, starting from 0.
+fn bri_index(card: Card) -> usize {
+    // SAFETY: every card is in the card order.
+    CARD_ORDER.iter().position(|&other| other == card).unwrap()
+}
+
+/// The cards of North, East and South as written in a `.bri` record.
+fn bri_cards(deal: &Deal) -> Vec<u8> {
+    let mut record = Vec::with_capacity(BRI_CARDS_LEN);
+    for hand in &deal.hands()[..3] {
+        let mut indexes: Vec<usize> = hand.as_cards().into_iter().map(bri_index).collect();
+        indexes.sort_unstable();
+        for index in indexes {
+            record.extend(format!("{:02}", index + 1).bytes());
+        }
+    }
+    record
+}
+
+/// The four hands as written in a `.dge` record.
+fn dge_hands(deal: &Deal) -> Vec<u8> {
+    let mut record = Vec::with_capacity(DGE_HANDS_LEN);
+    for hand in deal.hands() {
+        for (holding, symbol) in hand.into_iter().zip(DGE_SUITS) {
+            record.push(symbol);
+            record.extend(holding.into_iter().rev().map(|card| card.rankchar() as u8));
+        }
+    }
+    record
+}
+
+/// Completes the hands with the missing cards and checks them, returning them in the order
+/// North, East, South, West.
+fn complete_hands(mut hands: [Cards; NUMBER_OF_HANDS]) -> Option<[Hand; NUMBER_OF_HANDS]> {
+    let mut deck = Cards::ALL;
+    for &cards in &hands {
+        if !(cards - deck).is_empty() {
+            return None;
+        }
+        deck -= cards;
+    }
+    if let Some(missing) = hands.iter_mut().find(|cards| cards.is_empty()) {
+        *missing = deck;
+    }
+    let hands = hands.map(|cards| Hand::try_from(cards).ok());
+    if hands.iter().all(Option::is_some) {
+        Some(hands.map(Option::unwrap_or_default))
+    } else {
+        None
+    }
+}
+
+fn read_bri_cards(record: &[u8]) -> Option<[Hand; NUMBER_OF_HANDS]> {
+    let mut hands = [Cards::EMPTY; NUMBER_OF_HANDS];
+    for (position, digits) in record.chunks(2).enumerate() {
+        let index: usize = std::str::from_utf8(digits).ok()?.parse().ok()?;
+        let card = *CARD_ORDER.get(index.checked_sub(1)?)?;
+        let cards = &mut hands[position / 13];
+        if cards.contains(card) {
+            return None;
+        }
+        *cards = cards.insert(card);
+    }
+    complete_hands(hands)
+}
+
+fn read_dge_hands(record: &[u8]) -> Option<[Hand; NUMBER_OF_HANDS]> {
+    let mut hands = [Cards::EMPTY; NUMBER_OF_HANDS];
+    let mut suits_seen = 0usize;
+    for &byte in record {
+        if DGE_SUITS.contains(&byte) {
+            suits_seen += 1;
+            continue;
+        }
+        // Every hand has the four suits, so the suits seen tell us hand and suit.
+        let (hand, suit) = (suits_seen.checked_sub(1)? / 4, (suits_seen - 1) % 4);
+        let rank = DGE_RANKS.iter().position(|&rank| rank == byte)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let card = Card::new(DGE_SUIT_ORDER[suit], 14 - rank as u8);
+        let cards = hands.get_mut(hand)?;
+        *cards = cards.insert(card);
+    }
+    complete_hands(hands)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bri_layout_test() {
+        let deal = Deal::from_index(0).unwrap();
+        let file = MachineFormat::Bri.write(&[deal]).unwrap();
+        assert_eq!(file.len(), 128);
+        let expected = (1..=39).map(|number| format!("{number:02}")).join("");
+        assert_eq!(&file[..78], expected.as_bytes());
+        assert!(file[78..110].iter().all(|&byte| byte == b' '));
+        assert!(file[110..].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn dge_layout_test() {
+        let deal = Deal::from_index(0).unwrap();
+        let file = MachineFormat::Dge.write(&[deal]).unwrap();
+        assert_eq!(file.len(), 128);
+        // North has all the spades.
+        assert_eq!(&file[..17], b"\x06AKQJT98765432\x03\x04\x05");
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let dealer = DealerBuilder::new().build().unwrap();
+        let boards = dealer.deal_boards(5..=20).unwrap();
+        assert_eq!(boards[0].number(), 5);
+        assert_eq!(boards[0].dealer(), Seat::North);
+        assert_eq!(boards[0].vulnerability(), Vulnerability::NS);
+        for format in [MachineFormat::Bri, MachineFormat::Dge, MachineFormat::Dup] {
+            let file = format.write(&boards).unwrap();
+            assert_eq!(file.len(), 16 * format.record_len());
+            let read = format.read(&file, 5).unwrap();
+            for (read, board) in read.iter().zip(&boards) {
+                assert_eq!(read.hands(), board.hands());
+                assert_eq!(read.number(), board.number());
+                assert_eq!(read.vulnerability(), board.vulnerability());
+            }
+        }
+    }
+
+    #[test]
+    fn machine_errors_test() {
+        let deal = Deal::new().with_number(100);
+        assert!(MachineFormat::Dup
+            .write(std::slice::from_ref(&deal))
+            .is_err());
+        let mut file = MachineFormat::Bri.write(&[deal]).unwrap();
+        assert!(MachineFormat::Bri.read(&file[..127], 1).is_err());
+        file[0..4].copy_from_slice(b"0101");
+        assert!(MachineFormat::Bri.read(&file, 1).is_err());
+        file[0..2].copy_from_slice(b"53");
+        assert!(MachineFormat::Bri.read(&file, 1).is_err());
+        assert!(MachineFormat::Dge.read(&[b'x'; 128], 1).is_err());
+    }
+}
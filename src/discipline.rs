@@ -0,0 +1,272 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+use itertools::Itertools;
+use std::{collections::HashMap, fmt};
+
+/// A call made at the table with a hand that does not match the description the
+/// [`BiddingSystem`] gives for it.
+#[derive(Debug, Clone)]
+pub struct Deviation {
+    board: u32,
+    seat: Seat,
+    player: String,
+    auction: Bidding,
+    hand: Hand,
+}
+
+impl Deviation {
+    /// The number of the board.
+    #[must_use]
+    pub fn board(&self) -> u32 {
+        self.board
+    }
+
+    /// The seat of the player who made the call.
+    #[must_use]
+    pub fn seat(&self) -> Seat {
+        self.seat
+    }
+
+    /// The name of the player who made the call.
+    #[must_use]
+    pub fn player(&self) -> &str {
+        &self.player
+    }
+
+    /// The auction up to the offending call, included.
+    #[must_use]
+    pub fn auction(&self) -> &Bidding {
+        &self.auction
+    }
+
+    /// The offending call.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn call(&self) -> Bid {
+        // A deviation is always recorded after a call.
+        *self.auction.iter().last().unwrap()
+    }
+
+    /// The hand the player held.
+    #[must_use]
+    pub fn hand(&self) -> Hand {
+        self.hand
+    }
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "board {}: {} ({}) called {} after {} with {} ({} HCP)",
+            self.board,
+            self.player,
+            self.seat,
+            self.call(),
+            self.auction.iter().take(self.auction.len() - 1).format("-"),
+            self.hand,
+            self.hand.hcp()
+        )
+    }
+}
+
+/// How well a player sticks to the system: the number of calls the system describes and how
+/// many of them were made with a hand outside the description.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlayerDiscipline {
+    calls: usize,
+    deviations: usize,
+}
+
+impl PlayerDiscipline {
+    /// The calls made by the player that the system describes.
+    #[must_use]
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// The described calls made with a hand not matching the description.
+    #[must_use]
+    pub fn deviations(&self) -> usize {
+        self.deviations
+    }
+
+    /// The fraction of described calls that deviated from the system, 0 if there are none.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn deviation_rate(&self) -> f32 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.deviations as f32 / self.calls as f32
+        }
+    }
+}
+
+/// The result of [`BiddingSystem::check_discipline`]: every deviation found and the
+/// statistics of every player.
+#[derive(Debug, Clone, Default)]
+pub struct DisciplineReport {
+    deviations: Vec<Deviation>,
+    players: HashMap<String, PlayerDiscipline>,
+}
+
+impl DisciplineReport {
+    /// Every deviation, in the order of the boards and of the auction.
+    #[must_use]
+    pub fn deviations(&self) -> &[Deviation] {
+        &self.deviations
+    }
+
+    /// The statistics of every player who made at least one described call, by name.
+    #[must_use]
+    pub fn players(&self) -> &HashMap<String, PlayerDiscipline> {
+        &self.players
+    }
+
+    /// The statistics of a single player.
+    #[must_use]
+    pub fn player(&self, name: &str) -> Option<&PlayerDiscipline> {
+        self.players.get(name)
+    }
+
+    /// The numbers of the boards with at least one deviation, in increasing order.
+    #[must_use]
+    pub fn boards(&self) -> Vec<u32> {
+        let mut boards: Vec<u32> = self.deviations.iter().map(Deviation::board).collect();
+        boards.sort_unstable();
+        boards.dedup();
+        boards
+    }
+}
+
+impl BiddingSystem {
+    /// Checks the auctions of played boards against the system: every call the system
+    /// describes is compared, with [`HandDescriptor::check`], with the hand of the player
+    /// who made it. Calls the system does not describe are ignored, as are boards without
+    /// an auction.
+    ///
+    /// # Example
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>>{
+    /// let system = BiddingSystem::from_str("1NT: balanced hcp 15-17")?;
+    /// // Ann, South, opens 1NT with 18 HCP.
+    /// let board = LinDeal::from_str(concat!(
+    ///     "pn|Ann,Bob,Cid,Dan|st||",
+    ///     "md|1SAKQHAKQ2D32C5432,S432HJT98DQJT9CKQ,S765H7654DAK8CJT9,|",
+    ///     "rh||ah|Board 1|sv|o|mb|1N|mb|p|mb|p|mb|p|pg||",
+    /// ))?;
+    /// let report = system.check_discipline([&board]);
+    /// assert_eq!(report.boards(), [1]);
+    /// assert_eq!(report.player("Ann").unwrap().deviations(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn check_discipline<'a>(
+        &self,
+        boards: impl IntoIterator<Item = &'a LinDeal>,
+    ) -> DisciplineReport {
+        let mut report = DisciplineReport::default();
+        for board in boards {
+            let Some(auction) = board.bidding() else {
+                continue;
+            };
+            let mut prefix = Bidding::new();
+            for (index, &bid) in auction.iter().enumerate() {
+                if prefix.push(bid).is_err() {
+                    break;
+                }
+                let Some(description) = self.description(&prefix) else {
+                    continue;
+                };
+                let seat = board.dealer() + index;
+                let player = &board.players()[seat as usize];
+                let hand = board.hands()[seat as usize];
+                let stats = report.players.entry(player.clone()).or_default();
+                stats.calls += 1;
+                if !description.check(hand) {
+                    stats.deviations += 1;
+                    report.deviations.push(Deviation {
+                        board: board.number(),
+                        seat,
+                        player: player.clone(),
+                        auction: prefix.clone(),
+                        hand,
+                    });
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SYSTEM: &str = "
+        1NT: balanced hcp 15-17
+        1S: 5+xxx hcp 11-21
+        1S P 2S: 3+xxx hcp 6-9
+        P: hcp 0-11";
+
+    fn board(number: u32, hands: &str, auction: &str) -> LinDeal {
+        let bids: String = auction
+            .split_whitespace()
+            .flat_map(|bid| ["mb|", bid, "|"])
+            .collect();
+        LinDeal::from_str(&format!(
+            "pn|Ann,Bob,Cid,Dan|st||md|1{hands},|rh||ah|Board {number}|sv|o|{bids}pg||"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn check_discipline_test() {
+        let system = BiddingSystem::from_str(SYSTEM).unwrap();
+        // South (Ann) deals: 1NT with 18 HCP.
+        let strong = board(
+            1,
+            "SAKQHAKQ2D32C5432,S432HJT98DQJT9CKQ,S765H7654DAK8CJT9",
+            "1N p p p",
+        );
+        // South opens 1S with five spades, North raises with two spades.
+        let raise = board(
+            2,
+            "SAKQ32HK32D32C432,S987HQJT9DQJT9CKQ,S54H8765DAK8CJT98",
+            "1S p 2S p p p",
+        );
+        let report = system.check_discipline([&strong, &raise]);
+
+        assert_eq!(report.boards(), [1, 2]);
+        let deviations = report.deviations();
+        assert_eq!(deviations.len(), 2);
+        assert_eq!(deviations[0].player(), "Ann");
+        assert_eq!(deviations[0].seat(), Seat::South);
+        assert_eq!(deviations[0].call(), Bid::from_str("1NT").unwrap());
+        assert_eq!(deviations[1].player(), "Cid");
+        assert_eq!(deviations[1].seat(), Seat::North);
+        assert_eq!(deviations[1].auction().len(), 3);
+        assert_eq!(
+            deviations[1].to_string(),
+            "board 2: Cid (N) called 2♠ after 1♠-Pass with ♠54♥8765♦AK8♣JT98 (8 HCP)"
+        );
+
+        let ann = report.player("Ann").unwrap();
+        assert_eq!((ann.calls(), ann.deviations()), (2, 1));
+        assert!((ann.deviation_rate() - 0.5).abs() < f32::EPSILON);
+        let cid = report.player("Cid").unwrap();
+        assert_eq!((cid.calls(), cid.deviations()), (1, 1));
+        // West passed 1S: the system does not describe it.
+        assert!(report.player("Bob").is_none());
+
+        // Board 1 played again in the other room, after board 2.
+        let report = system.check_discipline([&strong, &raise, &strong]);
+        assert_eq!(report.deviations().len(), 3);
+        assert_eq!(report.boards(), [1, 2]);
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright (C) 2024 Alvaro Gaiotti
 // See end of file for license information
 
-use crate::prelude::{CreationShapeError, DealerError, ParseLinError};
+#[cfg(feature = "lin")]
+use crate::prelude::ParseLinError;
+use crate::prelude::{CreationShapeError, DealerError, ParsePbnError, ParseRbnError, PlayError};
 use std::error::Error;
 
 /// Error wrapper for the entire library, so we expose just this one at the highest level.
@@ -9,9 +11,13 @@ use std::error::Error;
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum SqueezerError {
+    #[cfg(feature = "lin")]
     LinParsing(ParseLinError),
+    PbnParsing(ParsePbnError),
+    RbnParsing(ParseRbnError),
     CreationShape(CreationShapeError),
     DealingError(DealerError),
+    Play(PlayError),
     #[cfg(feature = "dds")]
     DDSError(dds::ddserror::DdsError),
     #[cfg(feature = "dds")]
@@ -22,9 +28,13 @@ pub enum SqueezerError {
 impl Error for SqueezerError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
+            #[cfg(feature = "lin")]
             SqueezerError::LinParsing(ref err) => Some(err),
+            SqueezerError::PbnParsing(ref err) => Some(err),
+            SqueezerError::RbnParsing(ref err) => Some(err),
             SqueezerError::CreationShape(ref err) => Some(err),
             SqueezerError::DealingError(ref err) => Some(err),
+            SqueezerError::Play(ref err) => Some(err),
             #[cfg(feature = "dds")]
             SqueezerError::DDSError(ref err) => Some(err),
             #[cfg(feature = "dds")]
@@ -55,12 +65,31 @@ impl From<DealerError> for SqueezerError {
     }
 }
 
+impl From<PlayError> for SqueezerError {
+    fn from(value: PlayError) -> Self {
+        Self::Play(value)
+    }
+}
+
+#[cfg(feature = "lin")]
 impl From<ParseLinError> for SqueezerError {
     fn from(value: ParseLinError) -> Self {
         Self::LinParsing(value)
     }
 }
 
+impl From<ParsePbnError> for SqueezerError {
+    fn from(value: ParsePbnError) -> Self {
+        Self::PbnParsing(value)
+    }
+}
+
+impl From<ParseRbnError> for SqueezerError {
+    fn from(value: ParseRbnError) -> Self {
+        Self::RbnParsing(value)
+    }
+}
+
 #[cfg(feature = "dds")]
 impl From<dds::ddserror::DdsError> for SqueezerError {
     fn from(value: dds::ddserror::DdsError) -> Self {
@@ -0,0 +1,195 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Export of deals and simulation results as JSON and CSV, to be read by spreadsheets and
+//! notebooks. The schemas below are stable: new fields may be added at the end, existing
+//! ones are never renamed or removed.
+//!
+//! Cards are written as suit letter and rank (`SA`, `HT`, `C2`), hands as the PBN holdings
+//! from spades to clubs (`AK5.J942.K84.Q73`) and contracts as level, strain (`N` for no
+//! trumps), declarer and doubles, the form read by [`Contract::from_str`] (`4HS`, `3NEX`).
+//!
+//! - Deals, as JSON lines ([`Deal::to_json`], [`write_json_lines`]), one object per deal:
+//!   `{"board":1,"dealer":"N","vulnerability":"None","hands":[...],"contract":"4HS"}`.
+//!   `vulnerability` is one of `None`, `NS`, `EW` and `All`; `contract` is `null` if unknown.
+//!   `hands` has an object for every seat, from North:
+//!   `{"seat":"N","cards":"AK5.J942.K84.Q73","hcp":14,"shape":[3,4,3,3]}`, with the
+//!   shape from spades to clubs.
+//! - Lead simulations (`LeadSimulationResult::to_json` and `to_csv`), as
+//!   `{"contract":"2HE","deals":1000,"leads":[...]}` with a lead
+//!   `{"card":"SA","average_tricks":7.9,"set_percentage":41.2,"tricks":[0,...]}` where
+//!   `tricks` counts the deals in which the defence took 0 to 13 tricks. The CSV has the
+//!   columns `card,average_tricks,set_percentage,tricks_0,...,tricks_13`. Leads are sorted by
+//!   set percentage, best first.
+//! - Payoff matrices (`Payoff::to_json` and `to_csv`), as
+//!   `{"entries":["3NN",...],"mean":[[...]],"std_dev":[[...]]}` where `mean[i][j]` is the
+//!   average gain of entry `i` over entry `j`. Entries are contracts or cards, sets of cards
+//!   being written as their cards separated by spaces (`SA SK`). The CSV has a row for every
+//!   pair, with the columns `entry,against,mean,std_dev`.
+//! - Performance records (`performance_csv`), with a row for every card played and the
+//!   columns `seat,card_number,card,tricks,tricks_lost`: `tricks` are the double dummy tricks
+//!   after the card is played, `tricks_lost` is 0 for the double dummy correct card.
+//!
+//! Numbers that are not finite are written as `null` in JSON and left empty in CSV.
+
+use crate::prelude::*;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::Write;
+
+impl Deal {
+    /// The deal as a JSON object, on a single line. See [`crate::export`] for the schema.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// let deal = Deal::from_index(0).unwrap().with_number(2);
+    /// let json = deal.to_json();
+    /// assert!(json.starts_with(r#"{"board":2,"dealer":"E","vulnerability":"NS","hands":[{"seat":"N","cards":"AKQJT98765432...","hcp":10,"shape":[13,0,0,0]}"#));
+    /// assert!(json.ends_with(r#""contract":null}"#));
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let hands = self
+            .hands()
+            .iter()
+            .zip([Seat::North, Seat::East, Seat::South, Seat::West])
+            .map(|(hand, seat)| {
+                format!(
+                    r#"{{"seat":"{seat}","cards":{},"hcp":{},"shape":[{}]}}"#,
+                    json_string(&hand_code(*hand)),
+                    hand.hcp(),
+                    hand.into_iter().map(|holding| holding.len()).join(",")
+                )
+            })
+            .join(",");
+        format!(
+            r#"{{"board":{},"dealer":"{}","vulnerability":"{}","hands":[{hands}],"contract":{}}}"#,
+            self.number(),
+            self.dealer(),
+            pbn_vulnerability(self.vulnerability()),
+            self.contract()
+                .map_or_else(|| String::from("null"), |c| json_string(&contract_code(c)))
+        )
+    }
+}
+
+/// Writes `deals` as JSON lines, one object per deal.
+///
+/// # Errors
+/// If writing to `writer` fails.
+pub fn write_json_lines<'a, W: Write>(
+    deals: impl IntoIterator<Item = &'a Deal>,
+    mut writer: W,
+) -> std::io::Result<()> {
+    for deal in deals {
+        writeln!(writer, "{}", deal.to_json())?;
+    }
+    Ok(())
+}
+
+/// A contract in the form read by [`Contract::from_str`].
+pub(crate) fn contract_code(contract: Contract) -> String {
+    let doubled = match contract.doubled() {
+        Doubled::NotDoubled => "",
+        Doubled::Doubled => "X",
+        Doubled::Redoubled => "XX",
+    };
+    format!(
+        "{}{}{}{doubled}",
+        contract.level(),
+        strain_code(contract.strain()),
+        contract.declarer()
+    )
+}
+
+/// A JSON string literal, quotes included.
+pub(crate) fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // SAFETY: writing to a `String` cannot fail.
+            c if c.is_control() => write!(escaped, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// A JSON number, or `null` if `value` is not finite.
+pub(crate) fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        String::from("null")
+    }
+}
+
+/// A CSV number, or an empty field if `value` is not finite.
+pub(crate) fn csv_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// A CSV field, quoted if needed.
+pub(crate) fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deal_json_test() {
+        let mut deal = Deal::from_index(0).unwrap().with_number(7);
+        deal.set_contract(Some(Contract::new(
+            3,
+            Strain::NoTrumps,
+            Seat::East,
+            Vulnerable::Yes,
+            Doubled::Doubled,
+        )));
+        let json = deal.to_json();
+        assert!(json.starts_with(r#"{"board":7,"dealer":"S","vulnerability":"All","#));
+        assert!(
+            json.contains(r#"{"seat":"W","cards":"...AKQJT98765432","hcp":10,"shape":[0,0,0,13]}"#)
+        );
+        assert!(json.ends_with(r#""contract":"3NEX"}"#));
+        assert_eq!(
+            Contract::from_str("3NEX", Vulnerable::Yes).unwrap(),
+            deal.contract().unwrap()
+        );
+
+        let mut lines = Vec::new();
+        write_json_lines(&[deal.clone(), deal], &mut lines).unwrap();
+        let lines = String::from_utf8(lines).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert_eq!(lines.lines().next(), Some(json.as_str()));
+    }
+
+    #[test]
+    fn escaping_test() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+        assert_eq!(json_number(f32::NAN), "null");
+        assert_eq!(json_number(1.5), "1.5");
+        assert_eq!(csv_number(f32::INFINITY), "");
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+use colored::Color;
+
+/// How a [`Deal`] is laid out by [`Deal::display_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Layout {
+    /// A diagram with every hand on a single line.
+    #[default]
+    Short,
+    /// A diagram with every suit of a hand on its own line.
+    Long,
+    /// The deal as PBN `Board` and `Deal` tags.
+    Pbn,
+    /// The deal as a LIN string.
+    Lin,
+}
+
+/// How suits are written in diagrams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SuitNotation {
+    /// Unicode symbols: ♠ ♥ ♦ ♣.
+    #[default]
+    Symbols,
+    /// Letters: S H D C, or those of the [`CardNotation`] of the format.
+    Letters,
+}
+
+/// Options for printing a [`Deal`], used with [`Deal::display_with`].
+/// Header, HCP, colours and double dummy table apply only to diagrams.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// let deal = Deal::from_index(0).unwrap().with_number(2);
+/// let format = Format::long()
+///     .with_suits(SuitNotation::Letters)
+///     .with_header()
+///     .with_hcp();
+/// let diagram = deal.display_with(&format).to_string();
+/// assert!(diagram.starts_with("Board 2  Dealer E  Vul NS\n"));
+/// assert!(diagram.contains("S AKQJT98765432"));
+/// assert!(diagram.contains("HCP 10"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Format {
+    layout: Layout,
+    suits: SuitNotation,
+    colored: bool,
+    width: Option<usize>,
+    header: bool,
+    hcp: bool,
+    dd_table: Option<[[u8; NUMBER_OF_HANDS]; 5]>,
+    notation: CardNotation,
+}
+
+impl Format {
+    #[must_use]
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            layout,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn short() -> Self {
+        Self::new(Layout::Short)
+    }
+
+    #[must_use]
+    pub fn long() -> Self {
+        Self::new(Layout::Long)
+    }
+
+    #[must_use]
+    pub fn pbn() -> Self {
+        Self::new(Layout::Pbn)
+    }
+
+    #[must_use]
+    pub fn lin() -> Self {
+        Self::new(Layout::Lin)
+    }
+
+    #[must_use]
+    pub fn with_suits(mut self, suits: SuitNotation) -> Self {
+        self.suits = suits;
+        self
+    }
+
+    /// Writes ranks, and suits as letters, in `notation`. Suits are written as symbols if
+    /// the notation has no suit letters.
+    #[must_use]
+    pub fn with_notation(mut self, notation: CardNotation) -> Self {
+        self.notation = notation;
+        self
+    }
+
+    /// Writes hearts and diamonds in red, using ANSI escape codes. The codes are always
+    /// written, whatever the global settings of [`colored`].
+    #[must_use]
+    pub fn with_colors(mut self) -> Self {
+        self.colored = true;
+        self
+    }
+
+    /// Sets the width of the West column: North and South start after it, East after twice
+    /// the width. By default it fits the West hand.
+    #[must_use]
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Writes board number, dealer and vulnerability above the diagram.
+    #[must_use]
+    pub fn with_header(mut self) -> Self {
+        self.header = true;
+        self
+    }
+
+    /// Writes the HCP of every hand.
+    #[must_use]
+    pub fn with_hcp(mut self) -> Self {
+        self.hcp = true;
+        self
+    }
+
+    /// Writes a double dummy table under the diagram. `tricks` are the tricks taken by every
+    /// seat (North, East, South, West) as declarer in spades, hearts, diamonds, clubs and no
+    /// trumps, the order of the double dummy solver.
+    #[must_use]
+    pub fn with_dd_table(mut self, tricks: [[u8; NUMBER_OF_HANDS]; 5]) -> Self {
+        self.dd_table = Some(tricks);
+        self
+    }
+
+    /// Same as [`Format::with_dd_table`], with the table computed by the solver.
+    #[cfg(feature = "dds")]
+    #[must_use]
+    pub fn with_dd_results(
+        self,
+        table: &dds::tables::DdTableResults<dds::tables::Populated>,
+    ) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        self.with_dd_table(
+            table
+                .res_table
+                .map(|strain| strain.map(|tricks| tricks as u8)),
+        )
+    }
+
+    #[must_use]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// A suit, as symbol or letter and coloured if needed.
+    fn suit(&self, suit: Suit) -> Segment {
+        let text = match self.suits {
+            SuitNotation::Symbols => suit.unicode(),
+            SuitNotation::Letters => self.notation.suit(suit),
+        };
+        Segment {
+            text: text.to_string(),
+            red: self.colored && matches!(suit, Suit::Hearts | Suit::Diamonds),
+        }
+    }
+
+    /// The lines of a hand in a diagram.
+    fn hand_lines(&self, hand: Hand) -> Vec<Line> {
+        let void = match self.suits {
+            SuitNotation::Symbols => "—",
+            SuitNotation::Letters => "-",
+        };
+        let holdings = hand
+            .into_iter()
+            .zip([Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs])
+            .map(|(holding, suit)| {
+                let ranks = if holding.is_empty() {
+                    String::from(void)
+                } else {
+                    self.notation.holding_string(holding)
+                };
+                (self.suit(suit), ranks)
+            });
+        let hcp = format!("HCP {}", hand.hcp());
+        if self.layout == Layout::Long {
+            let mut lines: Vec<Line> = holdings
+                .map(|(suit, ranks)| Line::default().push(&suit).text(" ").text(&ranks))
+                .collect();
+            if self.hcp {
+                lines.push(Line::default().text(&hcp));
+            }
+            lines
+        } else {
+            let mut line = Line::default();
+            for (index, (suit, ranks)) in holdings.enumerate() {
+                if index != 0 {
+                    line = line.text(" ");
+                }
+                line = line.push(&suit).text(&ranks);
+            }
+            if self.hcp {
+                line = line.text(&format!(" ({hcp})"));
+            }
+            vec![line]
+        }
+    }
+
+    fn diagram(&self, deal: &Deal, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.header {
+            writeln!(
+                f,
+                "Board {}  Dealer {}  Vul {}",
+                deal.number(),
+                deal.dealer(),
+                pbn_vulnerability(deal.vulnerability())
+            )?;
+        }
+        let [north, east, south, west] = deal.hands().map(|hand| self.hand_lines(hand));
+        let width = self
+            .width
+            .unwrap_or_else(|| west.iter().map(|line| line.width).max().unwrap_or_default() + 2);
+        let mut lines = Vec::new();
+        lines.extend(
+            north
+                .iter()
+                .map(|line| Line::default().pad(width).append(line)),
+        );
+        for index in 0..west.len().max(east.len()) {
+            let mut line = west.get(index).cloned().unwrap_or_default();
+            if let Some(east) = east.get(index) {
+                line = line.pad(2 * width).append(east);
+            }
+            lines.push(line);
+        }
+        lines.extend(
+            south
+                .iter()
+                .map(|line| Line::default().pad(width).append(line)),
+        );
+        if let Some(table) = self.dd_table {
+            lines.push(Line::default());
+            lines.push(Line::default().text("     N  S  E  W"));
+            let strains = [
+                Segment::plain("NT"),
+                self.suit(Suit::Spades),
+                self.suit(Suit::Hearts),
+                self.suit(Suit::Diamonds),
+                self.suit(Suit::Clubs),
+            ];
+            for (strain, tricks) in strains.into_iter().zip([4, 0, 1, 2, 3].map(|i| table[i])) {
+                let mut line = Line::default().push(&strain).pad(3);
+                for seat in [Seat::North, Seat::South, Seat::East, Seat::West] {
+                    line = line.text(&format!("{:>3}", tricks[seat as usize]));
+                }
+                lines.push(line);
+            }
+        }
+        for (index, line) in lines.iter().enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line.text.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// A piece of text of a diagram line, possibly coloured.
+#[derive(Debug, Clone)]
+struct Segment {
+    text: String,
+    red: bool,
+}
+
+impl Segment {
+    fn plain(text: &str) -> Self {
+        Self {
+            text: text.to_owned(),
+            red: false,
+        }
+    }
+}
+
+/// A line of a diagram, keeping track of its visible width.
+#[derive(Debug, Clone, Default)]
+struct Line {
+    text: String,
+    width: usize,
+}
+
+impl Line {
+    fn push(mut self, segment: &Segment) -> Self {
+        self.width += segment.text.chars().count();
+        if segment.red {
+            self.text.push_str("\u{1b}[");
+            self.text.push_str(&Color::Red.to_fg_str());
+            self.text.push('m');
+            self.text.push_str(&segment.text);
+            self.text.push_str("\u{1b}[0m");
+        } else {
+            self.text.push_str(&segment.text);
+        }
+        self
+    }
+
+    fn text(self, text: &str) -> Self {
+        self.push(&Segment::plain(text))
+    }
+
+    fn pad(mut self, width: usize) -> Self {
+        // At least a space between columns.
+        let spaces = width
+            .saturating_sub(self.width)
+            .max(usize::from(self.width != 0));
+        self.text.extend(std::iter::repeat_n(' ', spaces));
+        self.width += spaces;
+        self
+    }
+
+    fn append(mut self, other: &Line) -> Self {
+        self.text.push_str(&other.text);
+        self.width += other.width;
+        self
+    }
+}
+
+/// A [`Deal`] printed with a [`Format`], returned by [`Deal::display_with`].
+pub struct DealDisplay<'a> {
+    deal: &'a Deal,
+    format: &'a Format,
+}
+
+impl fmt::Display for DealDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format.layout {
+            Layout::Pbn => write!(f, "{}", self.deal.as_pbn()),
+            Layout::Lin => write!(f, "{}", self.deal.as_lin()),
+            Layout::Short | Layout::Long => self.format.diagram(self.deal, f),
+        }
+    }
+}
+
+impl Deal {
+    /// Prints the deal with `format`, without changing it.
+    /// `Display` for [`Deal`] uses [`Format::default`], a short diagram.
+    #[must_use]
+    pub fn display_with<'a>(&'a self, format: &'a Format) -> DealDisplay<'a> {
+        DealDisplay { deal: self, format }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short_diagram_test() {
+        let deal = Deal::from_index(0).unwrap();
+        let diagram = deal.display_with(&Format::short()).to_string();
+        let lines: Vec<&str> = diagram.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].trim_start(), "♠AKQJT98765432 ♥— ♦— ♣—");
+        assert!(lines[1].starts_with("♠— ♥— ♦— ♣AKQJT98765432"));
+        assert!(lines[1].ends_with("♠— ♥AKQJT98765432 ♦— ♣—"));
+        assert_eq!(deal.to_string(), diagram);
+        let french = Format::short()
+            .with_notation(CardNotation::FRENCH)
+            .with_suits(SuitNotation::Letters);
+        let diagram = deal.display_with(&french).to_string();
+        assert_eq!(
+            diagram.lines().next().unwrap().trim_start(),
+            "PARDV1098765432 C- K- T-"
+        );
+    }
+
+    #[test]
+    fn long_diagram_test() {
+        let deal = Deal::from_index(0).unwrap().with_number(4);
+        let format = Format::long()
+            .with_suits(SuitNotation::Letters)
+            .with_width(6)
+            .with_header()
+            .with_hcp()
+            .with_dd_table([
+                [13, 0, 0, 0],
+                [0, 13, 0, 0],
+                [0, 0, 13, 0],
+                [0, 0, 0, 13],
+                [7, 7, 7, 7],
+            ]);
+        let diagram = deal.display_with(&format).to_string();
+        let lines: Vec<&str> = diagram.lines().collect();
+        assert_eq!(lines[0], "Board 4  Dealer W  Vul All");
+        assert_eq!(lines[1], "      S AKQJT98765432");
+        assert_eq!(lines[5], "      HCP 10");
+        assert_eq!(lines[6], "S -         S -");
+        // West's long clubs push East a column further.
+        assert_eq!(lines[9], "C AKQJT98765432 C -");
+        assert_eq!(lines[10], "HCP 10      HCP 10");
+        assert_eq!(lines[17], "     N  S  E  W");
+        assert_eq!(lines[18], "NT   7  7  7  7");
+        assert_eq!(lines[19], "S   13  0  0  0");
+        assert_eq!(lines[21], "D    0 13  0  0");
+    }
+
+    #[test]
+    fn colored_diagram_test() {
+        let deal = Deal::from_index(0).unwrap();
+        let diagram = deal.display_with(&Format::long().with_colors()).to_string();
+        assert!(diagram.contains("\u{1b}[31m♥\u{1b}[0m"));
+        assert!(!diagram.contains("\u{1b}[31m♠\u{1b}[0m"));
+        // Colours do not change the alignment.
+        let plain = deal.display_with(&Format::long()).to_string();
+        let strip = |text: &str| text.replace("\u{1b}[31m", "").replace("\u{1b}[0m", "");
+        assert_eq!(strip(&diagram), plain);
+    }
+
+    #[test]
+    fn other_layouts_test() {
+        let deal = Deal::from_index(0).unwrap();
+        assert_eq!(deal.display_with(&Format::pbn()).to_string(), deal.as_pbn());
+        assert!(deal
+            .display_with(&Format::lin())
+            .to_string()
+            .starts_with("st||md|"));
+        // Boards past 128 keep their number, dealer and vulnerability.
+        let mut deal = deal.with_number(300);
+        deal.set_dealer(Seat::South);
+        let lin = deal.display_with(&Format::lin()).to_string();
+        assert!(lin.starts_with("st||md|1"));
+        assert!(lin.ends_with("|sv|n|rh||ah|Board 300|"));
+    }
+}
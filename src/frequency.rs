@@ -0,0 +1,343 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+
+/// Number of HCP values a hand can have, from 0 to 37.
+const HCP_VALUES: usize = MAX_HCP_IN_HAND as usize + 1;
+/// Maximum number of HCP a single suit can hold (AKQJ).
+const MAX_HCP_IN_SUIT: usize = 10;
+
+/// Number of ways of choosing `k` elements out of `n`.
+#[must_use]
+pub(crate) const fn binomial(n: u64, k: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+    let k = if k > n - k { n - k } else { k };
+    let mut result = 1;
+    let mut index = 0;
+    while index < k {
+        // Exact at every step, since it's the binomial coefficient C(n - k + index + 1, index + 1).
+        result = result * (n - k + index + 1) / (index + 1);
+        index += 1;
+    }
+    result
+}
+
+/// Exact distribution of shapes and HCP of a hand dealt at random.
+///
+/// Instead of running a simulation, we enumerate every possible shape of the hand and,
+/// for every shape, we compute the exact (hypergeometric) distribution of the HCP, counting
+/// in how many ways the cards of each suit could be chosen from the remaining deck.
+/// This gives exact frequencies, with no sampling error, for everything that depends only on
+/// shape and HCP, like a [`HandType`] or a [`HandDescriptor`].
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// // How often will I hold 13 HCP when partner holds this 22 HCP balanced hand?
+/// let partner = Cards::from_str("AKQ2 KJ2 AQ3 K32")?;
+/// let distribution = HandDistribution::new(Cards::ALL - partner)?;
+/// let thirteen = HandType::new(Shape::All, HcpRange::new(13, 13));
+/// println!("{:.2}%", distribution.hand_type_probability(&thirteen) * 100.0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HandDistribution {
+    cells: Vec<(ShapePattern, [u64; HCP_VALUES])>,
+    total: u64,
+}
+
+impl HandDistribution {
+    /// Distribution of a hand of 13 cards dealt at random from `deck`.
+    ///
+    /// # Errors
+    /// When the deck does not contain enough cards for a hand.
+    pub fn new(deck: Cards) -> Result<Self, DealerError> {
+        Self::with_predeal(Cards::EMPTY, deck)
+    }
+
+    /// Distribution of a hand that already holds the `predealt` cards and gets the
+    /// remaining ones dealt at random from `deck`.
+    ///
+    /// # Errors
+    /// When the predealt cards are more than 13, when they are also contained in the deck or
+    /// when the deck does not contain enough cards to complete the hand.
+    pub fn with_predeal(predealt: Cards, deck: Cards) -> Result<Self, DealerError> {
+        if predealt.len() > MAX_LENGTH {
+            return Err(DealerError::new(format!(
+                "too many cards predealt to a hand: {predealt}"
+            )));
+        }
+        if !(predealt & deck).is_empty() {
+            return Err(DealerError::new(format!(
+                "card dealt twice: {}",
+                predealt & deck
+            )));
+        }
+        let to_draw = MAX_LENGTH - predealt.len();
+        if deck.len() < to_draw {
+            return Err(DealerError::new(format!(
+                "the deck doesn't contain enough cards to deal a hand: {deck}"
+            )));
+        }
+
+        let suit_tables: Vec<Vec<[u64; MAX_HCP_IN_SUIT + 1]>> = Suit::iter()
+            .map(|suit| Self::suit_table(deck.in_suit(suit)))
+            .collect();
+        let predealt_hcp = predealt.high_card_points() as usize;
+        let predealt_shape: Vec<u8> = Suit::iter()
+            .map(|suit| predealt.in_suit(suit).len())
+            .collect();
+
+        let mut cells = Vec::new();
+        let mut total = 0;
+        let available: Vec<u8> = Suit::iter().map(|suit| deck.in_suit(suit).len()).collect();
+        for spades in 0..=available[0].min(to_draw) {
+            for hearts in 0..=available[1].min(to_draw - spades) {
+                for diamonds in 0..=available[2].min(to_draw - spades - hearts) {
+                    let clubs = to_draw - spades - hearts - diamonds;
+                    if clubs > available[3] {
+                        continue;
+                    }
+                    let drawn = [spades, hearts, diamonds, clubs];
+                    let mut hcp_counts = [0u64; MAX_HCP_IN_DECK as usize + 1];
+                    hcp_counts[0] = 1;
+                    for (suit_table, &length) in suit_tables.iter().zip(drawn.iter()) {
+                        hcp_counts = Self::convolve(&hcp_counts, &suit_table[length as usize]);
+                    }
+                    let mut hand_counts = [0u64; HCP_VALUES];
+                    for (hcp, &count) in hcp_counts.iter().enumerate() {
+                        if count != 0 {
+                            hand_counts[hcp + predealt_hcp] += count;
+                            total += count;
+                        }
+                    }
+                    let pattern = [
+                        predealt_shape[0] + spades,
+                        predealt_shape[1] + hearts,
+                        predealt_shape[2] + diamonds,
+                        predealt_shape[3] + clubs,
+                    ];
+                    cells.push((pattern, hand_counts));
+                }
+            }
+        }
+        Ok(Self { cells, total })
+    }
+
+    /// For every length, the number of ways of picking that many cards from `suit`
+    /// getting every possible amount of HCP.
+    #[allow(clippy::cast_possible_truncation)]
+    fn suit_table(suit: Cards) -> Vec<[u64; MAX_HCP_IN_SUIT + 1]> {
+        let honors: Vec<usize> = suit
+            .into_iter()
+            .filter(|card| card.rank() > 10)
+            .map(|card| (card.rank() - 10) as usize)
+            .collect();
+        let spots = suit.len() - honors.len() as u8;
+        let mut table = vec![[0u64; MAX_HCP_IN_SUIT + 1]; MAX_LENGTH as usize + 1];
+        for subset in 0..(1usize << honors.len()) {
+            let honors_taken = subset.count_ones() as usize;
+            let hcp: usize = honors
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| subset & (1 << index) != 0)
+                .map(|(_, points)| points)
+                .sum();
+            for spots_taken in 0..=spots {
+                table[honors_taken + spots_taken as usize][hcp] +=
+                    binomial(u64::from(spots), u64::from(spots_taken));
+            }
+        }
+        table
+    }
+
+    fn convolve(
+        hcp_counts: &[u64; MAX_HCP_IN_DECK as usize + 1],
+        suit_counts: &[u64; MAX_HCP_IN_SUIT + 1],
+    ) -> [u64; MAX_HCP_IN_DECK as usize + 1] {
+        let mut result = [0u64; MAX_HCP_IN_DECK as usize + 1];
+        for (hcp, &count) in hcp_counts.iter().enumerate().filter(|(_, &c)| c != 0) {
+            for (suit_hcp, &suit_count) in suit_counts.iter().enumerate() {
+                if suit_count != 0 && hcp + suit_hcp < result.len() {
+                    result[hcp + suit_hcp] += count * suit_count;
+                }
+            }
+        }
+        result
+    }
+
+    /// Number of different hands that could be dealt.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Number of hands with the given shape and HCP.
+    #[must_use]
+    pub fn count(&self, pattern: ShapePattern, hcp: u8) -> u64 {
+        self.cells
+            .iter()
+            .find(|(cell_pattern, _)| *cell_pattern == pattern)
+            .and_then(|(_, counts)| counts.get(hcp as usize).copied())
+            .unwrap_or(0)
+    }
+
+    /// Exact probability that the hand has a shape and a number of HCP accepted by `accept`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn probability<F: Fn(ShapePattern, u8) -> bool>(&self, accept: F) -> f64 {
+        let favourable: u64 = self
+            .cells
+            .iter()
+            .flat_map(|(pattern, counts)| {
+                counts
+                    .iter()
+                    .enumerate()
+                    .filter(|(hcp, &count)| count != 0 && accept(*pattern, *hcp as u8))
+                    .map(|(_, count)| count)
+            })
+            .sum();
+        favourable as f64 / self.total as f64
+    }
+
+    /// Exact probability that the hand matches `hand_type`.
+    #[must_use]
+    pub fn hand_type_probability(&self, hand_type: &HandType) -> f64 {
+        self.probability(|pattern, hcp| hand_type.check_pattern(pattern, hcp))
+    }
+
+    /// Exact probability that the hand matches `hand_descriptor`.
+    #[must_use]
+    pub fn hand_descriptor_probability(&self, hand_descriptor: &HandDescriptor) -> f64 {
+        self.probability(|pattern, hcp| hand_descriptor.check_pattern(pattern, hcp))
+    }
+
+    /// Exact probability that the hand has one of the shapes accepted by `shape`.
+    #[must_use]
+    pub fn shape_probability(&self, shape: &Shape) -> f64 {
+        self.probability(|pattern, _| shape.contains_pattern(pattern))
+    }
+
+    /// Exact probability of every HCP value, from 0 to 37.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hcp_distribution(&self) -> [f64; HCP_VALUES] {
+        let mut distribution = [0.0; HCP_VALUES];
+        for (_, counts) in &self.cells {
+            for (probability, &count) in distribution.iter_mut().zip(counts.iter()) {
+                *probability += count as f64;
+            }
+        }
+        for probability in &mut distribution {
+            *probability /= self.total as f64;
+        }
+        distribution
+    }
+}
+
+impl HandType {
+    /// Exact frequency of this `HandType` in a hand dealt from a full deck.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn frequency(&self) -> f64 {
+        // SAFETY: a full deck always has enough cards.
+        HandDistribution::new(Cards::ALL)
+            .unwrap()
+            .hand_type_probability(self)
+    }
+}
+
+impl HandDescriptor {
+    /// Exact frequency of this `HandDescriptor` in a hand dealt from a full deck.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn frequency(&self) -> f64 {
+        // SAFETY: a full deck always has enough cards.
+        HandDistribution::new(Cards::ALL)
+            .unwrap()
+            .hand_descriptor_probability(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() < 1e-6,
+            "expected {expected}, got {value}"
+        );
+    }
+
+    #[test]
+    fn binomial_test() {
+        assert_eq!(binomial(52, 13), 635_013_559_600);
+        assert_eq!(binomial(39, 13), 8_122_425_444);
+        assert_eq!(binomial(5, 0), 1);
+        assert_eq!(binomial(3, 4), 0);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn full_deck_distribution_test() {
+        let distribution = HandDistribution::new(Cards::ALL).unwrap();
+        assert_eq!(distribution.total(), binomial(52, 13));
+        assert_eq!(distribution.cells.len(), SHAPE_COMBINATIONS);
+        let hcp = distribution.hcp_distribution();
+        assert_close(hcp.iter().sum(), 1.0);
+        assert_close(hcp[10], 0.094_051_1);
+        assert_close(hcp[37], 1.0 / binomial(52, 13) as f64);
+    }
+
+    #[test]
+    fn shape_frequencies_test() {
+        let distribution = HandDistribution::new(Cards::ALL).unwrap();
+        let flat = Shape::from_str("(4333)").unwrap();
+        assert_close(distribution.shape_probability(&flat), 0.105_361_4);
+        let balanced = Shape::new_from_patterns(&["(4333)", "(4432)", "(5332)"]).unwrap();
+        assert_close(distribution.shape_probability(&balanced), 0.476_041_5);
+    }
+
+    #[test]
+    fn hand_descriptor_frequency_matches_counting_test() {
+        let weak_nt = HandTypeBuilder::balanced(12, 14).build();
+        let strong = HandType::new(Shape::All, HcpRange::new(20, 37));
+        let descriptor = HandDescriptor::new(vec![weak_nt.clone(), strong.clone()]);
+        let frequency = descriptor.frequency();
+        // The two hand types are disjoint, so frequencies add up.
+        assert_close(frequency, weak_nt.frequency() + strong.frequency());
+        assert!(frequency > 0.0 && frequency < 1.0);
+    }
+
+    #[test]
+    fn predealt_distribution_test() {
+        let partner = Cards::from_str("AKQ2 KJ2 AQ3 K32").unwrap();
+        let deck = Cards::ALL - partner;
+        let distribution = HandDistribution::new(deck).unwrap();
+        assert_eq!(distribution.total(), binomial(39, 13));
+        // Partner holds 22 HCP: only 18 remain in the deck.
+        let hcp = distribution.hcp_distribution();
+        assert!(hcp[19..].iter().all(|&probability| probability == 0.0));
+        assert!(hcp[18] > 0.0);
+
+        let known = Cards::from_str("AKQJT98765432...").unwrap();
+        let distribution = HandDistribution::with_predeal(known, Cards::ALL - known).unwrap();
+        assert_eq!(distribution.total(), 1);
+        assert_eq!(distribution.count([13, 0, 0, 0], 10), 1);
+    }
+
+    #[test]
+    fn predealt_distribution_errors_test() {
+        let known = Cards::from_str("AKQ").unwrap();
+        assert!(HandDistribution::with_predeal(known, Cards::ALL).is_err());
+        assert!(HandDistribution::new(Cards::from_str("AKQ").unwrap()).is_err());
+    }
+}
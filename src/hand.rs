@@ -178,14 +178,9 @@ impl TryFrom<Cards> for Hand {
 impl FromStr for Hand {
     type Err = DealerError;
 
+    /// Reads a hand in the [`CardNotation::ENGLISH`] notation.
     fn from_str(hand: &str) -> Result<Hand, Self::Err> {
-        let cards = Cards::from_str(hand)?;
-        if cards.len() != 13 {
-            return Err(DealerError::new(format!(
-                "wrong number of cards for a Bridge hand: {cards}"
-            )));
-        }
-        Ok(Hand { cards })
+        CardNotation::ENGLISH.parse_hand(hand)
     }
 }
 
@@ -310,6 +305,13 @@ impl HandType {
         self.shape.is_member(hand) && self.hcp_range.contains(hand.hcp())
     }
 
+    /// Check if a hand with the given shape pattern and HCP would match this `HandType`.
+    #[must_use]
+    #[inline]
+    pub fn check_pattern(&self, pattern: ShapePattern, hcp: u8) -> bool {
+        self.shape.contains_pattern(pattern) && self.hcp_range.contains(hcp)
+    }
+
     /// Get the length ranges for each suit based on the accepted shapes.
     #[must_use]
     #[inline]
@@ -329,6 +331,19 @@ impl HandType {
     pub fn builder() -> HandTypeBuilder {
         HandTypeBuilder::new()
     }
+
+    /// The hands accepted both by `self` and by `other`, or `None` if there are none.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min_hcp = self.hcp_range.min().max(other.hcp_range.min());
+        let max_hcp = self.hcp_range.max().min(other.hcp_range.max());
+        let shape = self.shape.intersection(&other.shape);
+        if min_hcp > max_hcp || shape.is_empty() {
+            None
+        } else {
+            Some(Self::new(shape, HcpRange::new(min_hcp, max_hcp)))
+        }
+    }
 }
 
 /// Represents a descriptor for a set of possible hands with accepted shapes and High Card Point (HCP) ranges.
@@ -372,7 +387,7 @@ impl HandType {
 /// # Ok(())
 /// # }
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct HandDescriptor {
     possible_hands: Vec<HandType>,
 }
@@ -386,11 +401,44 @@ impl HandDescriptor {
             .any(|hand_type| hand_type.check(hand))
     }
 
+    /// Check if a hand with the given shape pattern and HCP would match any of the possible hand types.
+    #[must_use]
+    pub fn check_pattern(&self, pattern: ShapePattern, hcp: u8) -> bool {
+        self.possible_hands
+            .iter()
+            .any(|hand_type| hand_type.check_pattern(pattern, hcp))
+    }
+
     /// Create a new `HandDescriptor` with the specified list of possible hand types.
     #[must_use]
     pub fn new(possible_hands: Vec<HandType>) -> Self {
         Self { possible_hands }
     }
+
+    /// The hands accepted both by `self` and by `other`: every pair of hand types is
+    /// intersected, dropping the pairs that have no hand in common.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(
+            self.possible_hands
+                .iter()
+                .cartesian_product(&other.possible_hands)
+                .filter_map(|(hand_type, other)| hand_type.intersection(other))
+                .collect(),
+        )
+    }
+
+    /// The hand types accepted by this descriptor.
+    #[must_use]
+    pub fn hand_types(&self) -> &[HandType] {
+        &self.possible_hands
+    }
+}
+
+impl From<HandType> for HandDescriptor {
+    fn from(hand_type: HandType) -> Self {
+        Self::new(vec![hand_type])
+    }
 }
 
 #[derive(Default, Debug)]
@@ -492,6 +540,20 @@ impl HandTypeBuilder {
 mod test {
     use super::*;
     use std::str::FromStr;
+
+    #[test]
+    fn test_descriptor_intersection() {
+        let balanced = HandTypeBuilder::balanced(12, 17).build();
+        let strong = HandType::builder().with_range(15, 37).build();
+        let weak = HandType::builder().with_range(0, 11).build();
+        let both = HandDescriptor::new(vec![balanced])
+            .intersection(&HandDescriptor::new(vec![strong, weak]));
+        assert_eq!(both.hand_types().len(), 1);
+        assert_eq!(both.hand_types()[0].hcp_range(), HcpRange::new(15, 17));
+        assert!(both.check(Hand::from_str("AKQ AJT9 T3 K952").unwrap()));
+        assert!(!both.check(Hand::from_str("AKQJ8 AJT98 3 32").unwrap()));
+    }
+
     #[test]
     fn test_builder_pattern() {
         let mut builder = HandTypeBuilder::new();
@@ -518,6 +580,26 @@ mod test {
         assert_eq!(hand.clen(), 2);
     }
 
+    #[test]
+    fn parses_any_case_and_symbols_test() {
+        let hand = Hand::from_str("AKQ JT9 876 5432").unwrap();
+        assert_eq!(Hand::from_str("akq jt9 876 5432").unwrap(), hand);
+        assert_eq!(Hand::from_str("♠AKQ ♥JT9 ♦876 ♣5432").unwrap(), hand);
+        assert_eq!(Hand::from_str("s akq h jt9 d 876 c 5432").unwrap(), hand);
+        assert!(Hand::from_str("akq jt9 876 543").is_err());
+    }
+
+    #[test]
+    fn parses_voids_test() {
+        let hand = Hand::from_str("AKQ32.-.KQJ32.432").unwrap();
+        assert_eq!(hand.hlen(), 0);
+        assert_eq!(hand.dlen(), 5);
+        assert_eq!(hand.clen(), 3);
+        assert_eq!(Hand::from_str("AKQ32 - KQJ32 432").unwrap(), hand);
+        assert_eq!(Hand::from_str("AKQ32..KQJ32.432").unwrap(), hand);
+        assert!(Hand::from_str("AKQ32 - KQJ32 432 -").is_err());
+    }
+
     #[test]
     fn test_hcp_calculation() {
         let hand = Hand::from_str("AKQJ.KQJ2.AK2.32").unwrap();
@@ -0,0 +1,373 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+
+/// What an auction tells about a single hand: an HCP range and a length range for every
+/// suit, possibly restricted to balanced shapes.
+///
+/// Inferences are built by [`Bidding::natural_inferences`], which reads the auction with
+/// a small set of natural-bidding heuristics; use [`HandInference::hand_descriptor`] to
+/// turn them into constraints for a [`Dealer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandInference {
+    min_hcp: u8,
+    max_hcp: u8,
+    lengths: [(u8, u8); SUITS],
+    balanced: bool,
+}
+
+impl Default for HandInference {
+    fn default() -> Self {
+        Self {
+            min_hcp: 0,
+            max_hcp: 37,
+            lengths: [(0, 13); SUITS],
+            balanced: false,
+        }
+    }
+}
+
+impl HandInference {
+    /// The minimum HCP shown.
+    #[must_use]
+    pub fn min_hcp(&self) -> u8 {
+        self.min_hcp
+    }
+
+    /// The maximum HCP shown.
+    #[must_use]
+    pub fn max_hcp(&self) -> u8 {
+        self.max_hcp
+    }
+
+    /// The range of lengths shown in `suit`.
+    #[must_use]
+    pub fn len_range(&self, suit: Suit) -> LenRange {
+        let (min, max) = self.lengths[suit as usize];
+        LenRange::new(min, max)
+    }
+
+    /// Whether the hand showed a balanced shape.
+    #[must_use]
+    pub fn is_balanced(&self) -> bool {
+        self.balanced
+    }
+
+    /// Whether the auction told nothing about the hand.
+    #[must_use]
+    pub fn is_unconstrained(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The hands compatible with the inference, or `None` if the auction showed
+    /// something contradictory (e.g. a hand with both 12+ and at most 11 HCP).
+    #[must_use]
+    pub fn hand_descriptor(&self) -> Option<HandDescriptor> {
+        if self.min_hcp > self.max_hcp {
+            return None;
+        }
+        let shape = if self.lengths == [(0, 13); SUITS] {
+            Shape::All
+        } else {
+            let lengths = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs]
+                .map(|suit| self.len_range(suit));
+            Shape::Custom(Shapes::from(&lengths))
+        };
+        if shape.is_empty() {
+            return None;
+        }
+        let hand_type = HandType::new(shape, HcpRange::new(self.min_hcp, self.max_hcp));
+        if self.balanced {
+            HandTypeBuilder::balanced(0, 37)
+                .build()
+                .intersection(&hand_type)
+                .map(Into::into)
+        } else {
+            Some(hand_type.into())
+        }
+    }
+
+    fn hcp(&mut self, min: u8, max: u8) {
+        self.min_hcp = self.min_hcp.max(min);
+        self.max_hcp = self.max_hcp.min(max);
+    }
+
+    fn at_least(&mut self, strain: Strain, length: u8) {
+        if let Ok(suit) = Suit::try_from(strain) {
+            let (min, _) = &mut self.lengths[suit as usize];
+            *min = (*min).max(length);
+        }
+    }
+
+    fn at_most(&mut self, strain: Strain, length: u8) {
+        if let Ok(suit) = Suit::try_from(strain) {
+            let (_, max) = &mut self.lengths[suit as usize];
+            *max = (*max).min(length);
+        }
+    }
+
+    fn balanced(&mut self, min: u8, max: u8) {
+        self.hcp(min, max);
+        self.balanced = true;
+    }
+}
+
+impl Bidding {
+    /// Reads the auction with a built-in set of natural-bidding heuristics and returns
+    /// what it shows about every hand, indexed by [`Seat`].
+    ///
+    /// The heuristics cover:
+    /// - passes before the opening (at most 11 HCP) and passes of partner's one-level
+    ///   suit opening (at most 5 HCP);
+    /// - openings: 1NT 15-17 and 2NT 20-21 balanced, one of a major 11-21 with 5+ cards,
+    ///   one of a minor 11-21 with 3+ cards, 2♣ 22+, weak twos 5-10 with 6 cards,
+    ///   three-level preempts 5-10 and four-level preempts 5-12 with 7+ cards;
+    /// - responses: 6+ HCP, 4+ cards in a new suit, 3+ cards in a raise, 1NT 6-10,
+    ///   2NT 11-12 and 3NT 13-15;
+    /// - overcalls: 8-17 with 5+ cards at the one level, 10-17 at the two level, jump
+    ///   overcalls 5-10 with 6+ cards, 1NT 15-18 balanced, takeout doubles 12+;
+    /// - later bids: 4+ cards in a new suit, 3+ in partner's suit, 6+ in a rebid suit.
+    ///
+    /// # Example
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>>{
+    /// let auction = Bidding::from_str("1NT P 3NT AP")?;
+    /// let inferences = auction.natural_inferences(Seat::North);
+    /// assert_eq!(inferences[Seat::North as usize].min_hcp(), 15);
+    /// assert!(inferences[Seat::North as usize].is_balanced());
+    /// assert_eq!(inferences[Seat::South as usize].max_hcp(), 15);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn natural_inferences(&self, dealer: Seat) -> [HandInference; 4] {
+        let mut inferences = [HandInference::default(); 4];
+        // Index in the auction of the opening bid.
+        let mut opening: Option<usize> = None;
+        let mut last_contract: Option<(u8, Strain)> = None;
+        // Strains named so far by every seat, and whether the seat made a non-pass call.
+        let mut strains: [Vec<Strain>; 4] = Default::default();
+        let mut has_acted = [false; 4];
+
+        for (index, &bid) in self.iter().enumerate() {
+            let seat = (dealer + index) as usize;
+            let partner = (dealer + index + 2) as usize;
+            let inference = &mut inferences[seat];
+            let Some(opening_index) = opening else {
+                match bid {
+                    Bid::Pass => inference.hcp(0, 11),
+                    Bid::Contract(level, strain) => {
+                        opening_bid(inference, level.get(), strain);
+                        opening = Some(index);
+                        last_contract = Some((level.get(), strain));
+                        strains[seat].push(strain);
+                        has_acted[seat] = true;
+                    }
+                    Bid::Double | Bid::Redouble => {}
+                }
+                continue;
+            };
+            let opener = (dealer + opening_index) as usize;
+            let our_side = opener == seat || opener == partner;
+
+            match bid {
+                Bid::Pass => {
+                    // Responder passing partner's one-level suit opening without intervention.
+                    if opener == partner
+                        && index == opening_index + 2
+                        && self.get(opening_index + 1) == Some(&Bid::Pass)
+                        && matches!(last_contract, Some((1, strain)) if strain != Strain::NoTrumps)
+                    {
+                        inference.hcp(0, 5);
+                    }
+                }
+                Bid::Double => {
+                    if our_side && seat != opener && !has_acted[seat] {
+                        inference.hcp(6, 37);
+                    } else if !our_side
+                        && !has_acted[seat]
+                        && !has_acted[partner]
+                        && last_contract.is_some_and(|(level, _)| level <= 2)
+                    {
+                        inference.hcp(12, 37);
+                    }
+                    has_acted[seat] = true;
+                }
+                Bid::Redouble => has_acted[seat] = true,
+                Bid::Contract(level, strain) => {
+                    let level = level.get();
+                    let cheapest = match last_contract {
+                        Some((last_level, last_strain)) if strain > last_strain => last_level,
+                        Some((last_level, _)) => last_level + 1,
+                        None => 1,
+                    };
+                    let jump = level > cheapest;
+                    let first_call = !has_acted[seat];
+                    if our_side && seat != opener && first_call {
+                        // First response to partner's opening.
+                        inference.hcp(6, 37);
+                        if strain == Strain::NoTrumps {
+                            match level {
+                                1 => inference.hcp(6, 10),
+                                2 => inference.hcp(11, 12),
+                                3 => inference.hcp(13, 15),
+                                _ => {}
+                            }
+                        }
+                    } else if !our_side && first_call && !has_acted[partner] {
+                        // Overcall of the opponents' opening.
+                        if !strains[opener].contains(&strain) {
+                            overcall(inference, level, strain, jump);
+                        }
+                        strains[seat].push(strain);
+                        has_acted[seat] = true;
+                        last_contract = Some((level, strain));
+                        continue;
+                    }
+                    if strains[seat].contains(&strain) {
+                        inference.at_least(strain, 6);
+                    } else if strains[partner].contains(&strain) {
+                        inference.at_least(strain, 3);
+                    } else if !strains[(seat + 1) % 4].contains(&strain)
+                        && !strains[(seat + 3) % 4].contains(&strain)
+                    {
+                        inference.at_least(strain, 4);
+                    }
+                    strains[seat].push(strain);
+                    has_acted[seat] = true;
+                    last_contract = Some((level, strain));
+                }
+            }
+        }
+        inferences
+    }
+}
+
+fn opening_bid(inference: &mut HandInference, level: u8, strain: Strain) {
+    match (level, strain) {
+        (1, Strain::NoTrumps) => inference.balanced(15, 17),
+        (2, Strain::NoTrumps) => inference.balanced(20, 21),
+        (1, Strain::Spades | Strain::Hearts) => {
+            inference.hcp(11, 21);
+            inference.at_least(strain, 5);
+        }
+        (1, _) => {
+            inference.hcp(11, 21);
+            inference.at_least(strain, 3);
+        }
+        (2, Strain::Clubs) => inference.hcp(22, 37),
+        (2, _) => {
+            inference.hcp(5, 10);
+            inference.at_least(strain, 6);
+            inference.at_most(strain, 6);
+        }
+        (3, Strain::NoTrumps) | (5.., _) => {}
+        (3, _) => {
+            inference.hcp(5, 10);
+            inference.at_least(strain, 7);
+        }
+        (_, _) => {
+            inference.hcp(5, 12);
+            inference.at_least(strain, 7);
+        }
+    }
+}
+
+fn overcall(inference: &mut HandInference, level: u8, strain: Strain, jump: bool) {
+    match (level, strain) {
+        (1, Strain::NoTrumps) => inference.balanced(15, 18),
+        (_, Strain::NoTrumps) => {}
+        _ if jump => {
+            inference.hcp(5, 10);
+            inference.at_least(strain, 6);
+        }
+        (1, _) => {
+            inference.hcp(8, 17);
+            inference.at_least(strain, 5);
+        }
+        (2, _) => {
+            inference.hcp(10, 17);
+            inference.at_least(strain, 5);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn infer(auction: &str, dealer: Seat) -> [HandInference; 4] {
+        Bidding::from_str(auction)
+            .unwrap()
+            .natural_inferences(dealer)
+    }
+
+    #[test]
+    fn openings_and_responses_test() {
+        let inferences = infer("P 1S P 2H P 4S AP", Seat::West);
+        let [north, east, south, west] = inferences;
+        assert_eq!((west.min_hcp(), west.max_hcp()), (0, 11));
+        assert_eq!((north.min_hcp(), north.max_hcp()), (11, 21));
+        // Opened 1S and rebid spades.
+        assert_eq!(north.len_range(Suit::Spades).min(), 6);
+        assert_eq!(east.max_hcp(), 37);
+        assert_eq!(south.min_hcp(), 6);
+        assert_eq!(south.len_range(Suit::Hearts).min(), 4);
+        assert!(south.len_range(Suit::Spades).min() == 0);
+
+        let [north, _, south, _] = infer("1D P 1NT AP", Seat::North);
+        assert_eq!(north.len_range(Suit::Diamonds).min(), 3);
+        assert_eq!((south.min_hcp(), south.max_hcp()), (6, 10));
+
+        let [north, _, south, _] = infer("1H P P P", Seat::North);
+        assert_eq!(north.len_range(Suit::Hearts).min(), 5);
+        assert_eq!(south.max_hcp(), 5);
+    }
+
+    #[test]
+    fn competitive_auctions_test() {
+        let [north, east, south, west] = infer("1C 1S X 3S P P 4H AP", Seat::North);
+        assert_eq!(north.len_range(Suit::Clubs).min(), 3);
+        assert_eq!((east.min_hcp(), east.max_hcp()), (8, 17));
+        assert_eq!(east.len_range(Suit::Spades).min(), 5);
+        // Negative double and raise: nothing special inferred but the response values.
+        assert_eq!(south.min_hcp(), 6);
+        assert_eq!(west.len_range(Suit::Spades).min(), 3);
+        assert_eq!(south.len_range(Suit::Hearts).min(), 4);
+
+        let [north, east, _, _] = infer("1D 2S P 4S AP", Seat::North);
+        assert_eq!(north.max_hcp(), 21);
+        assert_eq!((east.min_hcp(), east.max_hcp()), (5, 10));
+        assert_eq!(east.len_range(Suit::Spades).min(), 6);
+
+        let [_, east, _, _] = infer("1H 1NT P 3NT AP", Seat::North);
+        assert!(east.is_balanced());
+        assert_eq!((east.min_hcp(), east.max_hcp()), (15, 18));
+
+        let [_, east, _, _] = infer("1H X AP", Seat::North);
+        assert_eq!(east.min_hcp(), 12);
+    }
+
+    #[test]
+    fn preempts_and_descriptors_test() {
+        let [north, ..] = infer("2H AP", Seat::North);
+        assert_eq!(north.len_range(Suit::Hearts).as_range(), 6..=6);
+        let descriptor = north.hand_descriptor().unwrap();
+        assert!(descriptor.check(Hand::from_str("83 KQJ932 J32 32").unwrap()));
+        assert!(!descriptor.check(Hand::from_str("83 KQJ9532 J3 32").unwrap()));
+        assert!(!descriptor.check(Hand::from_str("A3 AKQJ32 J32 32").unwrap()));
+
+        let [north, ..] = infer("1NT AP", Seat::North);
+        let descriptor = north.hand_descriptor().unwrap();
+        assert!(descriptor.check(Hand::from_str("AQ3 KJ93 K32 Q32").unwrap()));
+        assert!(!descriptor.check(Hand::from_str("AQ KJ9432 K32 Q3").unwrap()));
+
+        let [_, east, ..] = infer("P", Seat::North);
+        assert!(east.is_unconstrained());
+        assert!(east.hand_descriptor().is_some());
+    }
+}
@@ -105,28 +105,47 @@
  Only small modifications were made by me on that module.
 */
 
+mod auction;
+mod auto_bidder;
+#[cfg(feature = "dds")]
+mod auto_player;
 #[cfg(feature = "bbo")]
 mod bbo;
 #[cfg(feature = "bbo_async")]
 mod bbo_async;
 #[cfg(any(feature = "bbo", feature = "bbo_async"))]
 mod bbohelpers;
+pub mod bidding_system;
 pub mod card;
 mod contract;
 mod deal;
+mod dealing_machine;
 mod dealproduction;
+#[cfg(feature = "lin")]
+mod discipline;
 mod error;
 mod evaluator;
+pub mod export;
+mod format;
+mod frequency;
 mod hand;
+mod inference;
 #[cfg(feature = "lin")]
 mod linparser;
+mod notation;
+mod numbering;
+mod opening_lead;
+mod pbn;
 #[cfg(feature = "dds")]
 pub mod performance_analysis;
+mod play;
+mod rbn;
 mod shape;
 mod shapeparser;
-#[cfg(feature = "dds")]
 pub mod simulation;
 mod utils;
+#[cfg(feature = "lin")]
+mod vugraph;
 
 pub mod prelude {
     #[cfg(feature = "dds")]
@@ -141,25 +160,44 @@ pub mod prelude {
     pub const RANKS: u8 = 13;
     pub use crate::contract::*;
     pub const NUMBER_OF_HANDS: usize = 4;
+    pub use crate::auction::*;
+    pub use crate::auto_bidder::*;
+    #[cfg(feature = "dds")]
+    pub use crate::auto_player::*;
     #[cfg(feature = "bbo")]
     pub use crate::bbo::*;
     #[cfg(feature = "bbo_async")]
     pub use crate::bbo_async::*;
     #[cfg(any(feature = "bbo", feature = "bbo_async"))]
     pub use crate::bbohelpers::*;
+    pub use crate::bidding_system::*;
     pub use crate::card::*;
     pub use crate::deal::*;
+    pub use crate::dealing_machine::*;
     pub use crate::dealproduction::*;
+    #[cfg(feature = "lin")]
+    pub use crate::discipline::*;
     pub use crate::error::*;
     pub use crate::evaluator::*;
+    pub use crate::export::*;
+    pub use crate::format::*;
+    pub use crate::frequency::*;
     pub use crate::hand::*;
+    pub use crate::inference::*;
     #[cfg(feature = "lin")]
     pub use crate::linparser::*;
+    pub use crate::notation::*;
+    pub use crate::numbering::*;
+    pub use crate::opening_lead::*;
+    pub use crate::pbn::*;
+    pub use crate::play::*;
+    pub use crate::rbn::*;
     pub use crate::shape::*;
     pub use crate::shapeparser::*;
-    #[cfg(feature = "dds")]
     pub use crate::simulation::*;
     pub use crate::utils::*;
+    #[cfg(feature = "lin")]
+    pub use crate::vugraph::*;
     pub(crate) use itertools::Itertools;
     pub use std::str::FromStr;
 
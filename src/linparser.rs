@@ -3,47 +3,102 @@
 
 #![allow(dead_code)]
 use crate::prelude::*;
-use dds::analyseplay::PlayTraceBin;
-#[cfg(feature = "dds")]
-use dds::utils::{BuildSequenceError, RankSeq, SuitSeq, SEQUENCE_LENGTH};
-use fmt::Display;
-use log::error;
-use regex::Regex;
-use std::{
-    cell::LazyCell,
-    num::{NonZeroU8, ParseIntError},
-};
 /* Lin reference:
  pn|simodra,fra97,matmont,thevava|st||md|3S34JH258TQKD2JQC7,S27TH69D679TKAC23,S6QH47JD458C468JA,|rh||ah|Board 1|sv|o|mb|p|mb|1S|mb|2H|mb|2S|mb|3H|mb|4S|mb|p|mb|p|mb|p|pg||pc|C7|pc|C3|pc|CA|pc|C5|pg||pc|H4|pc|HA|pc|H5|pc|H6|pg||pc|SA|pc|S3|pc|S2|pc|S6|pg||pc|SK|pc|S4|pc|S7|pc|SQ|pg||pc|D3|pc|D2|pc|DA|pc|D5|pg||pc|DK|pc|D4|pc|H3|pc|DJ|pg||pc|C2|pc|C4|pc|C9|pc|SJ|pg||pc|HK|mc|11|
 */
 
-/// A struct for parsing lin files.
-/// You can feed a lin file and parse it,
-/// expecting to obtai a [`ParsedLin`] struct.
-/// The struct expects a something that can be turn into a
-/// Iterator<Item=char> for parsing it.
+/// A `tag|value|` pair of a lin file, e.g. `mb|1S|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinTag<'a> {
+    tag: &'a str,
+    value: &'a str,
+    offset: usize,
+}
+
+impl<'a> LinTag<'a> {
+    pub(crate) fn new(tag: &'a str, value: &'a str, offset: usize) -> Self {
+        Self { tag, value, offset }
+    }
+
+    /// The name of the tag, without the whitespace around it.
+    #[must_use]
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &'a str {
+        self.value
+    }
+
+    /// Byte offset of the tag in the lin file.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// A streaming tokenizer for lin files.
+/// It yields every [`LinTag`] of the file, in the order they appear, ignoring the whitespace
+/// (e.g. newlines) between a value and the next tag. After the first error it yields nothing more.
+///
 /// You'll interact very rarely with this struct directly.
 /// Instead, you'll use the [`LinDeal`] struct and its `from_str` method.
-struct LinParser<T: IntoIterator<Item = char>> {
-    stream: T,
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// let mut tags = LinTokenizer::new("ah|Board 1|\nmb|1S|mb|p");
+/// let tag = tags.next().unwrap().unwrap();
+/// assert_eq!((tag.tag(), tag.value(), tag.offset()), ("ah", "Board 1", 0));
+/// assert_eq!(tags.next().unwrap().unwrap().offset(), 12);
+/// // The value of the last tag is not terminated.
+/// assert_eq!(tags.next().unwrap().unwrap_err().offset(), Some(18));
+/// assert!(tags.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinTokenizer<'a> {
+    lin: &'a str,
+    position: usize,
 }
 
-/// This structure represents a full lin file parsed.
-/// The result if a struct containing:
-/// - the players
-/// - the hands of the players
-/// - the board number
-/// - the bidding (if present)
-/// - the play sequence (if present)
-///
-/// This is a 'low level' struct, and you'll interact with it very rarely.
-/// Instead, you'll use the `LinDeal` struct and its methods.
-pub struct ParsedLin {
-    players: String,
-    hands: String,
-    number: u8,
-    bidding: String,
-    play_sequence: String,
+impl<'a> LinTokenizer<'a> {
+    #[must_use]
+    pub fn new(lin: &'a str) -> Self {
+        Self { lin, position: 0 }
+    }
+
+    fn syntax_error(&mut self, offset: usize) -> ParseLinError {
+        self.position = self.lin.len();
+        ParseLinError::new(ParseLinErrorKind::Syntax, &self.lin[offset..]).at(offset)
+    }
+}
+
+impl<'a> Iterator for LinTokenizer<'a> {
+    type Item = Result<LinTag<'a>, ParseLinError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.lin.get(self.position..)?;
+        let trimmed = rest.trim_start();
+        if trimmed.is_empty() {
+            self.position = self.lin.len();
+            return None;
+        }
+        let offset = self.position + rest.len() - trimmed.len();
+        let Some((tag, tail)) = trimmed.split_once('|') else {
+            return Some(Err(self.syntax_error(offset)));
+        };
+        let Some((value, _)) = tail.split_once('|') else {
+            return Some(Err(self.syntax_error(offset)));
+        };
+        self.position = offset + tag.len() + value.len() + 2;
+        let tag = tag.trim_end();
+        if tag.is_empty() {
+            return Some(Err(self.syntax_error(offset)));
+        }
+        Some(Ok(LinTag { tag, value, offset }))
+    }
 }
 
 /// This struct represents a deal parsed from a lin file.
@@ -56,15 +111,73 @@ pub struct ParsedLin {
 /// - the play sequence (if present)
 /// - the dealer
 ///
+/// Tags that are not interpreted are kept, in the order they appear.
+///
 /// You can create a lin deal starting from a str with
 #[derive(Debug)]
 pub struct LinDeal {
     players: [String; 4],
     hands: Hands,
-    number: u8,
+    /// The `md` tag as read, since the hands may be unsorted or miss the last one.
+    md: String,
+    number: u32,
     bidding: Option<Bidding>,
     play_sequence: Option<PlaySequence>,
     dealer: Seat,
+    claim: Option<u8>,
+    vulnerability: Vulnerability,
+    title: String,
+    header: String,
+    comments: Vec<LinComment>,
+    extra_tags: Vec<ExtraTag>,
+}
+
+/// A tag that is not interpreted, with the number of bids, of cards played, of
+/// header tags (`pn`, `md`, `rh`, `ah` and `sv`) and of comments before it, so that it
+/// can be written back where it was.
+#[derive(Debug, Clone)]
+struct ExtraTag {
+    tag: String,
+    value: String,
+    bids: usize,
+    cards: usize,
+    headers: usize,
+    comments: usize,
+}
+
+impl ExtraTag {
+    fn position(&self) -> (usize, usize, usize) {
+        (self.bids, self.cards, self.headers)
+    }
+}
+
+/// A comment of a lin file (`nt` tag), e.g. a remark of a vugraph commentator,
+/// with the number of bids and of cards played before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinComment {
+    bids: usize,
+    cards: usize,
+    headers: usize,
+    text: String,
+}
+
+impl LinComment {
+    /// Number of bids made before the comment.
+    #[must_use]
+    pub fn bids(&self) -> usize {
+        self.bids
+    }
+
+    /// Number of cards played before the comment.
+    #[must_use]
+    pub fn cards(&self) -> usize {
+        self.cards
+    }
+
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
 }
 
 impl LinDeal {
@@ -79,7 +192,7 @@ impl LinDeal {
     }
 
     #[must_use]
-    pub fn number(&self) -> u8 {
+    pub fn number(&self) -> u32 {
         self.number
     }
 
@@ -98,55 +211,278 @@ impl LinDeal {
         self.dealer
     }
 
+    /// The vulnerability of the board: the one of the `sv` tag or, if missing,
+    /// the one of the board number.
     #[must_use]
-    pub fn contract(&self) -> Option<Contract> {
-        let mut contract = None;
-        if let Some(ref bidding) = self.bidding {
-            let mut declarer = None;
-            let mut last_bidder = self.dealer as usize + bidding.len();
-            let mut doubled = Doubled::NotDoubled;
-            let mut strain_found = (false, Strain::NoTrumps, 7u8);
-            for bid in bidding.iter().rev() {
-                if strain_found.0 {
-                    match *bid {
-                        Bid::Contract(_, strain) if strain == strain_found.1 => {
-                            last_bidder -= 1;
-                            declarer = Some(last_bidder);
-                        }
-                        _ => last_bidder -= 1,
-                    }
-                } else {
-                    match *bid {
-                        Bid::Double => {
-                            doubled = Doubled::Doubled;
-                            last_bidder -= 1;
-                        }
-                        Bid::Pass => last_bidder -= 1,
-                        Bid::Redouble => {
-                            doubled = Doubled::Redoubled;
-                            last_bidder -= 1;
-                        }
-                        Bid::Contract(level, strain) => {
-                            strain_found = (true, strain, level.into());
-                            last_bidder -= 1;
-                            declarer = Some(last_bidder);
-                        }
+    pub fn vulnerability(&self) -> Vulnerability {
+        self.vulnerability
+    }
+
+    /// The title of the board (`ah` tag), e.g. `Board 12`.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The header shown above the board (`rh` tag), usually empty.
+    #[must_use]
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// The comments (`nt` tags), in the order they appear.
+    #[must_use]
+    pub fn comments(&self) -> &[LinComment] {
+        &self.comments
+    }
+
+    /// The tags of the lin file that are not interpreted, e.g. `st` or `pg`,
+    /// as `(tag, value)` pairs in the order they appear.
+    pub fn extra_tags(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.extra_tags
+            .iter()
+            .map(|extra| (extra.tag.as_str(), extra.value.as_str()))
+    }
+
+    /// Builds a deal from the tags of a lin file, as yielded by a [`LinTokenizer`].
+    ///
+    /// # Errors
+    ///
+    /// If the hands (`md`) or the board number (`ah`) are missing, or if a tag has an
+    /// invalid value. The error carries the offset of the wrong tag.
+    pub fn from_tags<'a>(
+        tags: impl IntoIterator<Item = LinTag<'a>>,
+    ) -> Result<Self, ParseLinError> {
+        let mut players = None;
+        let mut dealer_and_hands = None;
+        let mut number = None;
+        let mut bidding = Bidding::new();
+        let mut sequence = Vec::new();
+        let mut claim = None;
+        let mut vulnerability = None;
+        let mut title = String::new();
+        let mut header = String::new();
+        let mut comments = Vec::new();
+        let mut extra_tags = Vec::new();
+        let mut headers = 0;
+        for tag in tags {
+            let error = |kind| ParseLinError::new(kind, tag.value).at(tag.offset);
+            headers += usize::from(matches!(tag.tag, "pn" | "md" | "rh" | "ah" | "sv"));
+            match tag.tag {
+                "pn" => {
+                    let names: Vec<&str> = tag.value.split(',').map(str::trim).collect();
+                    players =
+                        Some(lin_players(&names).ok_or_else(|| error(ParseLinErrorKind::Player))?);
+                }
+                "md" => {
+                    dealer_and_hands = Some((
+                        lin_dealer_and_hands(tag.value)
+                            .ok_or_else(|| error(ParseLinErrorKind::Hands))?,
+                        tag.value,
+                    ));
+                }
+                "ah" => {
+                    number = Some(
+                        lin_board_number(tag.value)
+                            .ok_or_else(|| error(ParseLinErrorKind::Number))?,
+                    );
+                    tag.value.clone_into(&mut title);
+                }
+                "rh" => tag.value.clone_into(&mut header),
+                "sv" => {
+                    vulnerability = Some(
+                        lin_vulnerability(tag.value)
+                            .ok_or_else(|| error(ParseLinErrorKind::Vulnerability))?,
+                    );
+                }
+                "nt" => comments.push(LinComment {
+                    bids: bidding.len(),
+                    cards: sequence.len(),
+                    headers,
+                    text: tag.value.to_owned(),
+                }),
+                "mb" => lin_bid(tag.value)
+                    .and_then(|bid| bidding.push(bid))
+                    .map_err(|e| error(e.into()))?,
+                "an" if !bidding.is_empty() => {
+                    bidding.set_alert(bidding.len() - 1, tag.value);
+                }
+                "pc" => {
+                    let card = tag.value.trim();
+                    if card.is_empty() {
+                        return Err(error(ParseLinErrorKind::Card));
                     }
+                    sequence
+                        .push(Card::from_str(card).map_err(|_| error(ParseLinErrorKind::Card))?);
+                }
+                "mc" => {
+                    claim =
+                        Some(lin_claim(tag.value).ok_or_else(|| error(ParseLinErrorKind::Claim))?);
                 }
+                _ => extra_tags.push(ExtraTag {
+                    tag: tag.tag.to_owned(),
+                    value: tag.value.to_owned(),
+                    bids: bidding.len(),
+                    cards: sequence.len(),
+                    headers,
+                    comments: comments.len(),
+                }),
             }
-            if let Some(declarer) = declarer {
-                let declarer = declarer.into();
-                let vuln = Vulnerable::from_number_and_seat(self.number, declarer);
-                contract = Some(Contract::new(
-                    strain_found.2,
-                    strain_found.1,
-                    declarer,
-                    vuln,
-                    doubled,
-                ));
+        }
+        let ((dealer, hands), md) = dealer_and_hands
+            .ok_or_else(|| ParseLinError::new(ParseLinErrorKind::Hands, "missing `md` tag"))?;
+        let number = number
+            .ok_or_else(|| ParseLinError::new(ParseLinErrorKind::Number, "missing `ah` tag"))?;
+        Ok(Self {
+            players: players.unwrap_or_else(|| ["NN"; NUMBER_OF_HANDS].map(String::from)),
+            hands,
+            md: md.to_owned(),
+            number,
+            bidding: Some(bidding),
+            play_sequence: Some(PlaySequence::new(sequence)),
+            dealer,
+            claim,
+            vulnerability: vulnerability.unwrap_or_else(|| Vulnerability::for_board(number)),
+            title,
+            header,
+            comments,
+            extra_tags,
+        })
+    }
+
+    pub(crate) fn set_players(&mut self, players: [String; 4]) {
+        self.players = players;
+    }
+
+    /// Mutable access to the bidding, e.g. to annotate it with alerts.
+    pub fn bidding_mut(&mut self) -> Option<&mut Bidding> {
+        self.bidding.as_mut()
+    }
+
+    /// Number of tricks claimed, if the play ended with a claim.
+    #[must_use]
+    pub fn claim(&self) -> Option<u8> {
+        self.claim
+    }
+
+    /// Writes the deal as a lin file, with players, hands, bidding (with alerts), play,
+    /// claim, comments and the tags not interpreted, so that it can be opened in BBO's
+    /// handviewer. A deal read from a lin file is written back as it was read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>>{
+    /// let lin = "pn|S,W,N,E|st||md|3SAKQJT98765432,HAKQJT98765432,DAKQJT98765432,|rh||ah|Board 1|sv|o|mb|p|mb|p|mb|7S|an|solid|mb|p|mb|p|mb|p|pg||pc|HA|pc|D2|pc|C2|pc|S2|pg||mc|13|";
+    /// let deal = LinDeal::from_str(lin)?;
+    /// assert_eq!(deal.as_lin(), lin);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn as_lin(&self) -> String {
+        // Lin format starts from south.
+        let lin_order = [Seat::South, Seat::West, Seat::North, Seat::East];
+        let headers = [
+            (
+                "pn",
+                lin_order
+                    .iter()
+                    .map(|&seat| self.players[seat as usize].as_str())
+                    .join(","),
+            ),
+            ("md", self.md.clone()),
+            ("rh", self.header.clone()),
+            ("ah", self.title.clone()),
+            ("sv", String::from(lin_sv(self.vulnerability))),
+        ];
+        // Comments and tags not interpreted, `st` and `pg` included, are written right
+        // before the first header, bid or card that follows them, in the order they were
+        // read.
+        let mut extra_tags = self.extra_tags.iter().peekable();
+        let mut notes = Vec::with_capacity(self.comments.len() + self.extra_tags.len());
+        for (index, comment) in self.comments.iter().enumerate() {
+            while let Some(extra) = extra_tags.next_if(|extra| extra.comments <= index) {
+                notes.push((extra.position(), extra.tag.as_str(), &extra.value));
+            }
+            notes.push((
+                (comment.bids, comment.cards, comment.headers),
+                "nt",
+                &comment.text,
+            ));
+        }
+        notes.extend(extra_tags.map(|extra| (extra.position(), extra.tag.as_str(), &extra.value)));
+        let mut notes = notes.into_iter().peekable();
+        let mut push_notes = |tags: &mut Vec<_>, position| {
+            while let Some((_, tag, value)) = notes.next_if(|&(read, _, _)| read <= position) {
+                tags.push((tag, value.clone()));
+            }
+        };
+        let mut tags = Vec::new();
+        for (index, header) in headers.into_iter().enumerate() {
+            push_notes(&mut tags, (0, 0, index));
+            tags.push(header);
+        }
+        let bids = self.bidding.as_ref().map_or(0, Bidding::len);
+        if let Some(ref bidding) = self.bidding {
+            for (index, (bid, alert)) in bidding.iter_with_alerts().enumerate() {
+                push_notes(&mut tags, (index, 0, usize::MAX));
+                tags.push(("mb", bid.as_lin()));
+                if let Some(alert) = alert {
+                    tags.push(("an", alert.to_owned()));
+                }
+            }
+        }
+        if let Some(ref play_sequence) = self.play_sequence {
+            for (index, card) in play_sequence.iter().enumerate() {
+                push_notes(&mut tags, (bids, index, usize::MAX));
+                tags.push(("pc", format!("{:?}{}", card.suit(), card.rankchar())));
+            }
+        }
+        push_notes(&mut tags, (usize::MAX, usize::MAX, usize::MAX));
+        if let Some(tricks) = self.claim {
+            tags.push(("mc", tricks.to_string()));
+        }
+        tags.iter()
+            .map(|(tag, value)| format!("{tag}|{value}|"))
+            .join("")
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> Option<Contract> {
+        self.bidding
+            .as_ref()?
+            .contract(self.dealer, self.vulnerability)
+    }
+
+    /// Replays the cards played at the table, checking that every card is legal.
+    /// The claim, if any, is applied when the play recorded ends at the start of a trick, so
+    /// that the tricks won are the ones of the table result. Returns `None` if no contract
+    /// was reached.
+    ///
+    /// # Errors
+    /// At the first card that could not have been played, or if the claim is not
+    /// consistent with the tricks already won.
+    pub fn play_state(&self) -> Result<Option<PlayState>, PlayError> {
+        let Some(contract) = self.contract() else {
+            return Ok(None);
+        };
+        let mut state = PlayState::new(&Deal::from(self), contract);
+        if let Some(sequence) = &self.play_sequence {
+            state.play_sequence(sequence)?;
+        }
+        // `mc` is the total of the tricks of the declarer, not the ones still to play.
+        if let Some(claim) = self.claim {
+            if state.current_trick().is_empty() && !state.is_finished() {
+                let tricks = claim.checked_sub(state.declarer_tricks()).ok_or_else(|| {
+                    PlayError::new(contract.declarer(), None, PlayErrorKind::TooManyTricks)
+                })?;
+                state.claim(tricks)?;
             }
         }
-        contract
+        Ok(Some(state))
     }
 }
 
@@ -165,31 +501,66 @@ impl std::fmt::Display for LinDeal {
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum ParseLinErrorKind {
+    /// Malformed `tag|value|` structure.
+    Syntax,
     Player,
     Hands,
     Number,
     Bidding(BuildBiddingError),
     Card,
+    Claim,
+    Vulnerability,
+    /// Invalid `vg` header of a multi-board file.
+    Header,
+    /// Invalid room marker (`qx`) of a multi-board file.
+    Room,
 }
 
 #[derive(Debug, Clone)]
 pub struct ParseLinError {
     lin: String,
     kind: ParseLinErrorKind,
+    offset: Option<usize>,
 }
 
 impl ParseLinError {
-    fn new<T: ToString + ?Sized>(kind: ParseLinErrorKind, lin: &T) -> Self {
+    pub(crate) fn new<T: ToString + ?Sized>(kind: ParseLinErrorKind, lin: &T) -> Self {
         Self {
             lin: lin.to_string(),
             kind,
+            offset: None,
         }
     }
+
+    /// Sets the offset of the error in the lin file.
+    pub(crate) fn at(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &ParseLinErrorKind {
+        &self.kind
+    }
+
+    /// Byte offset in the lin file of the tag we were unable to parse, if known.
+    #[must_use]
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
 }
 
 impl std::fmt::Display for ParseLinError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "unable to parse the following lin file:\n{}", self.lin)
+        if let Some(offset) = self.offset {
+            write!(
+                f,
+                "unable to parse the following lin file at byte {offset}:\n{}",
+                self.lin
+            )
+        } else {
+            write!(f, "unable to parse the following lin file:\n{}", self.lin)
+        }
     }
 }
 
@@ -197,10 +568,15 @@ impl std::error::Error for ParseLinError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self.kind {
             ParseLinErrorKind::Bidding(ref e) => Some(e),
-            ParseLinErrorKind::Player
+            ParseLinErrorKind::Syntax
+            | ParseLinErrorKind::Player
             | ParseLinErrorKind::Hands
             | ParseLinErrorKind::Number
-            | ParseLinErrorKind::Card => None,
+            | ParseLinErrorKind::Card
+            | ParseLinErrorKind::Claim
+            | ParseLinErrorKind::Vulnerability
+            | ParseLinErrorKind::Header
+            | ParseLinErrorKind::Room => None,
         }
     }
 }
@@ -213,522 +589,351 @@ impl From<BuildBiddingError> for ParseLinErrorKind {
 impl FromStr for LinDeal {
     type Err = ParseLinError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let players = players(s);
-        // Safety: Players always return a Vec of len 4;
-        let players: [String; 4] = players.try_into().unwrap();
-        let (dealer, hands) = dealer_and_hands(s)?;
-        let number = number(s)?;
-        let bidding = Some(match bidding(s) {
-            Ok(value) => value,
-            Err(e) => {
-                return Err(ParseLinError {
-                    lin: s.to_owned(),
-                    kind: e.into(),
-                })
-            }
-        });
-        let play_sequence = Some(play_sequence(s)?);
+        Self::from_tags(LinTokenizer::new(s).collect::<Result<Vec<_>, _>>()?)
+    }
+}
 
-        Ok(Self {
-            players,
-            hands,
-            number,
-            bidding,
-            play_sequence,
-            dealer,
-        })
+impl From<&LinDeal> for Deal {
+    /// The deal with the board number, dealer, vulnerability, auction and contract
+    /// of the lin file.
+    fn from(lin: &LinDeal) -> Self {
+        let mut deal = Deal::from_hands(*lin.hands.hands()).with_number(lin.number);
+        deal.set_dealer(lin.dealer);
+        deal.set_vuln(lin.vulnerability);
+        deal.set_contract(lin.contract());
+        deal.set_auction(lin.bidding.clone());
+        deal
     }
 }
 
 #[cfg(feature = "dds")]
 impl dds::deal::AsDDSDeal for LinDeal {
     fn to_dds_deal(&self) -> dds::deal::DDSDealRepr {
-        let mut remain_cards = [[0; 4]; 4];
-        for (seat, hand) in self.hands.into_iter().enumerate() {
-            for (index, suit) in hand.into_iter().enumerate() {
-                remain_cards[seat][index] = suit.into_iter().map(|card| 1 << card.rank()).sum();
-            }
-        }
-        remain_cards.into()
+        dds_cards(self.hands.hands().map(|hand| hand.as_cards())).into()
     }
 }
-/// Structure that represents a bidding sequence
-/// made of [`Bid`]s
-#[derive(Debug, Default)]
-pub struct Bidding {
-    bidding: Vec<Bid>,
-}
-
-impl IntoIterator for Bidding {
-    type Item = Bid;
-    type IntoIter = <Vec<Bid> as IntoIterator>::IntoIter;
-    fn into_iter(self) -> Self::IntoIter {
-        self.bidding.into_iter()
-    }
-}
-
-impl Display for Bidding {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for bid in &self.iter().chunks(4) {
-            writeln!(f, "{}", bid.into_iter().format("-"))?;
-        }
-        Ok(())
-    }
-}
-
-/// Enum modelling the possible kinds of errors we
-/// can encounter while parsing a bidding sequence:
-/// either the bid is not a starting bid (e.g. bidding starts with a double),
-/// insufficient, or simply we were unable to parse the last [`Bid`]
-#[derive(Debug, Clone)]
-#[non_exhaustive]
-pub enum BuildBiddingErrorKind {
-    Insufficient,
-    NonStarter,
-    NonExistent(BuildBidError),
-}
-
-impl std::fmt::Display for BuildBiddingErrorKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            BuildBiddingErrorKind::Insufficient => write!(f, "bidding is insufficient"),
-            BuildBiddingErrorKind::NonStarter => {
-                write!(f, "bidding cannot be started with a Double or a Redouble")
-            }
-            BuildBiddingErrorKind::NonExistent(ref bid) => write!(f, "{bid}"),
-        }
-    }
-}
-impl std::error::Error for BuildBiddingErrorKind {}
-
-#[derive(Debug, Clone)]
-#[non_exhaustive]
-pub struct BuildBiddingError {
-    bid: Option<Bid>,
-    kind: BuildBiddingErrorKind,
-}
-
-impl std::fmt::Display for BuildBiddingError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(bid) = self.bid {
-            write!(f, "unable to add to auction `{bid}`")
-        } else {
-            write!(f, "unable to parse bid: {}", match &self.kind {
-                BuildBiddingErrorKind::NonExistent(err) => &err.bid,
-                _=> unreachable!("tried to extract a string for failed bid parsing from a non BuildBiddingErrorKind::NonExistent")
-            })
+impl Bid {
+    /// The bid as written in a lin file: `p`, `d`, `r`, `1C`, `3N`...
+    #[must_use]
+    pub fn as_lin(&self) -> String {
+        match self {
+            Bid::Pass => String::from("p"),
+            Bid::Double => String::from("d"),
+            Bid::Redouble => String::from("r"),
+            Bid::Contract(level, Strain::NoTrumps) => format!("{level}N"),
+            Bid::Contract(level, strain) => format!("{level}{}", strain.not_unicode_str()),
         }
     }
 }
 
-impl std::error::Error for BuildBiddingError {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        match self.kind {
-            BuildBiddingErrorKind::Insufficient | BuildBiddingErrorKind::NonStarter => None,
-            BuildBiddingErrorKind::NonExistent(ref e) => e.source(),
-        }
+/// Players in lin order (South, West, North, East) to our order (North, East, South, West).
+pub(crate) fn lin_players(names: &[&str]) -> Option<[String; NUMBER_OF_HANDS]> {
+    match *names {
+        [south, west, north, east] => Some([north, east, south, west].map(String::from)),
+        _ => None,
     }
 }
 
-impl Bidding {
-    #[must_use]
-    #[inline]
-    pub fn new() -> Self {
-        Self {
-            bidding: Vec::new(),
+/// Parses the value of the `md` tag: the dealer followed by the hands, starting from South.
+/// The last hand is usually omitted, since it's made of the cards left.
+fn lin_dealer_and_hands(md: &str) -> Option<(Seat, Hands)> {
+    let md = md.trim();
+    let mut chars = md.chars();
+    // Dealer is a number starting from 1 for South.
+    let dealer = match chars.next()? {
+        digit @ '1'..='4' => Seat::South + (digit as u8 - b'1'),
+        _ => return None,
+    };
+    let mut deck = Cards::ALL;
+    let mut holdings = Vec::with_capacity(NUMBER_OF_HANDS);
+    for hand in chars.as_str().split(',') {
+        let cards = Cards::from_str(hand).ok()?;
+        if !(cards - deck).is_empty() {
+            return None;
         }
+        deck -= cards;
+        holdings.push(cards);
     }
-    #[must_use]
-    #[inline]
-    pub fn len(&self) -> usize {
-        self.bidding.len()
-    }
-    #[must_use]
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0usize
-    }
-    #[must_use]
-    #[inline]
-    pub fn get(&self, index: usize) -> Option<&Bid> {
-        self.bidding.get(index)
-    }
-    #[inline]
-    pub fn iter(&self) -> std::slice::Iter<Bid> {
-        self.bidding.iter()
+    if holdings.len() == NUMBER_OF_HANDS - 1 {
+        holdings.push(Cards::EMPTY);
     }
-    #[inline]
-    /// # Errors
-    /// - If the `Bid` is insufficient
-    /// - If we are at the first `Bid` and find a Double or Redouble
-    pub fn push(&mut self, bid: Bid) -> Result<(), BuildBiddingError> {
-        // If we have already some bids pushed
-        if let Some(last) = self.bidding.last() {
-            if bid.can_bid_over(last) {
-                self.bidding.push(bid);
-                Ok(())
-            } else {
-                Err(BuildBiddingError {
-                    bid: Some(bid),
-                    kind: BuildBiddingErrorKind::Insufficient,
-                })
-            }
-            // If this is the first bid that we push and is a contract or pass go ahead
-        } else if bid.is_contract() || bid == Bid::Pass {
-            self.bidding.push(bid);
-            Ok(())
-            // Else terminate error
-        } else {
-            Err(BuildBiddingError {
-                bid: Some(bid),
-                kind: BuildBiddingErrorKind::NonStarter,
-            })
-        }
+    if holdings.len() != NUMBER_OF_HANDS {
+        return None;
     }
-}
-
-impl<'a> IntoIterator for &'a Bidding {
-    type Item = &'a Bid;
-    type IntoIter = std::slice::Iter<'a, Bid>;
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    if let Some(missing) = holdings.iter_mut().find(|cards| cards.is_empty()) {
+        *missing = deck;
     }
+    let mut hands = holdings
+        .into_iter()
+        .map(|cards| Hand::try_from(cards).ok())
+        .collect::<Option<Vec<Hand>>>()?;
+    // Lin format has player start from south.
+    hands.rotate_right(2);
+    Some((dealer, Hands::new_from(hands.try_into().ok()?)))
 }
 
-/// We model bids as an Enum, with possible contracts as tuple
-/// variants containing [`NonZeroU8`] and a [`Strain`]
-#[derive(Debug, PartialEq, Copy, Clone)]
-pub enum Bid {
-    Pass,
-    Double,
-    Redouble,
-    Contract(NonZeroU8, Strain),
+/// The board number from the title of the board, e.g. `Board 12`.
+fn lin_board_number(title: &str) -> Option<u32> {
+    let title = title.trim();
+    let digits = title.strip_prefix("Board").unwrap_or(title).trim_start();
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    digits[..end].parse().ok().filter(|&number| number > 0)
 }
 
-impl std::fmt::Display for Bid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Bid::Contract(level, strain) => write!(f, "{level}{strain}"),
-            Bid::Pass => write!(f, "Pass"),
-            Bid::Redouble => write!(f, "Redouble"),
-            Bid::Double => write!(f, "Double"),
-        }
+/// Parses the value of the `sv` tag.
+fn lin_vulnerability(sv: &str) -> Option<Vulnerability> {
+    match sv.trim() {
+        "o" | "O" | "0" | "-" => Some(Vulnerability::None),
+        "n" | "N" => Some(Vulnerability::NS),
+        "e" | "E" => Some(Vulnerability::EW),
+        "b" | "B" => Some(Vulnerability::All),
+        _ => None,
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct BuildBidError {
-    pub bid: String,
-    pub kind: BuildBidErrorKind,
+/// Parses the value of the `mc` tag, the tricks taken by the declarer.
+fn lin_claim(mc: &str) -> Option<u8> {
+    mc.trim().parse().ok().filter(|&tricks| tricks <= 13)
 }
 
-/// Enum representing the various kind of errors
-/// we could encounter while parsing a single bid:
-/// either we are unable to parse the integer part of the bid
-/// or we are unable to parse the strain of the bid.
-#[non_exhaustive]
-#[derive(Debug, Clone)]
-pub enum BuildBidErrorKind {
-    Level(ParseIntError),
-    Strain,
-}
-impl std::fmt::Display for BuildBidErrorKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            BuildBidErrorKind::Level(_e) => write!(f, "unable to parse level"),
-            BuildBidErrorKind::Strain => write!(f, "unable to parse strain"),
-        }
-    }
+/// Parses a bid as written in a lin file: alerted bids may be marked with a `!`.
+fn lin_bid(bid: &str) -> Result<Bid, BuildBiddingError> {
+    Ok(Bid::from_str(bid.trim().trim_end_matches('!'))?)
 }
 
-impl std::error::Error for BuildBidError {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        match self.kind {
-            BuildBidErrorKind::Level(ref e) => Some(e),
-            BuildBidErrorKind::Strain => None,
-        }
-    }
-}
+#[allow(unused_imports)]
+mod test {
+    use crate::{Contract, Doubled, Seat, Strain, Vulnerable};
 
-impl std::fmt::Display for BuildBidError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "bid: {} cannot be parsed", self.bid)
-    }
-}
+    use super::{Bid, LinDeal, LinTokenizer, ParseLinErrorKind};
+    use crate::{Deal, PlayErrorKind, Vulnerability};
+    use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+    use std::num::NonZeroU8;
+    use std::str::FromStr;
 
-impl Bid {
-    #[must_use]
-    pub fn can_bid_over(&self, before: &Self) -> bool {
-        // We are not strictly checking if we keep bidding higher
-        // e.g. 1S-p-1H is validated with this implementation since we do not keep track of the
-        // level.
-        // FIX : Implement a builder to enforce state
-        match before {
-            Bid::Contract(level, strain) => match self {
-                Bid::Contract(self_level, self_strain) => {
-                    level < self_level || (level == self_level && strain < self_strain)
-                }
-                Bid::Pass | Bid::Double => true,
-                Bid::Redouble => false,
-            },
-            Bid::Pass => true,
-            Bid::Double => self != &Bid::Double,
-            Bid::Redouble => self != &Bid::Double || self != &Bid::Redouble,
+    const LIN: &str = "pn|simodra,fra97,matmont,thevava|st||md|3S34JH258TQKD2JQC7,S27TH69D679TKAC23,S6QH47JD458C468JA,|rh||ah|Board 1|sv|o|mb|p|mb|1S|an|5+|mb|2H|mb|2S|mb|3H|mb|4S|mb|p|mb|p|mb|p|pg||pc|C7|pc|C3|pc|CA|pc|C5|pg||pc|H4|pc|HA|pc|H5|pc|H6|pg||mc|11|";
+
+    #[test]
+    fn tokenizer_test() {
+        let tags = LinTokenizer::new("pn|a,b,c,d|st||\nmd|3S2,,,|  nt|hi, all|")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tags.iter()
+                .map(|tag| (tag.tag(), tag.value(), tag.offset()))
+                .collect::<Vec<_>>(),
+            [
+                ("pn", "a,b,c,d", 0),
+                ("st", "", 11),
+                ("md", "3S2,,,", 16),
+                ("nt", "hi, all", 28)
+            ]
+        );
+        assert_eq!(LinTokenizer::new(" \n").count(), 0);
+        for wrong in ["md", "md|3S2", "||", "pn|a|\n|b|"] {
+            let error = LinTokenizer::new(wrong).find_map(Result::err).unwrap();
+            assert!(matches!(error.kind(), ParseLinErrorKind::Syntax));
         }
+        let error = LinTokenizer::new("pn|a|\n|b|")
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(error.offset(), Some(6));
     }
 
-    #[must_use]
-    pub fn is_contract(&self) -> bool {
-        matches!(self, Bid::Contract(_, _))
+    #[test]
+    fn keeps_extra_tags_test() {
+        let deal = LinDeal::from_str(LIN).unwrap();
+        assert_eq!(
+            deal.extra_tags().collect::<Vec<_>>(),
+            [("st", ""), ("pg", ""), ("pg", ""), ("pg", "")]
+        );
+        assert_eq!(deal.bidding().unwrap().alert(1), Some("5+"));
+        assert_eq!(deal.play_sequence().unwrap().len(), 8);
+        assert_eq!(deal.claim(), Some(11));
     }
-}
 
-fn players(lin: &str) -> Vec<String> {
-    let players: LazyCell<Regex> = LazyCell::new(|| {
-        Regex::new(r"pn\|(?P<south>[\w ]+),(?P<west>[\w ]+),(?P<north>[\w ]+),(?P<east>[\w ]+)\|st")
-            .unwrap()
-    });
-    players.captures(lin).map_or_else(
-        || {
-            let mut data = Vec::new();
-            for _ in 0..4 {
-                data.push(String::from("NN"));
-            }
-            data
-        },
-        |captures| {
-            let mut vec: Vec<String> = captures
-                .iter()
-                .skip(1)
-                .map(|x| x.map_or(String::from("NN"), |m| m.as_str().to_string()))
-                .collect();
-            vec.rotate_left(2);
-            vec
-        },
-    )
-}
+    #[test]
+    fn play_state_test() {
+        let deal = LinDeal::from_str(LIN).unwrap();
+        let state = deal.play_state().unwrap().unwrap();
+        assert_eq!(state.trick_winners(), [Seat::North, Seat::East]);
+        assert_eq!(state.next_to_play(), Seat::East);
+        // East claims 11 tricks after winning the second one.
+        assert_eq!(state.claimed(), Some(10));
+        assert_eq!(state.declarer_tricks(), deal.claim().unwrap());
+        assert!(state.is_finished());
+        // The claim is not applied in the middle of a trick.
+        let mid_trick = LinDeal::from_str(&LIN.replace("pc|H6|", "")).unwrap();
+        let state = mid_trick.play_state().unwrap().unwrap();
+        assert_eq!((state.claimed(), state.declarer_tricks()), (None, 0));
+        // Nor if the declarer already won more tricks than claimed.
+        let wrong_claim = LinDeal::from_str(&LIN.replace("mc|11|", "mc|0|")).unwrap();
+        let error = wrong_claim.play_state().unwrap_err();
+        assert_eq!(error.kind(), PlayErrorKind::TooManyTricks);
 
-fn dealer_and_hands(lin: &str) -> Result<(Seat, Hands), ParseLinError> {
-    let hands = LazyCell::new(|| Regex::new(r"md\|(?P<dealer>\d)(?P<hands>[\w,]+?)\|").unwrap());
-    if let Some(captures) = hands.captures(lin) {
-        let capture = captures.name("dealer").expect("No dealer").as_str();
-        let dealer = match capture.parse::<u8>() {
-            Ok(dealer) => Seat::from(dealer + 1),
-            Err(_e) => {
-                error!("unable to parse dealer");
-                return Err(ParseLinError::new(ParseLinErrorKind::Hands, capture));
-            }
-        };
-        let mut deck = Cards::ALL;
-        let hand_capture = captures.name("hands").expect("No hands").as_str();
-
-        let mut vec = hand_capture
-            .split(',')
-            .map(|hand_str| {
-                let mut hand = Cards::from_str(hand_str)
-                    .map_err(|_| ParseLinError::new(ParseLinErrorKind::Hands, hand_str))?;
-                deck -= hand;
-                // We should get a empty [`Hand`] only at the end of the stream.
-                // Looks really error prone and should be corrected, so:
-                // TODO: FIX ME
-                if hand.is_empty() {
-                    if deck.len() == 13 {
-                        hand = deck;
-                    } else {
-                        return Err(ParseLinError::new(ParseLinErrorKind::Hands, hand_str));
-                    }
-                }
-                Hand::try_from(hand)
-                    .map_err(|_| ParseLinError::new(ParseLinErrorKind::Hands, hand_str))
-            })
-            .collect::<Result<Vec<Hand>, ParseLinError>>()?;
-        // Lin format has player start from south.
-        vec.rotate_right(2);
-        let hands: [Hand; 4] = match vec.try_into() {
-            Ok(array) => array,
-            Err(_) => return Err(ParseLinError::new(ParseLinErrorKind::Hands, hand_capture)),
-        };
-        Ok((dealer, Hands::new_from(hands)))
-    } else {
-        Err(ParseLinError::new(ParseLinErrorKind::Hands, lin))
+        // South discards a spade while holding hearts.
+        let revoke = LinDeal::from_str(&LIN.replace("pc|H5|", "pc|S3|")).unwrap();
+        let error = revoke.play_state().unwrap_err();
+        assert_eq!(error.seat(), Seat::South);
+        assert_eq!(error.kind(), PlayErrorKind::Revoke);
     }
-}
-
-fn number(lin: &str) -> Result<u8, ParseLinError> {
-    let number = LazyCell::new(|| Regex::new(r"ah\|\s?Board\s?(?P<number>[\d]+?)\|").unwrap());
-    let number = number
-        .captures(lin)
-        .expect("No number capture")
-        .name("number")
-        .expect("No number named match")
-        .as_str();
-    number
-        .parse::<u8>()
-        .map_err(|_| ParseLinError::new(ParseLinErrorKind::Number, number))
-}
 
-fn bidding(lin: &str) -> Result<Bidding, BuildBiddingError> {
-    let bids = LazyCell::new(|| Regex::new(r"(?P<waste>mb\|(?P<bid>\w+?)\|)+?").unwrap());
-    let captures = bids.captures_iter(lin);
-    let mut bidding = Bidding::new();
-    for cap in captures {
-        let bid = match &cap["bid"] {
-            "d" => Bid::Double,
-            "r" => Bid::Redouble,
-            "p" => Bid::Pass,
-            contract_bid => Bid::Contract(
-                match contract_bid[0..1].parse::<std::num::NonZeroU8>() {
-                    Ok(num) => num,
-                    Err(e) => {
-                        return Err(BuildBiddingError {
-                            bid: None,
-                            kind: BuildBiddingErrorKind::NonExistent(BuildBidError {
-                                bid: String::from(contract_bid),
-                                kind: BuildBidErrorKind::Level(e),
-                            }),
-                        });
-                    }
-                },
-                match &contract_bid[1..] {
-                    "c" | "C" => Strain::Clubs,
-                    "d" | "D" => Strain::Diamonds,
-                    "h" | "H" => Strain::Hearts,
-                    "s" | "S" => Strain::Spades,
-                    "n" | "N" => Strain::NoTrumps,
-                    _ => {
-                        return Err(BuildBiddingError {
-                            bid: None,
-                            kind: BuildBiddingErrorKind::NonExistent(BuildBidError {
-                                bid: String::from(contract_bid),
-                                kind: BuildBidErrorKind::Strain,
-                            }),
-                        })
-                    }
-                },
-            ),
-        };
-        bidding.push(bid)?;
-    }
-    Ok(bidding)
-}
-
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct PlaySequence {
-    sequence: Vec<Card>,
-}
-
-impl PlaySequence {
-    #[must_use]
-    pub fn new(sequence: Vec<Card>) -> Self {
-        Self { sequence }
-    }
-    #[must_use]
-    pub fn len(&self) -> usize {
-        self.sequence.len()
+    #[test]
+    fn parses_lin_metadata_test() {
+        let lin = LIN
+            .replace("rh||ah|Board 1|sv|o|", "rh|Final|ah|Board 1 Open|sv|e|")
+            .replace("mb|p|mb|1S|", "nt|no opening?|mb|p|mb|1S|")
+            .replace("pc|H4|", "nt|switch|pc|H4|");
+        let deal = LinDeal::from_str(&lin).unwrap();
+        assert_eq!(deal.header(), "Final");
+        assert_eq!(deal.title(), "Board 1 Open");
+        assert_eq!(deal.number(), 1);
+        assert_eq!(deal.vulnerability(), Vulnerability::EW);
+        // East declares, vulnerable because of `sv`, not of the board number.
+        assert_eq!(
+            deal.contract().unwrap(),
+            Contract::new(
+                4,
+                Strain::Spades,
+                Seat::East,
+                Vulnerable::Yes,
+                Doubled::NotDoubled
+            )
+        );
+        let comments = deal.comments();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].text(), "no opening?");
+        assert_eq!((comments[0].bids(), comments[0].cards()), (0, 0));
+        assert_eq!((comments[1].bids(), comments[1].cards()), (9, 4));
+        let alerts: Vec<_> = deal
+            .bidding()
+            .unwrap()
+            .iter_with_alerts()
+            .filter_map(|(bid, alert)| Some((*bid, alert?)))
+            .collect();
+        assert_eq!(
+            alerts,
+            [(Bid::Contract(NonZeroU8::MIN, Strain::Spades), "5+")]
+        );
+        let written = deal.as_lin();
+        assert!(written.contains("rh|Final|ah|Board 1 Open|sv|e|nt|no opening?|mb|p|"));
+        assert!(written.contains("pg||nt|switch|pc|H4|"));
+        assert_eq!(LinDeal::from_str(&written).unwrap().comments(), comments);
+        assert!(matches!(
+            LinDeal::from_str(&LIN.replace("sv|o|", "sv|x|"))
+                .unwrap_err()
+                .kind(),
+            ParseLinErrorKind::Vulnerability
+        ));
+        // Without `sv`, the vulnerability follows the board number.
+        let deal = LinDeal::from_str(&LIN.replace("ah|Board 1|sv|o|", "ah|Board 2|")).unwrap();
+        assert_eq!(deal.vulnerability(), Vulnerability::NS);
     }
 
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    #[test]
+    fn lin_deal_into_deal_test() {
+        let lin = LinDeal::from_str(&LIN.replace("ah|Board 1|sv|o|", "ah|Board 6|sv|b|")).unwrap();
+        let deal = Deal::from(&lin);
+        assert_eq!(deal.number(), 6);
+        // The dealer of the `md` tag wins over the one of the board number.
+        assert_eq!(deal.dealer(), Seat::North);
+        assert_eq!(deal.vulnerability(), Vulnerability::All);
+        assert_eq!(deal.hands(), lin.hands().hands());
+        assert_eq!(deal.contract(), lin.contract());
+        assert_eq!(deal.auction().unwrap().len(), 9);
+        assert_eq!(deal.auction().unwrap().alert(1), Some("5+"));
     }
 
-    #[must_use]
-    pub fn iter(&self) -> <&Self as std::iter::IntoIterator>::IntoIter {
-        self.into_iter()
+    #[test]
+    fn board_past_128_test() {
+        let lin = LinDeal::from_str(&LIN.replace("ah|Board 1|sv|o|", "ah|Board 300|")).unwrap();
+        assert_eq!(lin.number(), 300);
+        assert_eq!(lin.vulnerability(), Vulnerability::NS);
+        let mut deal = Deal::from(&lin);
+        assert_eq!(deal.number(), 300);
+        // A deal written as lin reads back with its own number, dealer and vulnerability.
+        deal.set_vuln(Vulnerability::EW);
+        let read = LinDeal::from_str(&deal.as_lin()).unwrap();
+        assert_eq!(read.number(), 300);
+        assert_eq!(read.dealer(), deal.dealer());
+        assert_eq!(read.vulnerability(), Vulnerability::EW);
+        assert_eq!(read.hands().hands(), deal.hands());
     }
-}
 
-impl IntoIterator for PlaySequence {
-    type Item = Card;
-    type IntoIter = <Vec<Card> as IntoIterator>::IntoIter;
-    fn into_iter(self) -> Self::IntoIter {
-        self.sequence.into_iter()
+    #[test]
+    fn lin_errors_test() {
+        let error = |lin: &str| LinDeal::from_str(lin).unwrap_err();
+        let missing_number = LIN.replace("ah|Board 1|", "");
+        assert!(matches!(
+            error(&missing_number).kind(),
+            ParseLinErrorKind::Number
+        ));
+        let missing_hands = LIN.replace("md|", "xx|");
+        assert!(matches!(
+            error(&missing_hands).kind(),
+            ParseLinErrorKind::Hands
+        ));
+        let wrong_bid = LIN.replace("mb|2H|", "mb|2X|");
+        let wrong_bid = error(&wrong_bid);
+        assert!(matches!(wrong_bid.kind(), ParseLinErrorKind::Bidding(_)));
+        assert_eq!(wrong_bid.offset(), LIN.find("mb|2H|"));
+        let wrong_card = LIN.replace("pc|HA|", "pc|HZ|");
+        assert_eq!(error(&wrong_card).offset(), LIN.find("pc|HA|"));
+        let repeated_card = LIN.replace("md|3S34J", "md|3S27J");
+        assert!(matches!(
+            error(&repeated_card).kind(),
+            ParseLinErrorKind::Hands
+        ));
+        assert!(matches!(
+            error(&LIN.replace("mc|11|", "mc|14|")).kind(),
+            ParseLinErrorKind::Claim
+        ));
+        assert!(LinDeal::from_str(&LIN.replace("Board 1", "Board 0")).is_err());
+        // Alerted bids may be marked with a `!`.
+        assert!(LinDeal::from_str(&LIN.replace("mb|1S|", "mb|1S!|")).is_ok());
     }
-}
 
-impl Display for PlaySequence {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for trick in &self.iter().chunks(4) {
-            writeln!(f, "{}", trick.into_iter().format("-"))?;
+    #[test]
+    fn fuzz_lin_test() {
+        const ALPHABET: &[char] = &[
+            '|', '|', '|', ',', ' ', '\n', '!', '0', '1', '3', '4', '7', '9', 'S', 'H', 'D', 'C',
+            'N', 'A', 'K', 'T', 'p', 'd', 'r', 'm', 'b', 'c', 'a', 'n', 'h', 'é', '♠',
+        ];
+        let mut rng = StdRng::seed_from_u64(0x5eed_0033);
+        let seeds = [LIN, "pn|S,W,N,E|md|1SAKQJT98765432,HAKQJT98765432,DAKQJT98765432,|ah|Board 7|mb|7N|mb|d|mb|r|mb|p|mb|p|mb|p|pc|SA|"];
+        for _ in 0..20_000 {
+            let mut lin: Vec<char> = seeds.choose(&mut rng).unwrap().chars().collect();
+            for _ in 0..rng.random_range(1..6) {
+                let index = rng.random_range(0..lin.len());
+                match rng.random_range(0..4) {
+                    0 => {
+                        lin.remove(index);
+                    }
+                    1 => lin.insert(index, *ALPHABET.choose(&mut rng).unwrap()),
+                    2 => lin[index] = *ALPHABET.choose(&mut rng).unwrap(),
+                    _ => lin.truncate(index),
+                }
+                if lin.is_empty() {
+                    break;
+                }
+            }
+            let lin: String = lin.into_iter().collect();
+            if let Ok(deal) = LinDeal::from_str(&lin) {
+                let _ = deal.contract();
+                let _ = deal.to_string();
+                let written = deal.as_lin();
+                assert!(LinDeal::from_str(&written).is_ok(), "{lin}\n{written}");
+            }
         }
-        Ok(())
-    }
-}
-
-#[cfg(feature = "dds")]
-impl TryFrom<&PlaySequence> for (SuitSeq, dds::utils::RankSeq) {
-    type Error = BuildSequenceError;
-
-    fn try_from(value: &PlaySequence) -> Result<Self, Self::Error> {
-        let len = value.len();
-        if len == 0 {
-            return Err(BuildSequenceError::SequenceTooShort);
-        } else if len > SEQUENCE_LENGTH {
-            return Err(BuildSequenceError::SequenceTooLong);
+        for _ in 0..5_000 {
+            let lin: String = (0..rng.random_range(0..80))
+                .map(|_| *ALPHABET.choose(&mut rng).unwrap())
+                .collect();
+            let _ = LinDeal::from_str(&lin);
         }
-
-        let (suitseq, rankseq): (Vec<_>, Vec<_>) = value
-            .into_iter()
-            .map(|card| (i32::from(card.suit() as u8), i32::from(card.rank())))
-            .unzip();
-        Ok((
-            SuitSeq::try_from(suitseq.as_slice())?,
-            RankSeq::try_from(rankseq.as_slice())?,
-        ))
-    }
-}
-
-#[cfg(feature = "dds")]
-impl TryFrom<&PlaySequence> for PlayTraceBin {
-    type Error = BuildSequenceError;
-
-    fn try_from(value: &PlaySequence) -> Result<Self, Self::Error> {
-        let sequences = <(SuitSeq, RankSeq)>::try_from(value)?;
-        Ok(Self::from_sequences(sequences.0, sequences.1))
-    }
-}
-
-impl<'a> IntoIterator for &'a PlaySequence {
-    type Item = &'a Card;
-    type IntoIter = <&'a Vec<Card> as IntoIterator>::IntoIter;
-    fn into_iter(self) -> Self::IntoIter {
-        self.sequence.iter()
-    }
-}
-
-// This function does not return an error if it is unable to parse a card, but returns a JOKER, so
-// remember to check for it and maybe try to reconstruct the right card based on the hand.
-fn play_sequence(lin: &str) -> Result<PlaySequence, ParseLinError> {
-    let play_sequence = LazyCell::new(|| Regex::new(r"(?P<waste>pc\|(?P<card>\w+?)\|)+?").unwrap());
-    let captures = play_sequence.captures_iter(lin);
-    let mut sequence: Vec<Card> = Vec::new();
-    for card in captures {
-        sequence.push(
-            Card::from_str(&card["card"])
-                .map_err(|_| ParseLinError::new(ParseLinErrorKind::Card, &card["card"]))?,
-        );
     }
-    Ok(PlaySequence { sequence })
-}
-
-/// Parses a claim line and returns the number of tricks claimed.
-/// Returns None if the claim is absent or invalid.
-fn claim(lin: &str) -> Option<u8> {
-    let claim = LazyCell::new(|| Regex::new(r"(?P<claim>mc\|(?P<tricks>\d+))").unwrap());
-    claim.captures(lin).map(|capture| {
-        capture
-            .name("tricks")
-            .map(|capture| capture.as_str().parse().ok())?
-    })?
-}
-
-#[allow(unused_imports)]
-mod test {
-    use crate::{Contract, Doubled, Seat, Strain, Vulnerable};
-
-    use super::LinDeal;
-    use std::str::FromStr;
 
     #[test]
     fn parses_lin_test0() {
@@ -761,6 +966,68 @@ mod test {
             )
         );
     }
+    #[test]
+    fn as_lin_round_trip_test() {
+        let played = "pn|gattochef,sebyx,Inter2018,fede00|st||md|3SAQ432HQJT72DT3CQ,SKJH983D974CT9876,S965HK654DKJ6CAJ5,ST87HADAQ852CK432|rh||ah|Board 1|sv|o|mb|1C|an|2+|mb|1D|mb|1H|an|picche|mb|2D|mb|p|mb|p|mb|3H|mb|p|mb|3S|mb|p|mb|4S|mb|p|mb|p|mb|p|pg||pc|DA|pc|D3|pc|D9|pc|D6|pg||pc|HA|pc|H2|pc|H9|pc|H4|pg||pc|D8|pc|DT|pc|D7|pc|DJ|pg||pc|S5|pc|S7|pc|SA|pc|SJ|pg||pc|CQ|pc|CT|pc|CA|pc|C2|pg||pc|S6|pc|S8|pc|SQ|pc|SK|pg||pc|H8|mc|9|";
+        let cases = [
+            LIN.to_owned(),
+            played.to_owned(),
+            // A claim after a complete trick, after the `pg` closing it.
+            LIN.replace("pc|H5|pc|H6|pg||mc|11|", "pg||mc|9|"),
+            // Without `st` and `pg`.
+            LIN.replace("st||", "").replace("pg||", ""),
+            // Tags not interpreted among the headers.
+            LIN.replace("|rh||", "|bn|1|rh||")
+                .replace("pn|", "qx|o1|pn|"),
+            LIN.replace("mb|p|mb|p|mb|p|pg||", "mb|p|mb|p|mb|p|"),
+        ];
+        for lin in cases {
+            assert_eq!(LinDeal::from_str(&lin).unwrap().as_lin(), lin);
+        }
+    }
+
+    #[test]
+    fn writes_lin_test() {
+        let lin = "pn|gattochef,sebyx,Inter2018,fede00|st||md|3SAQ432HQJT72DT3CQ,SKJH983D974CT9876,S965HK654DKJ6CAJ5,ST87HADAQ852CK432|rh||ah|Board 1|sv|o|mb|1C|an|2+|mb|1D|mb|1H|an|picche|mb|2D|mb|p|mb|p|mb|3H|mb|p|mb|3S|mb|p|mb|4S|mb|p|mb|p|mb|p|pg||pc|DA|pc|D3|pc|D9|pc|D6|pg||pc|HA|pc|H2|pc|H9|pc|H4|pg||pc|D8|pc|DT|pc|D7|pc|DJ|pg||pc|S5|pc|S7|pc|SA|pc|SJ|pg||pc|CQ|pc|CT|pc|CA|pc|C2|pg||pc|S6|pc|S8|pc|SQ|pc|SK|pg||pc|H8|mc|9|";
+        let parsed_lin = LinDeal::from_str(lin).unwrap();
+        assert_eq!(parsed_lin.claim(), Some(9));
+        let bidding = parsed_lin.bidding().unwrap();
+        assert_eq!(bidding.alert(0), Some("2+"));
+        assert_eq!(bidding.alert(1), None);
+        assert_eq!(bidding.alert(2), Some("picche"));
+        assert_eq!(parsed_lin.as_lin(), lin);
+
+        let mut reparsed = LinDeal::from_str(&parsed_lin.as_lin()).unwrap();
+        assert_eq!(reparsed.players(), parsed_lin.players());
+        assert_eq!(reparsed.hands().hands(), parsed_lin.hands().hands());
+        assert_eq!(reparsed.dealer(), parsed_lin.dealer());
+        assert_eq!(reparsed.contract(), parsed_lin.contract());
+        assert_eq!(
+            reparsed.play_sequence().unwrap().iter().collect::<Vec<_>>(),
+            parsed_lin
+                .play_sequence()
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>()
+        );
+        assert!(reparsed.bidding_mut().unwrap().set_alert(3, "natural"));
+        assert!(reparsed.as_lin().contains("mb|2D|an|natural|"));
+
+        // Tags not interpreted are written back where they were.
+        let lin = lin
+            .replace("mb|2D|", "mb|2D|nt|ok|bt|1|")
+            .replace("pc|H8|", "pc|H8|zz|x|");
+        let parsed_lin = LinDeal::from_str(&lin).unwrap();
+        assert_eq!(
+            parsed_lin
+                .extra_tags()
+                .filter(|&(tag, _)| tag == "bt")
+                .count(),
+            1
+        );
+        assert_eq!(parsed_lin.as_lin(), lin);
+    }
+
     #[test]
     fn parses_lin_test2() {
         let lin = String::from(include_str!("../tests/4207070707.lin"));
@@ -0,0 +1,344 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+
+/// Suits in the order used by [`CardNotation`] for suit letters.
+const SUIT_ORDER: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+/// Characters marking a void when parsing.
+const VOID_MARKERS: [char; 3] = ['-', '–', '—'];
+
+/// The letters used for ranks and suits in a language, to read and write cards, holdings and
+/// hands as they appear in local bulletins.
+///
+/// Parsing is case insensitive and accepts the suit symbols (♠♥♦♣, and the hollow ♤♡♢♧) in
+/// every notation. Suit letters are used only where they don't clash with the ranks: in
+/// Italian `F` is both the jack (_fante_) and clubs (_fiori_), and in German `K` is both the
+/// king (_König_) and clubs (_Kreuz_), so those notations use symbols, or holdings in order
+/// from spades separated by dots or spaces.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let hand = CardNotation::ITALIAN.parse_hand("♠AR10 ♥DF9 ♦8765 ♣432")?;
+/// assert_eq!(hand, Hand::from_str("AKT QJ9 8765 432")?);
+/// assert_eq!(CardNotation::FRENCH.hand_string(hand), "AR10.DV9.8765.432");
+/// assert_eq!(CardNotation::ENGLISH.cards_string(hand.as_cards()), "SAKTHQJ9D8765C432");
+/// let card = CardNotation::GERMAN.parse_card("♦B")?;
+/// assert_eq!(card, Card::DJ);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardNotation {
+    ranks: [&'static str; RANKS as usize],
+    suits: Option<[char; SUITS]>,
+}
+
+impl Default for CardNotation {
+    fn default() -> Self {
+        Self::ENGLISH
+    }
+}
+
+impl CardNotation {
+    /// `AKQJT`, suits `SHDC`.
+    pub const ENGLISH: Self = Self::new(
+        [
+            "2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A",
+        ],
+        Some(['S', 'H', 'D', 'C']),
+    );
+
+    /// `ARDF10` (_asso_, _re_, _donna_, _fante_), suits as symbols.
+    pub const ITALIAN: Self = Self::new(
+        [
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "F", "D", "R", "A",
+        ],
+        None,
+    );
+
+    /// `ARDV10` (_as_, _roi_, _dame_, _valet_), suits `PCKT` (_pique_, _cœur_, _carreau_,
+    /// _trèfle_).
+    pub const FRENCH: Self = Self::new(
+        [
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "V", "D", "R", "A",
+        ],
+        Some(['P', 'C', 'K', 'T']),
+    );
+
+    /// `AKDB10` (_Ass_, _König_, _Dame_, _Bube_), suits as symbols.
+    pub const GERMAN: Self = Self::new(
+        [
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "B", "D", "K", "A",
+        ],
+        None,
+    );
+
+    /// A notation with the given ranks, from the two to the ace, and suit letters, from
+    /// spades to clubs. Use `None` to write suits as symbols.
+    #[must_use]
+    pub const fn new(ranks: [&'static str; RANKS as usize], suits: Option<[char; SUITS]>) -> Self {
+        Self { ranks, suits }
+    }
+
+    /// The same notation, writing the ten as `ten`: `CardNotation::ENGLISH.with_ten("10")`.
+    #[must_use]
+    pub const fn with_ten(mut self, ten: &'static str) -> Self {
+        self.ranks[8] = ten;
+        self
+    }
+
+    /// The rank of a card, from 2 to 14.
+    #[must_use]
+    pub fn rank(&self, rank: u8) -> &'static str {
+        self.ranks[usize::from(rank.clamp(2, 14) - 2)]
+    }
+
+    /// The letter of a suit, or its symbol if the notation has no suit letters.
+    #[must_use]
+    pub fn suit(&self, suit: Suit) -> char {
+        match self.suits {
+            Some(letters) => letters[SUIT_ORDER.iter().position(|&s| s == suit).unwrap_or(0)],
+            None => suit.unicode(),
+        }
+    }
+
+    /// Whether the notation has suit letters.
+    #[must_use]
+    pub fn has_suit_letters(&self) -> bool {
+        self.suits.is_some()
+    }
+
+    /// A card as suit and rank, like `♠A` or `SA`.
+    #[must_use]
+    pub fn card_string(&self, card: Card) -> String {
+        format!("{}{}", self.suit(card.suit()), self.rank(card.rank()))
+    }
+
+    /// The ranks of a holding, from the highest.
+    #[must_use]
+    pub fn holding_string(&self, holding: Cards) -> String {
+        holding
+            .into_iter()
+            .rev()
+            .map(|card| self.rank(card.rank()))
+            .collect()
+    }
+
+    /// Cards grouped by suit, every suit introduced by its letter or symbol. Voids are
+    /// skipped, like the `Display` of [`Cards`].
+    #[must_use]
+    pub fn cards_string(&self, cards: Cards) -> String {
+        let mut string = String::new();
+        for suit in SUIT_ORDER {
+            let holding = cards.in_suit(suit);
+            if !holding.is_empty() {
+                string.push(self.suit(suit));
+                string.push_str(&self.holding_string(holding));
+            }
+        }
+        string
+    }
+
+    /// A hand as holdings from spades to clubs separated by dots, like PBN.
+    #[must_use]
+    pub fn hand_string(&self, hand: Hand) -> String {
+        hand.into_iter()
+            .map(|holding| self.holding_string(holding))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// The suit marked by `c`, if any.
+    fn parse_suit(&self, c: char) -> Option<Suit> {
+        match c {
+            '♠' | '♤' => Some(Suit::Spades),
+            '♥' | '♡' => Some(Suit::Hearts),
+            '♦' | '♢' => Some(Suit::Diamonds),
+            '♣' | '♧' => Some(Suit::Clubs),
+            c => {
+                let index = self
+                    .suits?
+                    .iter()
+                    .position(|letter| letter.eq_ignore_ascii_case(&c))?;
+                Some(SUIT_ORDER[index])
+            }
+        }
+    }
+
+    /// The rank at the start of `s` with its length in bytes, trying longer ranks first. The
+    /// ten is also read as `10` in every notation, and as `1` in English.
+    fn parse_rank(&self, s: &str) -> Option<(u8, usize)> {
+        let english_ten = (*self == Self::ENGLISH).then_some((10, "1"));
+        (2..=14u8)
+            .map(|rank| (rank, self.rank(rank)))
+            .chain(std::iter::once((10, "10")))
+            .chain(english_ten)
+            .filter(|(_, text)| {
+                s.get(..text.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(text))
+            })
+            .max_by_key(|(_, text)| text.len())
+            .map(|(rank, text)| (rank, text.len()))
+    }
+
+    /// Reads cards written in this notation. Suits can be introduced by their symbol or
+    /// letter, or follow each other from spades separated by dots or spaces. When suits are
+    /// marked, spaces only separate the cards, as in `♠ A R 10 4`.
+    ///
+    /// # Errors
+    /// If the string contains unknown characters, more than four suits or the same card twice.
+    pub fn parse_cards(&self, s: &str) -> Result<Cards, DealerError> {
+        let mut cards = Cards::EMPTY;
+        let mut suit = Suit::Spades;
+        // Whitespace separates suits only after some ranks, and only if suits are not marked.
+        let marked = s.chars().any(|c| self.parse_suit(c).is_some());
+        let mut after_rank = false;
+        let mut rest = s.trim();
+        while let Some(c) = rest.chars().next() {
+            let mut len = c.len_utf8();
+            if let Some(marked) = self.parse_suit(c) {
+                suit = marked;
+                after_rank = false;
+            } else if c == '.' || (c.is_whitespace() && after_rank && !marked) {
+                suit = suit.next().ok_or_else(|| {
+                    DealerError::new(format!("more than 4 suits in {s}, check the separators"))
+                })?;
+                after_rank = false;
+            } else if let Some((rank, rank_len)) = self.parse_rank(rest) {
+                let card = Card::new(suit, rank);
+                if cards.contains(card) {
+                    return Err(DealerError::new(format!("card {card} repeated in {s}")));
+                }
+                cards = cards.insert(card);
+                len = rank_len;
+                after_rank = true;
+            } else if VOID_MARKERS.contains(&c) {
+                // A void is a holding: the next separator closes its suit.
+                after_rank = true;
+            } else if !c.is_whitespace() {
+                return Err(DealerError::new(format!("unknown character {c} in {s}")));
+            }
+            rest = &rest[len..];
+        }
+        Ok(cards)
+    }
+
+    /// Reads a hand of 13 cards written in this notation.
+    ///
+    /// # Errors
+    /// If the cards cannot be read or are not 13.
+    pub fn parse_hand(&self, s: &str) -> Result<Hand, DealerError> {
+        let cards = self.parse_cards(s)?;
+        if cards.len() == 13 {
+            Ok(Hand { cards })
+        } else {
+            Err(DealerError::new(format!(
+                "wrong number of cards for a Bridge hand: {s}"
+            )))
+        }
+    }
+
+    /// Reads a single card, with the suit before or after the rank: `♠A`, `A♠`, `SA`.
+    ///
+    /// # Errors
+    /// If the string is not exactly one card.
+    pub fn parse_card(&self, s: &str) -> Result<Card, InvalidCardError> {
+        let s = s.trim();
+        let mut chars = s.chars();
+        let (suit, text) = match (chars.next(), chars.next_back()) {
+            (Some(first), _) if self.parse_suit(first).is_some() => {
+                (self.parse_suit(first), &s[first.len_utf8()..])
+            }
+            (_, Some(last)) if self.parse_suit(last).is_some() => {
+                (self.parse_suit(last), &s[..s.len() - last.len_utf8()])
+            }
+            _ => (None, s),
+        };
+        match (suit, self.parse_rank(text)) {
+            (Some(suit), Some((rank, len))) if len == text.len() => Ok(Card::new(suit, rank)),
+            _ => Err(InvalidCardError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_notations_test() {
+        let expected = Hand::from_str("AKQ JT9 8765 432").unwrap();
+        for (notation, hand) in [
+            (CardNotation::ENGLISH, "sakq hjt9 d8765 c432"),
+            (CardNotation::ENGLISH.with_ten("10"), "AKQ.J109.8765.432"),
+            (CardNotation::ITALIAN, "♠ARD ♥F109 ♦8765 ♣432"),
+            (CardNotation::ITALIAN, "ard f109 8765 432"),
+            (CardNotation::FRENCH, "PARD CV109 K8765 T432"),
+            (CardNotation::GERMAN, "♤AKD ♡B109 ♢8765 ♧432"),
+            (CardNotation::ENGLISH, "♣432 ♠AKQ ♦8765 ♥JT9"),
+            (CardNotation::ITALIAN, "♠ A R D ♥ F 10 9 ♦ 8 7 6 5 ♣ 4 3 2"),
+        ] {
+            assert_eq!(notation.parse_hand(hand).unwrap(), expected, "{hand}");
+        }
+        let cards = |notation: CardNotation, s| notation.parse_cards(s).unwrap();
+        assert_eq!(
+            cards(CardNotation::ENGLISH, "♣432 ♠AKQ"),
+            Cards::from_str("SAKQ C432").unwrap()
+        );
+        assert_eq!(
+            cards(CardNotation::ITALIAN, "♠ A R 10 4"),
+            Cards::from_str("SAK104").unwrap()
+        );
+        let void = CardNotation::ITALIAN
+            .parse_cards("ARDF1098765432 - ♦ — ♣ —")
+            .unwrap();
+        assert_eq!(void, Cards::SPADES);
+    }
+
+    #[test]
+    fn writes_notations_test() {
+        let hand = Hand::from_str("AKQ JT9 8765 432").unwrap();
+        assert_eq!(CardNotation::ITALIAN.hand_string(hand), "ARD.F109.8765.432");
+        assert_eq!(
+            CardNotation::GERMAN.cards_string(hand.as_cards()),
+            "♠AKD♥B109♦8765♣432"
+        );
+        assert_eq!(CardNotation::FRENCH.card_string(Card::C10), "T10");
+        assert_eq!(CardNotation::ENGLISH.card_string(Card::HQ), "HQ");
+        for notation in [
+            CardNotation::ENGLISH,
+            CardNotation::ITALIAN,
+            CardNotation::FRENCH,
+            CardNotation::GERMAN,
+        ] {
+            assert_eq!(
+                notation.parse_hand(&notation.hand_string(hand)).unwrap(),
+                hand
+            );
+            for card in hand.as_cards() {
+                assert_eq!(
+                    notation.parse_card(&notation.card_string(card)).unwrap(),
+                    card
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn notation_errors_test() {
+        let italian = CardNotation::ITALIAN;
+        assert!(italian.parse_cards("AKQ").is_err());
+        assert!(italian.parse_cards("A.R.D.F.10").is_err());
+        assert!(italian.parse_cards("AA").is_err());
+        assert!(italian.parse_hand("ARD").is_err());
+        assert!(italian.parse_card("A").is_err());
+        assert!(italian.parse_card("♠AR").is_err());
+        assert_eq!(italian.parse_card("10♥").unwrap(), Card::H10);
+    }
+}
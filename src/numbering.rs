@@ -0,0 +1,255 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Compact numbering of hands and deals.
+//!
+//! Every deal is identified by a number in `0..NUMBER_OF_DEALS` (it fits in 96 bits),
+//! following the lexicographic ordering introduced by Richard Pavlicek and used by Thomas
+//! Andrews: cards are taken in the order ♠A, ♠K, ..., ♠2, ♥A, ..., ♣2 and every card is
+//! given, in turn, to North, East, South or West. Pavlicek's deal numbers start from 1, so
+//! they are simply our deal index plus one.
+//!
+//! In the same way, every set of `n` cards (and so every [`Hand`]) is identified by its
+//! lexicographic index among all the `C(52, n)` sets of `n` cards.
+
+use crate::frequency::binomial;
+use crate::prelude::*;
+
+/// Number of different bridge deals: 52! / (13!)^4.
+pub const NUMBER_OF_DEALS: u128 = 53_644_737_765_488_792_839_237_440_000;
+
+/// Number of different bridge hands: C(52, 13).
+pub const NUMBER_OF_HANDS_IN_DECK: u64 = binomial(52, 13);
+
+/// The order of the cards used for numbering: spades to clubs, aces to deuces.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) const CARD_ORDER: [Card; 52] = {
+    let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+    let mut order = [Card::SA; 52];
+    let mut index = 0;
+    while index < 52 {
+        order[index] = Card::new(suits[index / 13], 14 - (index % 13) as u8);
+        index += 1;
+    }
+    order
+};
+
+impl Cards {
+    /// Lexicographic index of this set of cards among all the sets with the same number of cards
+    /// taken from a full deck. The index of a set of 13 cards is in `0..C(52, 13)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>>{
+    /// let cards = Cards::from_str("AKQJT98765432")?;
+    /// assert_eq!(cards.combination_index(), 0);
+    /// assert_eq!(Cards::from_combination_index(0, 13), Some(cards));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn combination_index(self) -> u64 {
+        let mut remaining = u64::from(self.len());
+        let mut index = 0;
+        for (position, &card) in (0u64..).zip(CARD_ORDER.iter()) {
+            if remaining == 0 {
+                break;
+            }
+            if self.contains(card) {
+                remaining -= 1;
+            } else {
+                // Every set with the same cards so far and this card comes before us.
+                index += binomial(51 - position, remaining - 1);
+            }
+        }
+        index
+    }
+
+    /// Inverse of [`Cards::combination_index`]: the set of `len` cards with lexicographic
+    /// index `index`. Returns `None` if `len` is greater than 52 or if `index` is not lower
+    /// than `C(52, len)`.
+    #[must_use]
+    pub fn from_combination_index(mut index: u64, len: u8) -> Option<Cards> {
+        let mut remaining = u64::from(len);
+        if remaining > 52 || index >= binomial(52, remaining) {
+            return None;
+        }
+        let mut cards = Cards::EMPTY;
+        for (position, &card) in (0u64..).zip(CARD_ORDER.iter()) {
+            if remaining == 0 {
+                break;
+            }
+            let with_card = binomial(51 - position, remaining - 1);
+            if index < with_card {
+                cards = cards.insert(card);
+                remaining -= 1;
+            } else {
+                index -= with_card;
+            }
+        }
+        Some(cards)
+    }
+}
+
+impl Hand {
+    /// Index of the hand among all the `C(52, 13)` possible bridge hands.
+    /// See [`Cards::combination_index`].
+    #[must_use]
+    pub fn index(&self) -> u64 {
+        self.cards.combination_index()
+    }
+
+    /// Builds the hand with index `index`, the inverse of [`Hand::index`].
+    ///
+    /// # Errors
+    ///
+    /// If `index` is not lower than `C(52, 13)`.
+    pub fn from_index(index: u64) -> Result<Hand, DealerError> {
+        Cards::from_combination_index(index, 13)
+            .map(|cards| Hand { cards })
+            .ok_or_else(|| {
+                DealerError::new(format!(
+                    "hand index {index} is too big: must be lower than {NUMBER_OF_HANDS_IN_DECK}"
+                ))
+            })
+    }
+}
+
+impl Deal {
+    /// The lexicographic index of the deal, in `0..NUMBER_OF_DEALS`.
+    /// Add one to get Pavlicek's deal number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use squeezer::prelude::*;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>>{
+    /// let deal = Deal::new();
+    /// let index = deal.index();
+    /// assert!(index < NUMBER_OF_DEALS);
+    /// assert_eq!(Deal::from_index(index)?.hands(), deal.hands());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn index(&self) -> u128 {
+        let mut vacancies = [13u128; NUMBER_OF_HANDS];
+        let mut deals_left = NUMBER_OF_DEALS;
+        let mut index = 0;
+        for (cards_left, &card) in (1..=52u128).rev().zip(CARD_ORDER.iter()) {
+            for (hand, vacancy) in self.hands().iter().zip(vacancies.iter_mut()) {
+                // Number of deals, among the ones left, in which this card goes to `hand`.
+                let deals_with_card = deals_left * *vacancy / cards_left;
+                if hand.contains(card) {
+                    deals_left = deals_with_card;
+                    *vacancy -= 1;
+                    break;
+                }
+                index += deals_with_card;
+            }
+        }
+        index
+    }
+
+    /// Builds the deal with lexicographic index `index`, the inverse of [`Deal::index`].
+    /// Subtract one from Pavlicek's deal number to get the index.
+    ///
+    /// # Errors
+    ///
+    /// If `index` is not lower than [`NUMBER_OF_DEALS`].
+    pub fn from_index(mut index: u128) -> Result<Deal, DealerError> {
+        if index >= NUMBER_OF_DEALS {
+            return Err(DealerError::new(format!(
+                "deal index {index} is too big: must be lower than {NUMBER_OF_DEALS}"
+            )));
+        }
+        let mut vacancies = [13u128; NUMBER_OF_HANDS];
+        let mut hands = [Cards::EMPTY; NUMBER_OF_HANDS];
+        let mut deals_left = NUMBER_OF_DEALS;
+        for (cards_left, &card) in (1..=52u128).rev().zip(CARD_ORDER.iter()) {
+            for (hand, vacancy) in hands.iter_mut().zip(vacancies.iter_mut()) {
+                let deals_with_card = deals_left * *vacancy / cards_left;
+                if index < deals_with_card {
+                    *hand = hand.insert(card);
+                    deals_left = deals_with_card;
+                    *vacancy -= 1;
+                    break;
+                }
+                index -= deals_with_card;
+            }
+        }
+        Ok(Deal::from_hands(hands.map(|cards| Hand { cards })))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn card_order_test() {
+        assert_eq!(CARD_ORDER[0], Card::SA);
+        assert_eq!(CARD_ORDER[12], Card::S2);
+        assert_eq!(CARD_ORDER[13], Card::HA);
+        assert_eq!(CARD_ORDER[51], Card::C2);
+    }
+
+    #[test]
+    fn number_of_deals_test() {
+        let multinomial = u128::from(binomial(52, 13))
+            * u128::from(binomial(39, 13))
+            * u128::from(binomial(26, 13));
+        assert_eq!(multinomial, NUMBER_OF_DEALS);
+        assert_eq!(NUMBER_OF_DEALS.leading_zeros(), 32);
+    }
+
+    #[test]
+    fn combination_index_test() {
+        let last = Cards::CLUBS;
+        assert_eq!(last.combination_index(), NUMBER_OF_HANDS_IN_DECK - 1);
+        assert_eq!(
+            Cards::from_combination_index(NUMBER_OF_HANDS_IN_DECK - 1, 13),
+            Some(last)
+        );
+        assert_eq!(
+            Cards::from_combination_index(NUMBER_OF_HANDS_IN_DECK, 13),
+            None
+        );
+        // ♠AKQJT98765432 is index 0, ♠AKQJT9876543 ♥A comes right after.
+        let second = Cards::from_str("SAKQJT9876543HA").unwrap();
+        assert_eq!(second.combination_index(), 1);
+        for _ in 0..100 {
+            let hand = Deal::new().north();
+            assert_eq!(Hand::from_index(hand.index()).unwrap(), hand);
+        }
+        assert!(Hand::from_index(NUMBER_OF_HANDS_IN_DECK).is_err());
+    }
+
+    #[test]
+    fn deal_index_test() {
+        // Index 0: North has all the spades, East the hearts and so on.
+        let first = Deal::from_index(0).unwrap();
+        assert_eq!(first.north().as_cards(), Cards::SPADES);
+        assert_eq!(first.east().as_cards(), Cards::HEARTS);
+        assert_eq!(first.south().as_cards(), Cards::DIAMONDS);
+        assert_eq!(first.west().as_cards(), Cards::CLUBS);
+        assert_eq!(first.index(), 0);
+        // Last index: it's the opposite.
+        let last = Deal::from_index(NUMBER_OF_DEALS - 1).unwrap();
+        assert_eq!(last.west().as_cards(), Cards::SPADES);
+        assert_eq!(last.north().as_cards(), Cards::CLUBS);
+        assert_eq!(last.index(), NUMBER_OF_DEALS - 1);
+        assert!(Deal::from_index(NUMBER_OF_DEALS).is_err());
+        for _ in 0..100 {
+            let deal = Deal::new();
+            assert_eq!(
+                Deal::from_index(deal.index()).unwrap().hands(),
+                deal.hands()
+            );
+        }
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+use std::cmp::Reverse;
+
+const TEN: u8 = 10;
+const JACK: u8 = 11;
+const ACE: u8 = 14;
+
+/// The agreement on the card led from a suit without a sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeadStyle {
+    /// Fourth best from four or more cards to an honour, second highest from four small and
+    /// middle-up-down from three small.
+    #[default]
+    FourthBest,
+    /// Third best from an even number of cards, fifth best from an odd one, and
+    /// middle-up-down from three small.
+    ThirdAndFifth,
+    /// Low from a suit with an honour, to encourage, and high from a suit without, to
+    /// discourage.
+    Attitude,
+}
+
+/// Why a card is the one led from its suit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LeadKind {
+    /// The top of a sequence of touching honours, solid or broken.
+    Sequence,
+    /// The top of the touching cards below a higher honour, as the jack from KJT.
+    InteriorSequence,
+    /// An ace, cashed rather than underled against a suit contract.
+    Ace,
+    Singleton,
+    /// The top of a doubleton.
+    Doubleton,
+    /// A card from four or more to an honour, as agreed with [`LeadStyle`].
+    Length,
+    /// The lowest card from three to an honour.
+    LowFromHonour,
+    /// A card from a suit without honours.
+    Nothing,
+    /// A trump.
+    Trump,
+}
+
+/// A lead chosen by a [`LeadEngine`]: the conventional card of its suit, why it is the one
+/// led and how good the lead is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookLead {
+    card: Card,
+    kind: LeadKind,
+    score: i32,
+}
+
+impl BookLead {
+    #[must_use]
+    pub fn card(&self) -> Card {
+        self.card
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> LeadKind {
+        self.kind
+    }
+
+    /// How good the lead is, higher is better. Scores only compare the leads of a hand.
+    #[must_use]
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+}
+
+/// A rule-based opening leader, choosing its lead as taught in books rather than by
+/// simulation. It can be compared with the results of a
+/// [`LeadSimulation`](crate::prelude::LeadSimulation), or used to make realistic leads
+/// where no double dummy solver is involved.
+///
+/// From every suit the engine picks the conventional card: the top of a sequence, the
+/// top of a doubleton, low from three to an honour, fourth best (or third and fifth, or
+/// attitude, see [`LeadStyle`]) from length and middle-up-down from three small. The suits
+/// are then ranked:
+/// - against no trumps, long suits and sequences come first, while short suits are led
+///   only as a last resort;
+/// - against a suit contract, sequences and ace-king lead the way, then singletons and
+///   safe leads from small cards; leading away from honours is avoided and aces are never
+///   underled;
+/// - partner's suits are preferred, the opponents' ones avoided, reading the auction
+///   naturally;
+/// - against slams the engine leads passively, and against suit contracts it attacks when
+///   the declaring side showed a side suit or leads a trump when they showed only trumps.
+///
+/// # Example
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let hand = Hand::from_str("KQJ5 T84 K83 972")?;
+/// let engine = LeadEngine::new();
+/// let contract = Contract::from_str("3NS", Vulnerable::No)?;
+/// assert_eq!(engine.lead(hand, contract, None), Some(Card::SK));
+/// // Partner overcalled in hearts: middle-up-down from three small.
+/// let auction = Bidding::from_str("1NT P 2C 2H 2NT P 3NT P P P")?;
+/// let leads = engine.leads(hand, contract, Some((&auction, Seat::South)));
+/// assert_eq!(leads[0].card(), Card::H8);
+/// assert_eq!(leads[0].kind(), LeadKind::Nothing);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeadEngine {
+    style: LeadStyle,
+}
+
+impl LeadEngine {
+    /// An engine leading fourth best.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_style(mut self, style: LeadStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub fn style(&self) -> LeadStyle {
+        self.style
+    }
+
+    /// The best lead from `hand` against `contract`, `None` if the hand is empty.
+    #[must_use]
+    pub fn lead(
+        &self,
+        hand: Hand,
+        contract: Contract,
+        auction: Option<(&Bidding, Seat)>,
+    ) -> Option<Card> {
+        self.leads(hand, contract, auction)
+            .first()
+            .map(BookLead::card)
+    }
+
+    /// A lead from every suit of `hand` against `contract`, best first. The auction, with
+    /// its dealer, tells which suits partner and the opponents bid.
+    #[must_use]
+    pub fn leads(
+        &self,
+        hand: Hand,
+        contract: Contract,
+        auction: Option<(&Bidding, Seat)>,
+    ) -> Vec<BookLead> {
+        let trumps = Suit::try_from(contract.strain()).ok();
+        let partner = contract.leader() + 2;
+        let mut partner_bid = [false; SUITS];
+        let mut opponents_bid = [false; SUITS];
+        for (index, bid) in auction.into_iter().flat_map(|(auction, dealer)| {
+            auction
+                .iter()
+                .enumerate()
+                .map(move |(index, bid)| (dealer + index, bid))
+        }) {
+            let Bid::Contract(_, strain) = bid else {
+                continue;
+            };
+            let Ok(suit) = Suit::try_from(*strain) else {
+                continue;
+            };
+            if index == partner {
+                partner_bid[suit as usize] = true;
+            } else if index.is_same_line(&contract.declarer()) {
+                opponents_bid[suit as usize] = true;
+            }
+        }
+        let side_suit_shown = Suit::ALL
+            .iter()
+            .any(|&suit| opponents_bid[suit as usize] && Some(suit) != trumps);
+        let situation = Situation {
+            trumps,
+            trump_length: trumps.map_or(0, |suit| hand.len_of_suit(suit)),
+            level: contract.level(),
+            side_suit_shown,
+            trumps_only: trumps.is_some_and(|suit| opponents_bid[suit as usize])
+                && !side_suit_shown,
+        };
+
+        let mut leads: Vec<BookLead> = Suit::ALL
+            .iter()
+            .filter_map(|&suit| {
+                let mut ranks: Vec<u8> = hand
+                    .as_cards()
+                    .in_suit(suit)
+                    .into_iter()
+                    .map(Card::rank)
+                    .collect();
+                if ranks.is_empty() {
+                    return None;
+                }
+                ranks.sort_unstable_by(|a, b| b.cmp(a));
+                let (rank, kind) = self.card_from(&ranks, trumps, Some(suit) == trumps);
+                let mut score = situation.score(kind, &ranks);
+                if partner_bid[suit as usize] {
+                    score += 50;
+                }
+                if opponents_bid[suit as usize] && kind != LeadKind::Sequence {
+                    score -= 25;
+                }
+                Some(BookLead {
+                    card: Card::new(suit, rank),
+                    kind,
+                    score,
+                })
+            })
+            .collect();
+        leads.sort_by_key(|lead| Reverse(lead.score));
+        leads
+    }
+
+    /// The card led from a suit holding `ranks`, sorted from the highest.
+    fn card_from(self, ranks: &[u8], trumps: Option<Suit>, is_trump: bool) -> (u8, LeadKind) {
+        let length = ranks.len();
+        let lowest = ranks[length - 1];
+        let touching = |from: usize| length > from + 1 && ranks[from] == ranks[from + 1] + 1;
+        // Three touching cards, or two and one just below the gap; against a suit contract
+        // two touching honours are enough.
+        let sequence = ranks[0] >= TEN
+            && touching(0)
+            && (touching(1)
+                || (length > 2 && ranks[1] == ranks[2] + 2)
+                || (trumps.is_some() && ranks[0] >= JACK)
+                || length == 2);
+        if is_trump {
+            return (if sequence { ranks[0] } else { lowest }, LeadKind::Trump);
+        }
+        if length == 1 {
+            return (ranks[0], LeadKind::Singleton);
+        }
+        if sequence {
+            return (ranks[0], LeadKind::Sequence);
+        }
+        if trumps.is_some() && ranks[0] == ACE {
+            return (ACE, LeadKind::Ace);
+        }
+        if length >= 3 && ranks[0] >= JACK && ranks[1] >= TEN && touching(1) {
+            return (ranks[1], LeadKind::InteriorSequence);
+        }
+        let honour = ranks[0] >= JACK;
+        match (length, honour, self.style) {
+            (2, ..) => (ranks[0], LeadKind::Doubleton),
+            (3, true, _) => (lowest, LeadKind::LowFromHonour),
+            (3, false, LeadStyle::Attitude) => (ranks[0], LeadKind::Nothing),
+            (3, false, _) => (ranks[1], LeadKind::Nothing),
+            (_, true, LeadStyle::FourthBest) => (ranks[3], LeadKind::Length),
+            (_, true, LeadStyle::Attitude) => (lowest, LeadKind::Length),
+            (_, _, LeadStyle::ThirdAndFifth) => {
+                let rank = ranks[if length.is_multiple_of(2) { 2 } else { 4 }];
+                let kind = if honour {
+                    LeadKind::Length
+                } else {
+                    LeadKind::Nothing
+                };
+                (rank, kind)
+            }
+            (_, false, _) => (ranks[1], LeadKind::Nothing),
+        }
+    }
+}
+
+/// What the leader knows of the contract and the auction.
+struct Situation {
+    trumps: Option<Suit>,
+    trump_length: u8,
+    level: u8,
+    // The declaring side bid a suit other than trumps.
+    side_suit_shown: bool,
+    // The declaring side bid only the trump suit.
+    trumps_only: bool,
+}
+
+impl Situation {
+    // A table of scores reads better with an arm for every case.
+    #[allow(clippy::match_same_arms)]
+    fn score(&self, kind: LeadKind, ranks: &[u8]) -> i32 {
+        #[allow(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+        let length = ranks.len() as i32;
+        let honour_doubleton = ranks[0] >= JACK;
+        let mut score = if self.trumps.is_none() {
+            let long_suit = 5 * (length - 4).max(0);
+            long_suit
+                + match kind {
+                    LeadKind::Sequence if length >= 4 => 35,
+                    LeadKind::Sequence => 25,
+                    LeadKind::InteriorSequence if length >= 4 => 30,
+                    LeadKind::InteriorSequence => 20,
+                    LeadKind::Length => 25,
+                    LeadKind::Nothing if length >= 4 => 10,
+                    LeadKind::LowFromHonour => 5,
+                    LeadKind::Doubleton => -10,
+                    LeadKind::Singleton => -20,
+                    _ => 0,
+                }
+        } else {
+            match kind {
+                LeadKind::Sequence if ranks[0] == ACE => 45,
+                LeadKind::Sequence => 40,
+                LeadKind::InteriorSequence => 25,
+                LeadKind::Singleton if self.trump_length == 0 => 0,
+                LeadKind::Singleton if self.trump_length >= 4 => 10,
+                LeadKind::Singleton => 25,
+                LeadKind::Doubleton if honour_doubleton => -10,
+                LeadKind::Doubleton | LeadKind::Nothing => 10,
+                LeadKind::Length => 5,
+                LeadKind::LowFromHonour => -5,
+                // A singleton trump, or one from a holding that may take a trick.
+                LeadKind::Trump if length == 1 || (ranks[0] >= JACK && ranks[0] < ACE) => -30,
+                LeadKind::Trump => 5,
+                _ => 0,
+            }
+        };
+        if self.level >= 6 {
+            // Against a slam, give nothing away.
+            score += match kind {
+                LeadKind::Sequence | LeadKind::Nothing => 10,
+                LeadKind::Trump if self.level == 7 => 20,
+                LeadKind::LowFromHonour | LeadKind::Length if self.trumps.is_some() => -20,
+                _ => 0,
+            };
+        } else if self.trumps.is_some() {
+            // Attack before the side suit is set up, or cut the ruffs.
+            score += match kind {
+                LeadKind::LowFromHonour | LeadKind::Length if self.side_suit_shown => 10,
+                LeadKind::Trump if self.side_suit_shown => -5,
+                LeadKind::Trump if self.trumps_only => 10,
+                _ => 0,
+            };
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn card(style: LeadStyle, holding: &str, strain: &str) -> (Card, LeadKind) {
+        let mut hand = Hand::new_empty();
+        hand.set_cards(Cards::from_str(&format!("S{holding}")).unwrap());
+        let contract = Contract::from_str(&format!("4{strain}S"), Vulnerable::No).unwrap();
+        let leads = LeadEngine::new()
+            .with_style(style)
+            .leads(hand, contract, None);
+        (leads[0].card(), leads[0].kind())
+    }
+
+    #[test]
+    fn card_from_suit_test() {
+        use LeadKind::*;
+        let fourth = |holding, strain| card(LeadStyle::FourthBest, holding, strain);
+        assert_eq!(fourth("KQJ4", "N"), (Card::SK, Sequence));
+        assert_eq!(fourth("KQT4", "N"), (Card::SK, Sequence));
+        assert_eq!(fourth("KQ74", "N"), (Card::S4, Length));
+        assert_eq!(fourth("KQ74", "H"), (Card::SK, Sequence));
+        assert_eq!(fourth("KJT5", "N"), (Card::SJ, InteriorSequence));
+        assert_eq!(fourth("AJT5", "H"), (Card::SA, Ace));
+        assert_eq!(fourth("AJT5", "N"), (Card::SJ, InteriorSequence));
+        assert_eq!(fourth("Q8532", "N"), (Card::S3, Length));
+        assert_eq!(fourth("8532", "N"), (Card::S5, Nothing));
+        assert_eq!(fourth("853", "N"), (Card::S5, Nothing));
+        assert_eq!(fourth("K53", "N"), (Card::S3, LowFromHonour));
+        assert_eq!(fourth("72", "N"), (Card::S7, Doubleton));
+        assert_eq!(fourth("7", "H"), (Card::S7, Singleton));
+        assert_eq!(fourth("Q73", "S"), (Card::S3, Trump));
+
+        let third_fifth = |holding| card(LeadStyle::ThirdAndFifth, holding, "N");
+        assert_eq!(third_fifth("Q853"), (Card::S5, Length));
+        assert_eq!(third_fifth("Q8532"), (Card::S2, Length));
+        assert_eq!(third_fifth("853"), (Card::S5, Nothing));
+
+        let attitude = |holding| card(LeadStyle::Attitude, holding, "N");
+        assert_eq!(attitude("Q853"), (Card::S3, Length));
+        assert_eq!(attitude("853"), (Card::S8, Nothing));
+    }
+
+    #[test]
+    fn rank_leads_test() {
+        let engine = LeadEngine::new();
+        let contract = |text: &str| Contract::from_str(text, Vulnerable::No).unwrap();
+
+        // Against no trumps, the long suit rather than the sequence in a short one.
+        let hand = Hand::from_str("KJ854 QJT 972 83").unwrap();
+        assert_eq!(engine.lead(hand, contract("3NS"), None), Some(Card::S5));
+        // Against a suit, the sequence rather than leading away from the king.
+        assert_eq!(engine.lead(hand, contract("4DS"), None), Some(Card::HQ));
+
+        // Ace-king against a suit contract, never an underled ace.
+        let hand = Hand::from_str("A874 AK3 9652 T4").unwrap();
+        let leads = engine.leads(hand, contract("4DS"), None);
+        assert_eq!(leads[0].card(), Card::HA);
+        assert!(leads.iter().all(|lead| lead.card() != Card::S4));
+
+        // The opponents bid diamonds and spades: lead the unbid major.
+        let hand = Hand::from_str("9843 K52 QJ7 T86").unwrap();
+        let auction = Bidding::from_str("1D P 1S P 1NT P 3NT P P P").unwrap();
+        let leads = engine.leads(hand, contract("3NS"), Some((&auction, Seat::South)));
+        assert_eq!(leads[0].card(), Card::H2);
+        assert_eq!(leads[3].card(), Card::D7);
+
+        // Partner's suit comes first, even from three small.
+        let auction = Bidding::from_str("1D P 1S 2C 2NT P 3NT P P P").unwrap();
+        let leads = engine.leads(hand, contract("3NS"), Some((&auction, Seat::South)));
+        assert_eq!(leads[0].card(), Card::C8);
+
+        // Against a grand slam, a trump.
+        let hand = Hand::from_str("K832 Q8532 64 92").unwrap();
+        assert_eq!(engine.lead(hand, contract("7DS"), None), Some(Card::D4));
+    }
+}
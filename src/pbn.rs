@@ -0,0 +1,1217 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Reading and writing of PBN (Portable Bridge Notation) files.
+//!
+//! A PBN file is a sequence of games, separated by empty lines. Every game is made of tag pairs
+//! (`[Name "Value"]`), some of which (like `Auction`, `Play` and `OptimumResultTable`) are
+//! followed by a section of data. We keep every tag in the order we found it, so that a file
+//! can be written back as it was read, and we offer typed access to the most common tags.
+
+use crate::prelude::*;
+
+/// Tag value that, in a PBN file, means "same value as the previous game".
+const REPEAT_PREVIOUS: &str = "#";
+
+/// Tag value used to write an [`OptimumResultTable`](PbnGame::optimum_result_table).
+const OPTIMUM_RESULT_TABLE_FORMAT: &str = "Declarer;Denomination\\2R;Result\\2R";
+
+/// A row of an `OptimumResultTable`: declarer, strain and number of tricks taken.
+pub type OptimumResult = (Seat, Strain, u8);
+
+/// Error kind that models possible errors that could occur while parsing a PBN file.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ParsePbnErrorKind {
+    /// A malformed line, with its number (starting from 1).
+    Syntax(usize),
+    /// A tag whose value is not valid, with the name of the tag.
+    Tag(String),
+    Deal,
+    Auction(BuildBiddingError),
+    Play,
+}
+
+impl std::fmt::Display for ParsePbnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(line) => write!(f, "syntax error at line {line}"),
+            Self::Tag(name) => write!(f, "invalid value for tag {name}"),
+            Self::Deal => write!(f, "invalid deal"),
+            Self::Auction(e) => write!(f, "invalid auction: {e}"),
+            Self::Play => write!(f, "invalid play"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsePbnError {
+    pbn: String,
+    kind: ParsePbnErrorKind,
+}
+
+impl ParsePbnError {
+    fn new<T: ToString + ?Sized>(kind: ParsePbnErrorKind, pbn: &T) -> Self {
+        Self {
+            pbn: pbn.to_string(),
+            kind,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &ParsePbnErrorKind {
+        &self.kind
+    }
+}
+
+impl std::fmt::Display for ParsePbnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unable to parse PBN, {}: {}", self.kind, self.pbn)
+    }
+}
+
+impl std::error::Error for ParsePbnError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.kind {
+            ParsePbnErrorKind::Auction(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A tag pair of a PBN game, together with the lines of its section (if any).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbnTag {
+    name: String,
+    value: String,
+    section: Vec<String>,
+}
+
+impl PbnTag {
+    #[must_use]
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            section: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The lines of data following the tag, as found in the file.
+    #[must_use]
+    pub fn section(&self) -> &[String] {
+        &self.section
+    }
+
+    /// The tokens of the section, without comments.
+    fn section_tokens(&self) -> Vec<&str> {
+        let mut tokens = Vec::new();
+        let mut in_comment = false;
+        for line in &self.section {
+            let line = line.split(';').next().unwrap_or_default();
+            for token in line.split_whitespace() {
+                if in_comment || token.starts_with('{') {
+                    in_comment = !token.contains('}');
+                } else {
+                    tokens.push(token);
+                }
+            }
+        }
+        tokens
+    }
+}
+
+impl std::fmt::Display for PbnTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{} \"{}\"]", self.name, escape_tag_value(&self.value))?;
+        for line in &self.section {
+            write!(f, "\n{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A comment of a PBN game, with the number of tags before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbnComment {
+    tags: usize,
+    text: String,
+}
+
+impl PbnComment {
+    /// Number of tags of the game before the comment.
+    #[must_use]
+    pub fn tags(&self) -> usize {
+        self.tags
+    }
+
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// A single game (board) of a PBN file.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let pbn = PbnFile::from_str(
+///     "[Board \"1\"]\n[Dealer \"N\"]\n[Vulnerable \"None\"]\n\
+///      [Deal \"N:AKQJ.AKQ.AKQ.AKQ T987.JT9.JT9.JT9 6543.876.876.876 2.5432.5432.5432\"]\n\
+///      [Declarer \"N\"]\n[Contract \"7NT\"]\n[Result \"13\"]",
+/// )?;
+/// let game = &pbn.games()[0];
+/// assert_eq!(game.board()?, Some(1));
+/// assert_eq!(game.contract()?.unwrap().level(), 7);
+/// let deal = game.deal()?.unwrap();
+/// assert_eq!(deal.north().hcp(), 37);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PbnGame {
+    tags: Vec<PbnTag>,
+    commentary: Vec<PbnComment>,
+}
+
+impl PbnGame {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every tag of the game, in order.
+    #[must_use]
+    pub fn tags(&self) -> &[PbnTag] {
+        &self.tags
+    }
+
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&PbnTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    /// The value of the tag `name`, if present.
+    #[must_use]
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.tag(name).map(PbnTag::value)
+    }
+
+    /// Sets the value of the tag `name`, adding the tag if not present.
+    /// The section of the tag, if any, is removed.
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        self.set_tag_with_section(name, value, Vec::new());
+    }
+
+    /// Sets the value and the section of the tag `name`, adding the tag if not present.
+    pub fn set_tag_with_section(
+        &mut self,
+        name: &str,
+        value: impl Into<String>,
+        section: Vec<String>,
+    ) {
+        let value = value.into();
+        if let Some(tag) = self.tags.iter_mut().find(|tag| tag.name == name) {
+            tag.value = value;
+            tag.section = section;
+        } else {
+            self.tags.push(PbnTag {
+                name: name.to_owned(),
+                value,
+                section,
+            });
+        }
+    }
+
+    /// Removes the tag `name`, returning it.
+    pub fn remove_tag(&mut self, name: &str) -> Option<PbnTag> {
+        let index = self.tags.iter().position(|tag| tag.name == name)?;
+        for comment in &mut self.commentary {
+            if comment.tags > index {
+                comment.tags -= 1;
+            }
+        }
+        Some(self.tags.remove(index))
+    }
+
+    /// The comments found between the tags of the game.
+    #[must_use]
+    pub fn commentary(&self) -> &[PbnComment] {
+        &self.commentary
+    }
+
+    /// Adds a comment after the last tag of the game.
+    pub fn add_commentary(&mut self, text: impl Into<String>) {
+        self.commentary.push(PbnComment {
+            tags: self.tags.len(),
+            text: text.into(),
+        });
+    }
+
+    #[must_use]
+    pub fn event(&self) -> Option<&str> {
+        self.value("Event")
+    }
+
+    #[must_use]
+    pub fn site(&self) -> Option<&str> {
+        self.value("Site")
+    }
+
+    #[must_use]
+    pub fn date(&self) -> Option<&str> {
+        self.value("Date")
+    }
+
+    #[must_use]
+    pub fn scoring(&self) -> Option<&str> {
+        self.value("Scoring")
+    }
+
+    /// The players of the game, in the order North, East, South, West.
+    #[must_use]
+    pub fn players(&self) -> [Option<&str>; NUMBER_OF_HANDS] {
+        ["North", "East", "South", "West"].map(|seat| self.value(seat))
+    }
+
+    /// # Errors
+    /// If the `Board` tag is not a number.
+    pub fn board(&self) -> Result<Option<u32>, ParsePbnError> {
+        self.parse_tag("Board", |value| value.parse().ok())
+    }
+
+    /// # Errors
+    /// If the `Dealer` tag is not a seat.
+    pub fn dealer(&self) -> Result<Option<Seat>, ParsePbnError> {
+        self.parse_tag("Dealer", parse_seat)
+    }
+
+    /// # Errors
+    /// If the `Vulnerable` tag is not a valid vulnerability.
+    pub fn vulnerability(&self) -> Result<Option<Vulnerability>, ParsePbnError> {
+        self.parse_tag("Vulnerable", |value| match value {
+            "None" | "Love" | "-" => Some(Vulnerability::None),
+            "NS" => Some(Vulnerability::NS),
+            "EW" => Some(Vulnerability::EW),
+            "All" | "Both" => Some(Vulnerability::All),
+            _ => None,
+        })
+    }
+
+    /// The hands of the `Deal` tag, in the order North, East, South, West.
+    /// Hands unknown in the file (written as `-`) are `None`.
+    ///
+    /// # Errors
+    /// If the `Deal` tag is malformed.
+    pub fn hands(&self) -> Result<Option<[Option<Hand>; NUMBER_OF_HANDS]>, ParsePbnError> {
+        let Some(value) = self.value("Deal") else {
+            return Ok(None);
+        };
+        let error = || ParsePbnError::new(ParsePbnErrorKind::Deal, value);
+        let (first, hands) = value.split_once(':').ok_or_else(error)?;
+        let first = parse_seat(first.trim()).ok_or_else(error)?;
+        let mut result = [None; NUMBER_OF_HANDS];
+        let mut deck = Cards::ALL;
+        let mut count = 0;
+        for (seat, hand) in (0..NUMBER_OF_HANDS)
+            .map(|offset| first + offset)
+            .zip(hands.split_whitespace())
+        {
+            count += 1;
+            if hand == "-" {
+                continue;
+            }
+            if hand.split('.').count() != SUITS {
+                return Err(error());
+            }
+            let cards = Cards::from_str(hand).map_err(|_| error())?;
+            if cards.len() != 13 || !(cards - deck).is_empty() {
+                return Err(error());
+            }
+            deck -= cards;
+            result[seat as usize] = Some(Hand { cards });
+        }
+        if count != NUMBER_OF_HANDS {
+            return Err(error());
+        }
+        Ok(Some(result))
+    }
+
+    /// The [`Deal`] of the game, with its board number, dealer, vulnerability, contract and
+    /// auction. If only one hand is missing, it's completed with the remaining cards.
+    /// Dealer and vulnerability, if missing, are derived from the board number.
+    ///
+    /// # Errors
+    /// If the `Deal` tag is malformed, more than a hand is missing or one of the other tags
+    /// is invalid.
+    pub fn deal(&self) -> Result<Option<Deal>, ParsePbnError> {
+        let Some(hands) = self.hands()? else {
+            return Ok(None);
+        };
+        let mut deck = Cards::ALL;
+        for hand in hands.iter().flatten() {
+            deck -= hand.cards;
+        }
+        if hands.iter().filter(|hand| hand.is_none()).count() > 1 {
+            return Err(ParsePbnError::new(
+                ParsePbnErrorKind::Deal,
+                self.value("Deal").unwrap_or_default(),
+            ));
+        }
+        let mut deal = Deal::from_hands(hands.map(|hand| hand.unwrap_or(Hand { cards: deck })))
+            .with_number(self.board()?.unwrap_or(1));
+        let auction = self.auction()?;
+        // Without a `Dealer` tag, the dealer is the first seat of the auction.
+        if let Some(dealer) = self.dealer()?.or(auction.as_ref().map(|&(first, _)| first)) {
+            deal.set_dealer(dealer);
+        }
+        if let Some(vulnerability) = self.vulnerability()? {
+            deal.set_vuln(vulnerability);
+        }
+        deal.set_contract(self.contract()?);
+        deal.set_auction(auction.map(|(_, auction)| auction));
+        Ok(Some(deal))
+    }
+
+    /// The declarer of the game. Returns `None` if there is no declarer, written as an empty
+    /// or a `?` tag, as for a passed out deal.
+    ///
+    /// # Errors
+    /// If the `Declarer` tag is not a seat.
+    pub fn declarer(&self) -> Result<Option<Seat>, ParsePbnError> {
+        if matches!(self.value("Declarer").map(str::trim), Some("" | "?")) {
+            return Ok(None);
+        }
+        self.parse_tag("Declarer", |value| {
+            // A `^` before the declarer means irregular declarer.
+            parse_seat(value.trim_start_matches('^'))
+        })
+    }
+
+    /// The contract of the game. Returns `None` if there is no contract or the deal was passed
+    /// out. The vulnerability of the declarer is taken from the `Vulnerable` tag.
+    ///
+    /// # Errors
+    /// If the `Contract`, `Declarer` or `Vulnerable` tags are not valid.
+    pub fn contract(&self) -> Result<Option<Contract>, ParsePbnError> {
+        let Some(value) = self.value("Contract") else {
+            return Ok(None);
+        };
+        if value.eq_ignore_ascii_case("pass") || value.is_empty() || value == "?" {
+            return Ok(None);
+        }
+        let error = || ParsePbnError::new(ParsePbnErrorKind::Tag("Contract".to_owned()), value);
+        let (level, strain, doubled) = parse_contract(value).ok_or_else(error)?;
+        let declarer = self
+            .declarer()?
+            .ok_or_else(|| ParsePbnError::new(ParsePbnErrorKind::Tag("Declarer".to_owned()), ""))?;
+        let vulnerable = self
+            .vulnerability()?
+            .unwrap_or_default()
+            .is_vulnerable(declarer);
+        Ok(Some(Contract::new(
+            level, strain, declarer, vulnerable, doubled,
+        )))
+    }
+
+    /// Number of tricks taken by declarer.
+    ///
+    /// # Errors
+    /// If the `Result` tag is not a number of tricks.
+    pub fn result(&self) -> Result<Option<u8>, ParsePbnError> {
+        self.parse_tag("Result", |value| {
+            value.parse().ok().filter(|&tricks: &u8| tricks <= 13)
+        })
+    }
+
+    /// The auction of the game, with the seat that made the first call.
+    ///
+    /// # Errors
+    /// If the `Auction` tag is not a seat or the auction contains invalid calls.
+    pub fn auction(&self) -> Result<Option<(Seat, Bidding)>, ParsePbnError> {
+        let Some(tag) = self.tag("Auction") else {
+            return Ok(None);
+        };
+        let first = parse_seat(&tag.value).ok_or_else(|| {
+            ParsePbnError::new(ParsePbnErrorKind::Tag("Auction".to_owned()), &tag.value)
+        })?;
+        let mut bidding = Bidding::new();
+        let push = |bidding: &mut Bidding, bid| {
+            bidding
+                .push(bid)
+                .map_err(|e| ParsePbnError::new(ParsePbnErrorKind::Auction(e), &tag.value))
+        };
+        for token in tag.section_tokens() {
+            match token {
+                "AP" => {
+                    let bids = bidding.iter().rev().take_while(|bid| **bid == Bid::Pass);
+                    let passes = if bidding.iter().all(|bid| *bid == Bid::Pass) {
+                        4 - bids.count().min(4)
+                    } else {
+                        3 - bids.count().min(3)
+                    };
+                    for _ in 0..passes {
+                        push(&mut bidding, Bid::Pass)?;
+                    }
+                }
+                // Notes, annotations, and markers of irregular or unknown calls.
+                _ if token.starts_with('=') || token.starts_with('$') => {}
+                "-" | "*" | "+" | "!" | "?" => {}
+                call => {
+                    let bid = parse_call(call).ok_or_else(|| {
+                        ParsePbnError::new(
+                            ParsePbnErrorKind::Auction(BuildBiddingError::unparsable(call)),
+                            call,
+                        )
+                    })?;
+                    push(&mut bidding, bid)?;
+                }
+            }
+        }
+        Ok(Some((first, bidding)))
+    }
+
+    /// The cards played, in the order they were played, with the opening leader.
+    /// Needs a valid contract to know who won every trick.
+    ///
+    /// # Errors
+    /// If the `Play` tag is not a seat, the play contains invalid cards or there is no contract.
+    pub fn play(&self) -> Result<Option<(Seat, PlaySequence)>, ParsePbnError> {
+        let Some(tag) = self.tag("Play") else {
+            return Ok(None);
+        };
+        let error = || ParsePbnError::new(ParsePbnErrorKind::Play, &tag.value);
+        let first = parse_seat(&tag.value).ok_or_else(error)?;
+        let strain = self.contract()?.ok_or_else(error)?.strain();
+        let tokens: Vec<&str> = tag
+            .section_tokens()
+            .into_iter()
+            .take_while(|&token| token != "*")
+            .filter(|token| !token.starts_with('=') && !token.starts_with('$'))
+            .collect();
+        let mut sequence = Vec::with_capacity(tokens.len());
+        let mut leader = first;
+        for trick in tokens.chunks(NUMBER_OF_HANDS) {
+            let mut cards = Vec::with_capacity(NUMBER_OF_HANDS);
+            for seat in (0..NUMBER_OF_HANDS).map(|offset| leader + offset) {
+                let column = (seat as usize + NUMBER_OF_HANDS - first as usize) % NUMBER_OF_HANDS;
+                match trick.get(column) {
+                    Some(&"-") | None => break,
+                    Some(card) => {
+                        cards.push(
+                            Card::from_str(card.trim_end_matches(['!', '?']))
+                                .map_err(|_| error())?,
+                        );
+                    }
+                }
+            }
+            sequence.extend_from_slice(&cards);
+            if cards.len() < NUMBER_OF_HANDS {
+                break;
+            }
+            leader = leader + trick_winner(&cards, strain);
+        }
+        Ok(Some((first, PlaySequence::new(sequence))))
+    }
+
+    /// The double dummy results in the `OptimumResultTable` tag, as declarer, strain and tricks.
+    ///
+    /// # Errors
+    /// If the table contains invalid entries.
+    pub fn optimum_result_table(&self) -> Result<Option<Vec<OptimumResult>>, ParsePbnError> {
+        let Some(tag) = self.tag("OptimumResultTable") else {
+            return Ok(None);
+        };
+        let error = || ParsePbnError::new(ParsePbnErrorKind::Tag(tag.name.clone()), &tag.value);
+        let tokens = tag.section_tokens();
+        if tokens.len() % 3 != 0 {
+            return Err(error());
+        }
+        tokens
+            .chunks(3)
+            .map(|row| {
+                Some((
+                    parse_seat(row[0])?,
+                    parse_strain(row[1])?,
+                    row[2].parse().ok().filter(|&tricks: &u8| tricks <= 13)?,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Some)
+            .ok_or_else(error)
+    }
+
+    /// The notes of the game, as number and text (e.g. `[Note "1:strong"]`).
+    #[must_use]
+    pub fn notes(&self) -> Vec<(u32, &str)> {
+        self.tags
+            .iter()
+            .filter(|tag| tag.name == "Note")
+            .filter_map(|tag| {
+                let (number, text) = tag.value.split_once(':')?;
+                Some((number.trim().parse().ok()?, text))
+            })
+            .collect()
+    }
+
+    pub fn set_board(&mut self, board: u32) {
+        self.set_tag("Board", board.to_string());
+    }
+
+    pub fn set_dealer(&mut self, dealer: Seat) {
+        self.set_tag("Dealer", dealer.to_string());
+    }
+
+    pub fn set_vulnerability(&mut self, vulnerability: Vulnerability) {
+        self.set_tag("Vulnerable", pbn_vulnerability(vulnerability));
+    }
+
+    /// Sets the `Deal` tag, starting from North.
+    pub fn set_deal(&mut self, deal: &Deal) {
+        self.set_tag(
+            "Deal",
+            format!("N:{}", deal.iter().copied().map(hand_code).format(" ")),
+        );
+    }
+
+    /// Sets the `Contract` and the `Declarer` tags. Use `None` for a passed out deal.
+    pub fn set_contract(&mut self, contract: Option<&Contract>) {
+        if let Some(contract) = contract {
+            self.set_tag("Declarer", contract.declarer().to_string());
+            self.set_tag("Contract", contract_str(*contract));
+        } else {
+            self.set_tag("Declarer", "?");
+            self.set_tag("Contract", "Pass");
+        }
+    }
+
+    pub fn set_result(&mut self, tricks: u8) {
+        self.set_tag("Result", tricks.to_string());
+    }
+
+    /// Sets the `Auction` tag, with `first` the seat making the first call.
+    pub fn set_auction(&mut self, first: Seat, bidding: &Bidding) {
+        let section = bidding
+            .iter()
+            .map(|&bid| call_str(bid))
+            .chunks(NUMBER_OF_HANDS)
+            .into_iter()
+            .map(|mut calls| calls.join(" "))
+            .collect();
+        self.set_tag_with_section("Auction", first.to_string(), section);
+    }
+
+    /// Sets the `Play` tag from the cards played in order, starting with the opening lead of
+    /// `leader`. The play is written in columns, one trick per line, the first column being
+    /// the opening leader's. We need the `strain` of the contract to know who won every trick.
+    pub fn set_play(&mut self, leader: Seat, play: &PlaySequence, strain: Strain) {
+        let mut section = Vec::new();
+        let mut trick_leader = leader;
+        for trick in &play.iter().copied().chunks(NUMBER_OF_HANDS) {
+            let cards: Vec<Card> = trick.collect();
+            let mut columns = [String::from("-"), "-".into(), "-".into(), "-".into()];
+            for (seat, card) in (0..NUMBER_OF_HANDS)
+                .map(|offset| trick_leader + offset)
+                .zip(cards.iter())
+            {
+                columns[(seat as usize + NUMBER_OF_HANDS - leader as usize) % NUMBER_OF_HANDS] =
+                    card_code(*card);
+            }
+            section.push(columns.join(" "));
+            if cards.len() < NUMBER_OF_HANDS {
+                break;
+            }
+            trick_leader = trick_leader + trick_winner(&cards, strain);
+        }
+        if play.len() < 52 {
+            section.push(String::from("*"));
+        }
+        self.set_tag_with_section("Play", leader.to_string(), section);
+    }
+
+    /// Sets the `OptimumResultTable` tag from the double dummy results of declarer and strain.
+    pub fn set_optimum_result_table(&mut self, table: &[OptimumResult]) {
+        let section = table
+            .iter()
+            .map(|(seat, strain, tricks)| format!("{seat} {} {tricks}", strain.not_unicode_str()))
+            .collect();
+        self.set_tag_with_section("OptimumResultTable", OPTIMUM_RESULT_TABLE_FORMAT, section);
+    }
+
+    /// Adds a `Note` tag with the given number.
+    pub fn add_note(&mut self, number: u32, text: &str) {
+        self.tags
+            .push(PbnTag::new("Note", format!("{number}:{text}")));
+    }
+
+    fn parse_tag<T, F: Fn(&str) -> Option<T>>(
+        &self,
+        name: &str,
+        parse: F,
+    ) -> Result<Option<T>, ParsePbnError> {
+        self.value(name)
+            .map(|value| {
+                parse(value.trim()).ok_or_else(|| {
+                    ParsePbnError::new(ParsePbnErrorKind::Tag(name.to_owned()), value)
+                })
+            })
+            .transpose()
+    }
+}
+
+impl From<&Deal> for PbnGame {
+    /// A game with the `Board`, `Dealer`, `Vulnerable` and `Deal` tags of `deal`,
+    /// and with its contract and auction, if known.
+    fn from(deal: &Deal) -> Self {
+        let mut game = PbnGame::new();
+        game.set_board(deal.number());
+        game.set_dealer(deal.dealer());
+        game.set_vulnerability(deal.vulnerability());
+        game.set_deal(deal);
+        // Without a contract, a non empty auction was passed out.
+        if deal.contract().is_some() || deal.auction().is_some_and(|auction| !auction.is_empty()) {
+            game.set_contract(deal.contract().as_ref());
+        }
+        if let Some(auction) = deal.auction() {
+            game.set_auction(deal.dealer(), auction);
+        }
+        game
+    }
+}
+
+impl std::fmt::Display for PbnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Comments are written right before the first tag that follows them.
+        let mut commentary = self.commentary.iter().peekable();
+        for (index, tag) in self.tags.iter().enumerate() {
+            while let Some(comment) = commentary.next_if(|comment| comment.tags <= index) {
+                writeln!(f, "{{{}}}", comment.text)?;
+            }
+            writeln!(f, "{tag}")?;
+        }
+        for comment in commentary {
+            writeln!(f, "{{{}}}", comment.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// A PBN file: the escape lines found at the beginning (e.g. `% PBN 2.1`) and the games.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let mut deal = Deal::new();
+/// let mut file = PbnFile::new();
+/// file.push(PbnGame::from(&deal));
+/// let read = PbnFile::from_str(&file.to_string())?;
+/// assert_eq!(read.games()[0].deal()?.unwrap().hands(), deal.hands());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PbnFile {
+    directives: Vec<String>,
+    games: Vec<PbnGame>,
+}
+
+impl PbnFile {
+    /// A new file, declaring the PBN version.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            directives: vec![String::from("% PBN 2.1"), String::from("% EXPORT")],
+            games: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn directives(&self) -> &[String] {
+        &self.directives
+    }
+
+    #[must_use]
+    pub fn games(&self) -> &[PbnGame] {
+        &self.games
+    }
+
+    pub fn push(&mut self, game: PbnGame) {
+        self.games.push(game);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PbnGame> {
+        self.games.iter()
+    }
+}
+
+impl IntoIterator for PbnFile {
+    type Item = PbnGame;
+    type IntoIter = <Vec<PbnGame> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.games.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PbnFile {
+    type Item = &'a PbnGame;
+    type IntoIter = std::slice::Iter<'a, PbnGame>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromStr for PbnFile {
+    type Err = ParsePbnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut file = PbnFile::default();
+        let mut game = PbnGame::new();
+        let mut comment: Option<String> = None;
+        for (number, line) in (1..).zip(s.lines()) {
+            let line = line.trim_end();
+            if let Some(ref mut text) = comment {
+                if let Some((end, _)) = line.split_once('}') {
+                    text.push('\n');
+                    text.push_str(end);
+                    game.add_commentary(std::mem::take(text));
+                    comment = None;
+                } else {
+                    text.push('\n');
+                    text.push_str(line);
+                }
+                continue;
+            }
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                file.finish_game(&mut game);
+            } else if trimmed.starts_with('%') {
+                file.directives.push(line.to_owned());
+            } else if let Some(text) = trimmed.strip_prefix(';') {
+                game.add_commentary(text.trim());
+            } else if let Some(text) = trimmed.strip_prefix('{') {
+                if let Some((text, _)) = text.split_once('}') {
+                    game.add_commentary(text);
+                } else {
+                    comment = Some(text.to_owned());
+                }
+            } else if trimmed.starts_with('[') {
+                let (mut tag, rest) = parse_tag_pair(trimmed)
+                    .ok_or_else(|| ParsePbnError::new(ParsePbnErrorKind::Syntax(number), line))?;
+                if tag.value == REPEAT_PREVIOUS {
+                    if let Some(previous) = file.games.last().and_then(|g| g.value(&tag.name)) {
+                        previous.clone_into(&mut tag.value);
+                    }
+                }
+                if !rest.is_empty() {
+                    tag.section.push(rest.to_owned());
+                }
+                game.tags.push(tag);
+            } else if let Some(tag) = game.tags.last_mut() {
+                tag.section.push(line.to_owned());
+            } else {
+                return Err(ParsePbnError::new(ParsePbnErrorKind::Syntax(number), line));
+            }
+        }
+        if comment.is_some() {
+            return Err(ParsePbnError::new(
+                ParsePbnErrorKind::Syntax(s.lines().count()),
+                "unterminated comment",
+            ));
+        }
+        file.finish_game(&mut game);
+        Ok(file)
+    }
+}
+
+impl PbnFile {
+    fn finish_game(&mut self, game: &mut PbnGame) {
+        if game.tags.is_empty() {
+            // Comments before the first tag of a game belong to the game itself.
+            return;
+        }
+        self.games.push(std::mem::take(game));
+    }
+}
+
+impl std::fmt::Display for PbnFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for directive in &self.directives {
+            writeln!(f, "{directive}")?;
+        }
+        for (index, game) in self.games.iter().enumerate() {
+            if index != 0 || !self.directives.is_empty() {
+                writeln!(f)?;
+            }
+            write!(f, "{game}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a `[Name "Value"]` tag pair, returning it with the rest of the line.
+fn parse_tag_pair(line: &str) -> Option<(PbnTag, &str)> {
+    let line = line.strip_prefix('[')?.trim_start();
+    let name_end = line.find(|c: char| c.is_whitespace() || c == '"')?;
+    let (name, rest) = line.split_at(name_end);
+    let rest = rest.trim_start().strip_prefix('"')?;
+    let mut value = String::new();
+    let mut chars = rest.char_indices();
+    let end = loop {
+        match chars.next()? {
+            // Only quotes and backslashes are escaped: other backslashes, as in the
+            // `\2R` of an `OptimumResultTable`, are part of the value.
+            (_, '\\') => match chars.clone().next()? {
+                (_, escaped @ ('"' | '\\')) => {
+                    chars.next();
+                    value.push(escaped);
+                }
+                _ => value.push('\\'),
+            },
+            (index, '"') => break index,
+            (_, c) => value.push(c),
+        }
+    };
+    let rest = rest[end + 1..].trim_start().strip_prefix(']')?;
+    Some((PbnTag::new(name, value), rest.trim()))
+}
+
+/// Escapes a tag value, the inverse of [`parse_tag_pair`]: quotes are always escaped,
+/// backslashes only when they would be read as an escape.
+fn escape_tag_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' if matches!(chars.peek(), None | Some('"' | '\\')) => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A seat written as its initial, in any case.
+pub(crate) fn parse_seat(value: &str) -> Option<Seat> {
+    let mut chars = value.chars();
+    let seat = Seat::try_from(chars.next()?.to_ascii_uppercase()).ok()?;
+    chars.next().is_none().then_some(seat)
+}
+
+/// Parses a contract like `4S`, `3NTX` or `6HXX`.
+fn parse_contract(value: &str) -> Option<(u8, Strain, Doubled)> {
+    let level = value
+        .get(..1)?
+        .parse()
+        .ok()
+        .filter(|level| (1..=7).contains(level))?;
+    let rest = value.get(1..)?;
+    let strain_and_doubles = rest.trim_end_matches(['X', 'x']);
+    let doubled = match rest.len() - strain_and_doubles.len() {
+        0 => Doubled::NotDoubled,
+        1 => Doubled::Doubled,
+        2 => Doubled::Redoubled,
+        _ => return None,
+    };
+    Some((level, parse_strain(strain_and_doubles)?, doubled))
+}
+
+/// Parses a call of an auction like `Pass`, `X`, `XX`, `1NT`, ignoring the `!` and `?`
+/// annotations.
+fn parse_call(value: &str) -> Option<Bid> {
+    Bid::from_str(value.trim_end_matches(['!', '?'])).ok()
+}
+
+fn call_str(bid: Bid) -> String {
+    match bid {
+        Bid::Pass => String::from("Pass"),
+        Bid::Contract(level, Strain::NoTrumps) => format!("{level}NT"),
+        bid => bid_code(bid),
+    }
+}
+
+fn contract_str(contract: Contract) -> String {
+    let doubled = match contract.doubled() {
+        Doubled::NotDoubled => "",
+        Doubled::Doubled => "X",
+        Doubled::Redoubled => "XX",
+    };
+    format!(
+        "{}{}{doubled}",
+        contract.level(),
+        contract.strain().not_unicode_str()
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::NonZeroU8;
+
+    const PBN: &str = r##"% PBN 2.1
+% EXPORT
+[Event "Club night"]
+[Site "Milano"]
+[Date "2024.03.01"]
+[Board "1"]
+[West "Wendy"]
+[North "Nick"]
+[East "Eve"]
+[South "Sam"]
+[Dealer "N"]
+[Vulnerable "None"]
+[Deal "N:AKQ.AKQ.AKQ.AKQJ JT9.JT9.JT9.T987 876.876.876.6543 5432.5432.5432.2"]
+[Scoring "IMP"]
+[Declarer "S"]
+[Contract "6NTX"]
+[Result "12"]
+[Auction "N"]
+2C Pass 2D Pass
+6NT =1= Pass Pass X
+AP
+[Note "1:to play"]
+[Play "W"]
+S2 SA SJ S8
+{the rest is easy}
+*
+[OptimumResultTable "Declarer;Denomination\2R;Result\2R"]
+N NT 13
+S NT 13
+E S 0
+
+[Event "#"]
+[Board "2"]
+[Dealer "E"]
+[Vulnerable "NS"]
+[Deal "E:- AKQ.AKQ.AKQ.AKQJ JT9.JT9.JT9.T987 876.876.876.6543"]
+"##;
+
+    #[test]
+    fn parses_pbn_test() {
+        let file = PbnFile::from_str(PBN).unwrap();
+        assert_eq!(file.directives().len(), 2);
+        assert_eq!(file.games().len(), 2);
+        let game = &file.games()[0];
+        assert_eq!(game.event(), Some("Club night"));
+        assert_eq!(game.board().unwrap(), Some(1));
+        assert_eq!(
+            game.players(),
+            [Some("Nick"), Some("Eve"), Some("Sam"), Some("Wendy")]
+        );
+        assert_eq!(game.dealer().unwrap(), Some(Seat::North));
+        let deal = game.deal().unwrap().unwrap();
+        assert_eq!(deal.north().hcp(), 37);
+        assert_eq!(
+            deal.west().as_cards(),
+            Cards::from_str("5432.5432.5432.2").unwrap()
+        );
+        assert_eq!(
+            game.contract().unwrap().unwrap(),
+            Contract::new(
+                6,
+                Strain::NoTrumps,
+                Seat::South,
+                Vulnerable::No,
+                Doubled::Doubled
+            )
+        );
+        assert_eq!(game.result().unwrap(), Some(12));
+        let (first, bidding) = game.auction().unwrap().unwrap();
+        assert_eq!(first, Seat::North);
+        assert_eq!(bidding.len(), 11);
+        assert_eq!(game.notes(), vec![(1, "to play")]);
+        let (leader, play) = game.play().unwrap().unwrap();
+        assert_eq!(leader, Seat::West);
+        assert_eq!(
+            play.iter().copied().collect::<Vec<_>>(),
+            vec![Card::S2, Card::SA, Card::SJ, Card::S8]
+        );
+        let table = game.optimum_result_table().unwrap().unwrap();
+        assert_eq!(table[0], (Seat::North, Strain::NoTrumps, 13));
+        assert_eq!(table[2], (Seat::East, Strain::Spades, 0));
+        assert_eq!(
+            game.value("OptimumResultTable"),
+            Some(OPTIMUM_RESULT_TABLE_FORMAT)
+        );
+        let (tag, _) = parse_tag_pair(r#"[Event "The \"A\" \\ C:\Games\\"]"#).unwrap();
+        assert_eq!(tag.value, r#"The "A" \ C:\Games\"#);
+        assert_eq!(tag.to_string(), r#"[Event "The \"A\" \ C:\Games\\"]"#);
+
+        let game = &file.games()[1];
+        assert_eq!(game.event(), Some("Club night"));
+        assert_eq!(game.vulnerability().unwrap(), Some(Vulnerability::NS));
+        let deal = game.deal().unwrap().unwrap();
+        assert_eq!(
+            deal.east().as_cards(),
+            Cards::from_str("5432.5432.5432.2").unwrap()
+        );
+        assert!(game.contract().unwrap().is_none());
+    }
+
+    #[test]
+    fn writes_pbn_test() {
+        let file = PbnFile::from_str(PBN).unwrap();
+        let written = file.to_string();
+        let reread = PbnFile::from_str(&written).unwrap();
+        assert_eq!(file, reread);
+
+        let deal = Deal::new();
+        let mut game = PbnGame::from(&deal);
+        let contract = Contract::new(
+            3,
+            Strain::NoTrumps,
+            Seat::East,
+            Vulnerable::No,
+            Doubled::NotDoubled,
+        );
+        game.set_contract(Some(&contract));
+        let mut bidding = Bidding::new();
+        for bid in [
+            Bid::Pass,
+            Bid::Contract(NonZeroU8::new(3).unwrap(), Strain::NoTrumps),
+            Bid::Pass,
+            Bid::Pass,
+            Bid::Pass,
+        ] {
+            bidding.push(bid).unwrap();
+        }
+        game.set_auction(Seat::North, &bidding);
+        // Two tricks, South always following with the lowest card.
+        let south = deal.south().as_cards();
+        let mut play = Vec::new();
+        for suit in [Suit::Spades, Suit::Hearts] {
+            for seat in [Seat::South, Seat::West, Seat::North, Seat::East] {
+                let cards = deal[seat as usize].as_cards();
+                let card = cards
+                    .in_suit(suit)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| cards.into_iter().next().unwrap());
+                play.push(card);
+            }
+            if south.in_suit(suit).is_empty() {
+                break;
+            }
+        }
+        let play = PlaySequence::new(play);
+        game.set_play(Seat::South, &play, Strain::NoTrumps);
+        game.set_result(9);
+        game.set_optimum_result_table(&[(Seat::East, Strain::NoTrumps, 9)]);
+
+        let written = game.to_string();
+        assert!(written
+            .lines()
+            .any(|line| line == r#"[OptimumResultTable "Declarer;Denomination\2R;Result\2R"]"#));
+        let reread = PbnFile::from_str(&written).unwrap();
+        let reread = &reread.games()[0];
+        assert_eq!(reread.deal().unwrap().unwrap().hands(), deal.hands());
+        assert_eq!(reread.contract().unwrap(), Some(contract));
+        assert_eq!(reread.auction().unwrap().unwrap().1.len(), 5);
+        let (leader, reread_play) = reread.play().unwrap().unwrap();
+        assert_eq!(leader, Seat::South);
+        assert_eq!(reread_play.iter().collect_vec(), play.iter().collect_vec());
+        assert_eq!(reread.result().unwrap(), Some(9));
+        assert_eq!(
+            reread.optimum_result_table().unwrap(),
+            Some(vec![(Seat::East, Strain::NoTrumps, 9)])
+        );
+
+        // A passed out deal has neither a contract nor a declarer.
+        game.set_contract(None);
+        let reread = PbnFile::from_str(&game.to_string()).unwrap();
+        assert_eq!(reread.games()[0].declarer().unwrap(), None);
+        assert_eq!(reread.games()[0].contract().unwrap(), None);
+    }
+
+    #[test]
+    fn deal_metadata_test() {
+        let mut deal = Deal::new().with_number(7);
+        let contract = Contract::new(
+            2,
+            Strain::Hearts,
+            Seat::West,
+            Vulnerable::Yes,
+            Doubled::Doubled,
+        );
+        let mut auction = Bidding::new();
+        for bid in [
+            Bid::Pass,
+            Bid::Pass,
+            Bid::Contract(NonZeroU8::new(2).unwrap(), Strain::Hearts),
+            Bid::Double,
+            Bid::Pass,
+            Bid::Pass,
+            Bid::Pass,
+        ] {
+            auction.push(bid).unwrap();
+        }
+        deal.set_contract(Some(contract));
+        deal.set_auction(Some(auction));
+        let game = PbnGame::from(&deal);
+        assert_eq!(game.value("Dealer"), Some("S"));
+        assert_eq!(game.value("Vulnerable"), Some("All"));
+        let read = game.deal().unwrap().unwrap();
+        assert_eq!(read.number(), 7);
+        assert_eq!(read.dealer(), Seat::South);
+        assert_eq!(read.vulnerability(), Vulnerability::All);
+        assert_eq!(read.contract(), Some(contract));
+        assert_eq!(read.auction().unwrap().len(), 7);
+        assert_eq!(read.hands(), deal.hands());
+
+        // Without `Dealer`, the dealer is the first seat of the auction.
+        let mut game = PbnGame::from(&deal);
+        game.remove_tag("Dealer");
+        assert_eq!(game.deal().unwrap().unwrap().dealer(), Seat::South);
+        game.set_tag_with_section("Auction", "W", vec![String::from("Pass 2H X AP")]);
+        let read = game.deal().unwrap().unwrap();
+        assert_eq!(read.dealer(), Seat::West);
+        assert_eq!(read.auction().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn keeps_comments_in_place_test() {
+        let pbn = "{first}\n[Board \"1\"]\n{c}\n[Dealer \"N\"]\n{last}\n";
+        let file = PbnFile::from_str(pbn).unwrap();
+        let game = &file.games()[0];
+        assert_eq!(
+            game.commentary()
+                .iter()
+                .map(|comment| (comment.tags(), comment.text()))
+                .collect_vec(),
+            [(0, "first"), (1, "c"), (2, "last")]
+        );
+        assert_eq!(game.to_string(), pbn);
+        let mut game = game.clone();
+        game.remove_tag("Board");
+        assert_eq!(game.to_string(), "{first}\n{c}\n[Dealer \"N\"]\n{last}\n");
+    }
+
+    #[test]
+    fn pbn_errors_test() {
+        assert!(PbnFile::from_str("[Board \"1\"\n").is_err());
+        assert!(PbnFile::from_str("2C Pass").is_err());
+        assert!(PbnFile::from_str("{ never closed").is_err());
+        let file =
+            PbnFile::from_str("[Board \"x\"]\n[Deal \"N:AKQ - - -\"]\n[Auction \"N\"]\nX").unwrap();
+        let game = &file.games()[0];
+        assert!(game.board().is_err());
+        assert!(game.deal().is_err());
+        assert!(game.auction().is_err());
+    }
+}
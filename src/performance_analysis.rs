@@ -328,7 +328,7 @@ enum Position {
 ///
 /// # Panics
 ///
-/// Panics if there is no specified winner for a trick.
+/// Panics if there are no solver results.
 #[must_use]
 pub fn analyse_players_performance(
     contract: Contract,
@@ -338,20 +338,6 @@ pub fn analyse_players_performance(
     let mut results_iterator = results.into_iter();
     let mut players_records: [PlayerPlayRecord; 4] =
         std::array::from_fn(|_| PlayerPlayRecord::new());
-    let (winner_notrump, winner_trump);
-
-    // Determine the winner based on whether it's a No Trump contract or not
-    let winner_function: &dyn Fn(Card, Card, Seat, Seat) -> (Seat, Card) =
-        if matches!(contract.strain(), Strain::NoTrumps) {
-            winner_notrump = winner_nt;
-            &winner_notrump
-        } else {
-            // If it's a Trump contract, set the winner accordingly
-            let trump = contract.strain().try_into().expect("it's not NoTrump");
-            winner_trump = curry_winner_trump(trump);
-            &winner_trump
-        };
-
     let mut winner = contract.leader();
     let mut previous_result = results_iterator
         .next()
@@ -363,56 +349,45 @@ pub fn analyse_players_performance(
         .into_iter()
         .zip(results_iterator.chunks(4).into_iter())
     {
-        let mut card_result_iterator = trick.into_iter().zip(results.into_iter());
-        let (first_card, result) = card_result_iterator.next().expect("there should always be a leader, otherwise the tricks iterator should not have entered another loop.");
-
-        // Process the first card of each trick
-        let diff = performance_difference(previous_result, result);
-        players_records[winner as usize].push_trick(first_card, diff);
-        let mut winner_card = first_card;
-        previous_result = result;
-        let mut seat_iterator = winner.iter_from();
-
-        // Iterate through the rest of the cards in the trick
-        for (card, result) in card_result_iterator {
-            let actual_player = seat_iterator.next().unwrap();
-            (winner, winner_card) = winner_function(winner_card, card, winner, actual_player);
+        let mut cards = Vec::with_capacity(NUMBER_OF_HANDS);
+        for (offset, (card, result)) in trick.into_iter().zip(results).enumerate() {
             let diff = performance_difference(previous_result, result);
-            players_records[actual_player as usize].push_trick(card, diff);
+            players_records[(winner + offset) as usize].push_trick(card, diff);
             previous_result = result;
+            cards.push(card);
         }
-    }
-    players_records
-}
-
-/// Curries a function to determine the correct winner based on the trump suit, returning the
-/// curried function. The function returned returns the winner between two cards, making the
-/// function useful for a reduce-style operation on a iterator.
-fn curry_winner_trump(trump: Suit) -> impl Fn(Card, Card, Seat, Seat) -> (Seat, Card) {
-    move |previous_card: Card, card: Card, winner: Seat, actual_player: Seat| {
-        if previous_card.suit() != card.suit() {
-            // If the second card is a different suit it has to be the only trump
-            if previous_card.suit() == trump {
-                (winner, previous_card)
-            } else {
-                (actual_player, card)
-            }
-        } else if previous_card.rank() > card.rank() {
-            (winner, previous_card)
-        } else {
-            (actual_player, card)
+        if cards.len() == NUMBER_OF_HANDS {
+            winner = winner + trick_winner(&cards, contract.strain());
         }
     }
+    players_records
 }
 
-/// The winner of a no trump trick between two cards
-fn winner_nt(previous_card: Card, card: Card, winner: Seat, actual_player: Seat) -> (Seat, Card) {
-    // Short circuits.
-    if previous_card.suit() != card.suit() || previous_card.rank() > card.rank() {
-        (winner, previous_card)
-    } else {
-        (actual_player, card)
-    }
+/// The performance records of the four players as CSV, with a header and a row for every
+/// card played, from North. See [`crate::export`] for the columns.
+#[must_use]
+pub fn performance_csv(records: &[PlayerPlayRecord; 4]) -> String {
+    let rows = records
+        .iter()
+        .zip([Seat::North, Seat::East, Seat::South, Seat::West])
+        .flat_map(|(record, seat)| {
+            (0..record.results.len()).filter_map(move |index| {
+                let card = record.tricks[index]?;
+                let performance = record.results[index]?;
+                Some(format!(
+                    "{seat},{},{},{},{}\n",
+                    index + 1,
+                    card_code(card),
+                    performance.tricks().0,
+                    performance
+                        .difference()
+                        .map_or(0, |difference| difference.0)
+                ))
+            })
+        });
+    std::iter::once(String::from("seat,card_number,card,tricks,tricks_lost\n"))
+        .chain(rows)
+        .collect()
 }
 
 fn performance_difference(previous_result: i32, result: i32) -> CardPerformance {
@@ -473,50 +448,14 @@ mod tests {
     }
 
     #[test]
-    fn test_winner_suit() {
-        let winner_fn = curry_winner_trump(Suit::Clubs);
-        let winner = winner_fn(
-            Card::new(Suit::Hearts, 10),
-            Card::new(Suit::Hearts, 14),
-            Seat::North,
-            Seat::South,
-        );
-        assert_eq!(winner, (Seat::South, Card::HA));
-
-        let winner = winner_fn(
-            Card::new(Suit::Hearts, 10),
-            Card::new(Suit::Clubs, 2),
-            Seat::North,
-            Seat::South,
-        );
-        assert_eq!(winner, (Seat::South, Card::C2));
-
-        let winner = winner_fn(
-            Card::new(Suit::Clubs, 10),
-            Card::new(Suit::Clubs, 2),
-            Seat::North,
-            Seat::South,
-        );
-        assert_eq!(winner, (Seat::North, Card::C10));
-    }
-
-    #[test]
-    fn test_winner_nt() {
-        let winner = winner_nt(
-            Card::new(Suit::Hearts, 10),
-            Card::new(Suit::Hearts, 14),
-            Seat::North,
-            Seat::South,
-        );
-        assert_eq!(winner, (Seat::South, Card::HA));
-
-        let winner = winner_nt(
-            Card::new(Suit::Spades, 5),
-            Card::new(Suit::Hearts, 14),
-            Seat::West,
-            Seat::North,
+    fn performance_csv_test() {
+        let mut records: [PlayerPlayRecord; 4] = std::array::from_fn(|_| PlayerPlayRecord::new());
+        records[Seat::West as usize].push_trick(Card::SK, performance_difference(9, 9));
+        records[Seat::North as usize].push_trick(Card::S2, performance_difference(9, 8));
+        assert_eq!(
+            performance_csv(&records),
+            "seat,card_number,card,tricks,tricks_lost\nN,1,S2,8,1\nW,1,SK,9,0\n"
         );
-        assert_eq!(winner, (Seat::West, Card::S5));
     }
 
     #[test]
@@ -601,6 +540,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_analyse_discards() {
+        let contract = Contract::from_str("4SW", Vulnerable::No).unwrap();
+        // East and West discard on North's HK: North leads again.
+        let trace_solved = TraceSolved {
+            tricks: PlaySequence::new(vec![Card::HK, Card::D2, Card::H2, Card::C3, Card::HQ]),
+            results: SolvedPlay::from_seq(vec![9, 9, 9, 9, 9, 8]),
+        };
+        let players_performance = analyse_players_performance(contract, trace_solved);
+        assert_eq!(
+            players_performance[Seat::North as usize].tricks[1],
+            Some(Card::HQ)
+        );
+        assert_eq!(
+            players_performance[Seat::North as usize].results[1],
+            Some(CardPerformance::Good(Tricks(8), TrickDifference(1)))
+        );
+    }
+
     #[test]
     fn test_compute_player_performance() {
         let p_performance = [
@@ -632,6 +590,7 @@ mod tests {
         assert_eq!(8, e_perf.correct_cards);
     }
     #[test]
+    #[cfg(feature = "lin")]
     fn test_complete_analysis_pipeline() {
         use dds::analyseplay::PlayAnalyzer;
         let lin = "pn|gattochef,sebyx,Inter2018,fede00|st||md|3SAQ432HQJT72DT3CQ,SKJH983D974CT9876,S965HK654DKJ6CAJ5,ST87HADAQ852CK432|rh||ah|Board 1|sv|o|mb|1C|an|2+|mb|1D|mb|1H|an|picche|mb|2D|mb|p|mb|p|mb|3H|mb|p|mb|3S|mb|p|mb|4S|mb|p|mb|p|mb|p|pg||pc|DA|pc|D3|pc|D9|pc|D6|pg||pc|HA|pc|H2|pc|H9|pc|H4|pg||pc|D8|pc|DT|pc|D7|pc|DJ|pg||pc|S5|pc|S7|pc|SA|pc|SJ|pg||pc|CQ|pc|CT|pc|CA|pc|C2|pg||pc|S6|pc|S8|pc|SQ|pc|SK|pg||pc|H8|mc|9|";
@@ -0,0 +1,560 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use crate::prelude::*;
+#[cfg(feature = "dds")]
+use dds::analyseplay::PlayTraceBin;
+#[cfg(feature = "dds")]
+use dds::utils::{BuildSequenceError, RankSeq, SuitSeq, SEQUENCE_LENGTH};
+use std::{error::Error, fmt};
+
+/// Enum modelling the reasons why a card cannot be played, or a claim cannot be
+/// made, in a [`PlayState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlayErrorKind {
+    /// The player does not hold the card.
+    NotHeld,
+    /// The player did not follow suit while holding a card of the suit led.
+    Revoke,
+    /// All the tricks were played or claimed.
+    Finished,
+    /// A claim can only be made at the start of a trick.
+    ClaimMidTrick,
+    /// More tricks were claimed than there are left to play.
+    TooManyTricks,
+}
+
+impl fmt::Display for PlayErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayErrorKind::NotHeld => write!(f, "the player does not hold the card"),
+            PlayErrorKind::Revoke => write!(f, "the player must follow suit"),
+            PlayErrorKind::Finished => write!(f, "the play is over"),
+            PlayErrorKind::ClaimMidTrick => {
+                write!(f, "claims can only be made at the start of a trick")
+            }
+            PlayErrorKind::TooManyTricks => {
+                write!(f, "more tricks claimed than there are left to play")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayError {
+    seat: Seat,
+    card: Option<Card>,
+    kind: PlayErrorKind,
+}
+
+impl PlayError {
+    pub(crate) fn new(seat: Seat, card: Option<Card>, kind: PlayErrorKind) -> Self {
+        Self { seat, card, kind }
+    }
+
+    /// The player who tried to play or claim.
+    #[must_use]
+    pub fn seat(&self) -> Seat {
+        self.seat
+    }
+
+    /// The card that could not be played, `None` for a claim.
+    #[must_use]
+    pub fn card(&self) -> Option<Card> {
+        self.card
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> PlayErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(card) = self.card {
+            write!(f, "{} cannot play {card}: {}", self.seat, self.kind)
+        } else {
+            write!(f, "{} cannot claim: {}", self.seat, self.kind)
+        }
+    }
+}
+
+impl Error for PlayError {}
+
+/// The winner of a trick, as an index in `trick`: the highest trump if any was played,
+/// otherwise the highest card of the suit led.
+///
+/// # Panics
+/// If `trick` is empty.
+#[must_use]
+pub fn trick_winner(trick: &[Card], strain: Strain) -> usize {
+    let trump = Suit::try_from(strain).ok();
+    let led = trick[0].suit();
+    let rank = |card: &Card| match card.suit() {
+        suit if Some(suit) == trump => 2 * 16 + card.rank(),
+        suit if suit == led => 16 + card.rank(),
+        _ => 0,
+    };
+    trick
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, card)| rank(card))
+        .map(|(index, _)| index)
+        .expect("a trick has at least a card")
+}
+
+/// The cards played in a deal, in order, starting from the opening lead.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlaySequence {
+    sequence: Vec<Card>,
+}
+
+impl PlaySequence {
+    #[must_use]
+    pub fn new(sequence: Vec<Card>) -> Self {
+        Self { sequence }
+    }
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[must_use]
+    pub fn iter(&self) -> <&Self as std::iter::IntoIterator>::IntoIter {
+        self.into_iter()
+    }
+}
+
+impl IntoIterator for PlaySequence {
+    type Item = Card;
+    type IntoIter = <Vec<Card> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.sequence.into_iter()
+    }
+}
+
+impl fmt::Display for PlaySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trick in &self.iter().chunks(4) {
+            writeln!(f, "{}", trick.into_iter().format("-"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dds")]
+impl TryFrom<&PlaySequence> for (SuitSeq, dds::utils::RankSeq) {
+    type Error = BuildSequenceError;
+
+    fn try_from(value: &PlaySequence) -> Result<Self, Self::Error> {
+        let len = value.len();
+        if len == 0 {
+            return Err(BuildSequenceError::SequenceTooShort);
+        } else if len > SEQUENCE_LENGTH {
+            return Err(BuildSequenceError::SequenceTooLong);
+        }
+
+        let (suitseq, rankseq): (Vec<_>, Vec<_>) = value
+            .into_iter()
+            .map(|card| (i32::from(card.suit() as u8), i32::from(card.rank())))
+            .unzip();
+        Ok((
+            SuitSeq::try_from(suitseq.as_slice())?,
+            RankSeq::try_from(rankseq.as_slice())?,
+        ))
+    }
+}
+
+#[cfg(feature = "dds")]
+impl TryFrom<&PlaySequence> for PlayTraceBin {
+    type Error = BuildSequenceError;
+
+    fn try_from(value: &PlaySequence) -> Result<Self, Self::Error> {
+        let sequences = <(SuitSeq, RankSeq)>::try_from(value)?;
+        Ok(Self::from_sequences(sequences.0, sequences.1))
+    }
+}
+
+impl<'a> IntoIterator for &'a PlaySequence {
+    type Item = &'a Card;
+    type IntoIter = <&'a Vec<Card> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.sequence.iter()
+    }
+}
+
+/// The state of the play of a deal: the cards still held by every player, the cards
+/// played so far and the tricks won by each side.
+///
+/// Cards are played one at a time with [`PlayState::play`], which checks that the player
+/// on turn holds the card and follows suit when able; completed tricks are awarded to their
+/// winner, who leads to the next one. Cards can be taken back with [`PlayState::undo`] and
+/// the remaining tricks can be claimed with [`PlayState::claim`].
+///
+/// # Example
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let deal = Deal::from_hands([
+///     Hand::from_str("AKQJ T98 765 432")?,
+///     Hand::from_str("T98 765 432 AKQJ")?,
+///     Hand::from_str("765 432 AKQJ T98")?,
+///     Hand::from_str("432 AKQJ T98 765")?,
+/// ]);
+/// let mut play = PlayState::new(&deal, Contract::from_str("1SS", Vulnerable::No)?);
+/// assert_eq!(play.next_to_play(), Seat::West);
+/// play.play(Card::HA)?;
+/// play.play(Card::H8)?;
+/// // East must follow suit.
+/// assert!(play.play(Card::CA).is_err());
+/// play.play(Card::H5)?;
+/// assert_eq!(play.play(Card::H2)?, Some(Seat::West));
+/// assert_eq!(play.defender_tricks(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PlayState {
+    contract: Contract,
+    hands: [Cards; NUMBER_OF_HANDS],
+    played: Vec<Card>,
+    // The leader of every trick started, the first one being the opening leader.
+    leaders: Vec<Seat>,
+    claim: Option<u8>,
+}
+
+impl PlayState {
+    /// Starts the play of `contract` on `deal`, with the opening lead to make.
+    #[must_use]
+    pub fn new(deal: &Deal, contract: Contract) -> Self {
+        Self::with_hands(deal.hands().map(|hand| hand.as_cards()), contract)
+    }
+
+    /// Starts the play of `contract` with the players holding `hands`, indexed by [`Seat`].
+    /// Hands may hold fewer than 13 cards, e.g. to play out an ending.
+    #[must_use]
+    pub fn with_hands(hands: [Cards; NUMBER_OF_HANDS], contract: Contract) -> Self {
+        Self {
+            contract,
+            hands,
+            played: Vec::new(),
+            leaders: vec![contract.leader()],
+            claim: None,
+        }
+    }
+
+    /// Plays a whole sequence of cards, as the one recorded in a lin or pbn file.
+    ///
+    /// # Errors
+    /// At the first card that cannot be played.
+    pub fn play_sequence(&mut self, sequence: &PlaySequence) -> Result<(), PlayError> {
+        for &card in sequence {
+            self.play(card)?;
+        }
+        Ok(())
+    }
+
+    /// The cards played so far as a [`PlaySequence`].
+    #[must_use]
+    pub fn to_play_sequence(&self) -> PlaySequence {
+        PlaySequence::new(self.played.clone())
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    /// The cards still held by `seat`.
+    #[must_use]
+    pub fn remaining(&self, seat: Seat) -> Cards {
+        self.hands[seat as usize]
+    }
+
+    /// The cards played so far, in order.
+    #[must_use]
+    pub fn played(&self) -> &[Card] {
+        &self.played
+    }
+
+    /// The cards played so far to the current trick.
+    #[must_use]
+    pub fn current_trick(&self) -> &[Card] {
+        let start = self.played.len() - self.played.len() % 4;
+        &self.played[start..]
+    }
+
+    /// The player who led, or is going to lead, to the current trick.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn trick_leader(&self) -> Seat {
+        *self
+            .leaders
+            .last()
+            .expect("there is always an opening leader")
+    }
+
+    /// The player on turn.
+    #[must_use]
+    pub fn next_to_play(&self) -> Seat {
+        self.trick_leader() + self.current_trick().len()
+    }
+
+    /// The number of tricks completed so far.
+    #[must_use]
+    pub fn completed_tricks(&self) -> usize {
+        self.played.len() / 4
+    }
+
+    /// The cards the player on turn can play: all their cards when leading, otherwise the
+    /// ones in the suit led if they have any.
+    #[must_use]
+    pub fn legal_cards(&self) -> Cards {
+        if self.is_finished() {
+            return Cards::EMPTY;
+        }
+        let hand = self.remaining(self.next_to_play());
+        match self.current_trick().first() {
+            Some(led) if !hand.in_suit(led.suit()).is_empty() => hand.in_suit(led.suit()),
+            _ => hand,
+        }
+    }
+
+    /// Whether the player on turn can play `card`.
+    #[must_use]
+    pub fn is_legal(&self, card: Card) -> bool {
+        self.legal_cards().contains(card)
+    }
+
+    /// Plays `card` for the player on turn, returning the winner of the trick if the card
+    /// completes it.
+    ///
+    /// # Errors
+    /// If the player does not hold the card, if they revoke or if the play is over.
+    pub fn play(&mut self, card: Card) -> Result<Option<Seat>, PlayError> {
+        let seat = self.next_to_play();
+        let error = |kind| Err(PlayError::new(seat, Some(card), kind));
+        if self.is_finished() {
+            return error(PlayErrorKind::Finished);
+        }
+        if !self.remaining(seat).contains(card) {
+            return error(PlayErrorKind::NotHeld);
+        }
+        if !self.is_legal(card) {
+            return error(PlayErrorKind::Revoke);
+        }
+        self.hands[seat as usize] = self.hands[seat as usize].remove(card);
+        self.played.push(card);
+        if self.current_trick().is_empty() {
+            let trick = &self.played[self.played.len() - 4..];
+            let winner = self.trick_leader() + trick_winner(trick, self.contract.strain());
+            self.leaders.push(winner);
+            Ok(Some(winner))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Takes back the last card played, or the claim if one was made. Returns the card taken
+    /// back, `None` if there was none (or a claim was withdrawn).
+    pub fn undo(&mut self) -> Option<Card> {
+        if self.claim.take().is_some() {
+            return None;
+        }
+        let card = self.played.pop()?;
+        if self.played.len() % 4 == 3 {
+            self.leaders.pop();
+        }
+        let seat = self.next_to_play();
+        self.hands[seat as usize] = self.hands[seat as usize].insert(card);
+        Some(card)
+    }
+
+    /// The declarer claims `tricks` of the remaining ones, conceding the others.
+    ///
+    /// # Errors
+    /// If the play is over, a trick is in progress or there are fewer than `tricks` tricks
+    /// left to play.
+    pub fn claim(&mut self, tricks: u8) -> Result<(), PlayError> {
+        let error = |kind| Err(PlayError::new(self.contract.declarer(), None, kind));
+        if self.is_finished() {
+            return error(PlayErrorKind::Finished);
+        }
+        if !self.current_trick().is_empty() {
+            return error(PlayErrorKind::ClaimMidTrick);
+        }
+        if tricks > self.tricks_left() {
+            return error(PlayErrorKind::TooManyTricks);
+        }
+        self.claim = Some(tricks);
+        Ok(())
+    }
+
+    /// The tricks claimed by the declarer, if a claim was made.
+    #[must_use]
+    pub fn claimed(&self) -> Option<u8> {
+        self.claim
+    }
+
+    /// Whether every card was played or the remaining tricks were claimed.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.claim.is_some() || self.hands.iter().all(Cards::is_empty)
+    }
+
+    /// The tricks won by the side of `seat`, counting claimed and conceded tricks.
+    #[must_use]
+    pub fn tricks(&self, seat: Seat) -> u8 {
+        #[allow(clippy::cast_possible_truncation)]
+        let won = self.leaders[1..]
+            .iter()
+            .filter(|winner| winner.is_same_line(&seat))
+            .count() as u8;
+        match self.claim {
+            Some(claimed) if seat.is_same_line(&self.contract.declarer()) => won + claimed,
+            Some(claimed) => won + self.tricks_left() - claimed,
+            None => won,
+        }
+    }
+
+    /// The tricks won by the declaring side.
+    #[must_use]
+    pub fn declarer_tricks(&self) -> u8 {
+        self.tricks(self.contract.declarer())
+    }
+
+    /// The tricks won by the defenders.
+    #[must_use]
+    pub fn defender_tricks(&self) -> u8 {
+        self.tricks(self.contract.leader())
+    }
+
+    /// The winners of the completed tricks, in order.
+    #[must_use]
+    pub fn trick_winners(&self) -> &[Seat] {
+        &self.leaders[1..]
+    }
+
+    // Tricks not completed yet, the current one included.
+    fn tricks_left(&self) -> u8 {
+        self.hands.iter().map(Cards::len).max().unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "dds")]
+impl dds::deal::AsDDSDeal for PlayState {
+    fn to_dds_deal(&self) -> dds::deal::DDSDealRepr {
+        dds_cards(self.hands).into()
+    }
+
+    fn current_trick(&self) -> [Option<(dds::deal::DdsRank, dds::deal::DdsSuit)>; 3] {
+        use dds::traits::AsDDSCard;
+        let mut current_trick = [None; 3];
+        for (played, card) in current_trick.iter_mut().zip(self.current_trick()) {
+            *played = Some(card.as_card());
+        }
+        current_trick
+    }
+}
+
+/// DDS solves for the player on lead to the current trick.
+#[cfg(feature = "dds")]
+impl dds::traits::AsDDSContract for PlayState {
+    fn as_dds_contract(&self) -> (dds::deal::DdsSuit, dds::deal::DdsHandEncoding) {
+        (self.contract.strain().into(), self.trick_leader().into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn deal() -> Deal {
+        Deal::from_hands([
+            Hand::from_str("AKQJ T98 765 432").unwrap(),
+            Hand::from_str("T98 765 432 AKQJ").unwrap(),
+            Hand::from_str("765 432 AKQJ T98").unwrap(),
+            Hand::from_str("432 AKQJ T98 765").unwrap(),
+        ])
+    }
+
+    #[test]
+    fn trick_winner_test() {
+        let trick = [Card::H2, Card::HA, Card::S2, Card::C3];
+        assert_eq!(trick_winner(&trick, Strain::NoTrumps), 1);
+        assert_eq!(trick_winner(&trick, Strain::Spades), 2);
+        assert_eq!(trick_winner(&trick, Strain::Diamonds), 1);
+        assert_eq!(trick_winner(&trick, Strain::Clubs), 3);
+    }
+
+    #[test]
+    fn play_and_undo_test() {
+        let contract = Contract::from_str("3NS", Vulnerable::No).unwrap();
+        let mut play = PlayState::new(&deal(), contract);
+        assert_eq!(play.next_to_play(), Seat::West);
+        assert_eq!(play.legal_cards().len(), 13);
+        let error = play.play(Card::SA).unwrap_err();
+        assert_eq!(error.kind(), PlayErrorKind::NotHeld);
+
+        for card in [Card::HA, Card::H8] {
+            assert_eq!(play.play(card), Ok(None));
+        }
+        assert_eq!(play.legal_cards(), Cards::from_str("H765").unwrap());
+        let error = play.play(Card::CA).unwrap_err();
+        assert_eq!(error.kind(), PlayErrorKind::Revoke);
+        assert_eq!(error.seat(), Seat::East);
+        assert_eq!(play.play(Card::H5), Ok(None));
+        assert_eq!(play.play(Card::H2), Ok(Some(Seat::West)));
+        assert_eq!((play.declarer_tricks(), play.defender_tricks()), (0, 1));
+        assert_eq!(play.next_to_play(), Seat::West);
+
+        assert_eq!(play.undo(), Some(Card::H2));
+        assert_eq!(play.defender_tricks(), 0);
+        assert_eq!(play.next_to_play(), Seat::South);
+        assert!(play.remaining(Seat::South).contains(Card::H2));
+        assert_eq!(play.play(Card::H2), Ok(Some(Seat::West)));
+
+        // West switches to diamonds, won by South's ace.
+        for card in [Card::D8, Card::D5, Card::D2, Card::DA] {
+            play.play(card).unwrap();
+        }
+        assert_eq!(play.trick_winners(), [Seat::West, Seat::South]);
+        assert_eq!(play.next_to_play(), Seat::South);
+        assert_eq!(play.completed_tricks(), 2);
+    }
+
+    #[test]
+    fn claim_test() {
+        let contract = Contract::from_str("4SN", Vulnerable::No).unwrap();
+        let mut play = PlayState::new(&deal(), contract);
+        for card in [Card::D2, Card::DA, Card::D8] {
+            play.play(card).unwrap();
+        }
+        let error = play.claim(10).unwrap_err();
+        assert_eq!(error.kind(), PlayErrorKind::ClaimMidTrick);
+        assert_eq!(play.play(Card::D5), Ok(Some(Seat::South)));
+        assert_eq!(
+            play.claim(13).unwrap_err().kind(),
+            PlayErrorKind::TooManyTricks
+        );
+        play.claim(10).unwrap();
+        assert!(play.is_finished());
+        assert_eq!((play.declarer_tricks(), play.defender_tricks()), (11, 2));
+        assert_eq!(
+            play.play(Card::SK).unwrap_err().kind(),
+            PlayErrorKind::Finished
+        );
+        assert_eq!(play.undo(), None);
+        assert_eq!(play.declarer_tricks(), 1);
+        assert!(!play.is_finished());
+    }
+}
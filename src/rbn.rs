@@ -0,0 +1,921 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Reading and writing of RBN and RBX files, the formats of Richard Pavlicek's archives.
+//!
+//! An RBN file is a sequence of records separated by empty lines. Every line of a record starts
+//! with a tag letter and a space, followed by the data:
+//! - `E` event, `L` location, `D` date, `S` session, `N` names (`north:east:south:west`);
+//! - `B` the board number;
+//! - `H` the hands, starting from the seat before the colon: `H W:AK5.J942.K84.Q73:...`.
+//!   The last hand may be omitted;
+//! - `A` the auction: dealer, vulnerability (`Z`, `N`, `E` or `B`), a colon and the calls
+//!   (`P`, `X`, `R`, `1C`...`7N`, `A` for all pass). A call may be followed by `!` if alerted
+//!   and by `^n`, a reference to the note `n`. Colons between rounds are ignored;
+//! - `P` the play, trick by trick separated by colons, the cards in the order they were played;
+//! - `R` the result: contract, doubles and declarer, a colon and declarer's tricks (`4HXE:9`),
+//!   or `P` for a passed out deal;
+//! - `F` a note, as number and text (`F 1 strong`).
+//!
+//! RBX is the same, with a record on every line and every tag enclosed in braces:
+//! `{B1}{HW:...}{AWZ:1DP1SP:2SA}`. Lines starting with `%` are comments.
+
+use crate::prelude::*;
+use std::num::NonZeroU8;
+
+/// Error kind that models possible errors that could occur while parsing an RBN file.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ParseRbnErrorKind {
+    /// A malformed line, with its number (starting from 1).
+    Syntax(usize),
+    /// A line whose data is not valid, with its tag.
+    Tag(char),
+    Deal,
+    Auction(BuildBiddingError),
+    Play,
+}
+
+impl std::fmt::Display for ParseRbnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(line) => write!(f, "syntax error at line {line}"),
+            Self::Tag(tag) => write!(f, "invalid data for tag {tag}"),
+            Self::Deal => write!(f, "invalid hands"),
+            Self::Auction(e) => write!(f, "invalid auction: {e}"),
+            Self::Play => write!(f, "invalid play"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseRbnError {
+    rbn: String,
+    kind: ParseRbnErrorKind,
+}
+
+impl ParseRbnError {
+    fn new<T: ToString + ?Sized>(kind: ParseRbnErrorKind, rbn: &T) -> Self {
+        Self {
+            rbn: rbn.to_string(),
+            kind,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &ParseRbnErrorKind {
+        &self.kind
+    }
+}
+
+impl std::fmt::Display for ParseRbnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unable to parse RBN, {}: {}", self.kind, self.rbn)
+    }
+}
+
+impl std::error::Error for ParseRbnError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.kind {
+            ParseRbnErrorKind::Auction(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A line of an RBN record: its tag letter and its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RbnLine {
+    tag: char,
+    value: String,
+}
+
+impl RbnLine {
+    #[must_use]
+    pub fn new(tag: char, value: impl Into<String>) -> Self {
+        Self {
+            tag,
+            value: value.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn tag(&self) -> char {
+        self.tag
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::fmt::Display for RbnLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.tag, self.value)
+    }
+}
+
+/// A record (a board) of an RBN file.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let rbn = RbnFile::from_str(
+///     "B 1\n\
+///      H N:AKQJ.AKQ.AKQ.AKQ:T987.JT9.JT9.JT9:6543.876.876.876:\n\
+///      A NZ:7NA\n\
+///      R 7NN:13",
+/// )?;
+/// let record = &rbn.records()[0];
+/// assert_eq!(record.board()?, Some(1));
+/// assert_eq!(record.contract()?.unwrap().level(), 7);
+/// let deal = record.deal()?.unwrap();
+/// assert_eq!(deal.north().hcp(), 37);
+/// assert_eq!(deal.auction().unwrap().len(), 4);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RbnRecord {
+    lines: Vec<RbnLine>,
+}
+
+impl RbnRecord {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every line of the record, in order.
+    #[must_use]
+    pub fn lines(&self) -> &[RbnLine] {
+        &self.lines
+    }
+
+    /// The data of the first line with tag `tag`, if present.
+    #[must_use]
+    pub fn value(&self, tag: char) -> Option<&str> {
+        self.lines
+            .iter()
+            .find(|line| line.tag == tag)
+            .map(RbnLine::value)
+    }
+
+    /// Sets the data of the line with tag `tag`, adding the line if not present.
+    pub fn set_value(&mut self, tag: char, value: impl Into<String>) {
+        let value = value.into();
+        if let Some(line) = self.lines.iter_mut().find(|line| line.tag == tag) {
+            line.value = value;
+        } else {
+            self.lines.push(RbnLine { tag, value });
+        }
+    }
+
+    #[must_use]
+    pub fn event(&self) -> Option<&str> {
+        self.value('E')
+    }
+
+    #[must_use]
+    pub fn location(&self) -> Option<&str> {
+        self.value('L')
+    }
+
+    #[must_use]
+    pub fn date(&self) -> Option<&str> {
+        self.value('D')
+    }
+
+    /// The players, in the order North, East, South, West.
+    #[must_use]
+    pub fn names(&self) -> [Option<&str>; NUMBER_OF_HANDS] {
+        let mut names = self.value('N').unwrap_or_default().split(':');
+        [(); NUMBER_OF_HANDS].map(|()| names.next().filter(|name| !name.is_empty()))
+    }
+
+    /// # Errors
+    /// If the `B` line is not a number.
+    pub fn board(&self) -> Result<Option<u32>, ParseRbnError> {
+        self.parse_value('B', |value| value.parse().ok())
+    }
+
+    /// Dealer and vulnerability, from the `A` line.
+    ///
+    /// # Errors
+    /// If the `A` line does not start with a valid dealer and vulnerability.
+    pub fn dealer_and_vulnerability(&self) -> Result<Option<(Seat, Vulnerability)>, ParseRbnError> {
+        self.parse_value('A', |value| {
+            let mut chars = value.chars();
+            let dealer = Seat::try_from(chars.next()?.to_ascii_uppercase()).ok()?;
+            let vulnerability = match chars.next()?.to_ascii_uppercase() {
+                'Z' | '-' => Vulnerability::None,
+                'N' => Vulnerability::NS,
+                'E' => Vulnerability::EW,
+                'B' => Vulnerability::All,
+                _ => return None,
+            };
+            Some((dealer, vulnerability))
+        })
+    }
+
+    /// The hands of the `H` line, in the order North, East, South, West.
+    /// If a hand is missing, it's completed with the remaining cards.
+    ///
+    /// # Errors
+    /// If the `H` line is malformed.
+    pub fn hands(&self) -> Result<Option<[Hand; NUMBER_OF_HANDS]>, ParseRbnError> {
+        let Some(value) = self.value('H') else {
+            return Ok(None);
+        };
+        let error = || ParseRbnError::new(ParseRbnErrorKind::Deal, value);
+        let (first, hands) = value.split_once(':').ok_or_else(error)?;
+        let first = parse_seat(first.trim()).ok_or_else(error)?;
+        let mut result = [Cards::EMPTY; NUMBER_OF_HANDS];
+        let hands: Vec<&str> = hands.split(':').collect();
+        if hands.len() > NUMBER_OF_HANDS {
+            return Err(error());
+        }
+        let mut deck = Cards::ALL;
+        for (seat, hand) in (0..NUMBER_OF_HANDS).map(|offset| first + offset).zip(hands) {
+            if hand.is_empty() {
+                continue;
+            }
+            if hand.split('.').count() != SUITS {
+                return Err(error());
+            }
+            let cards = Cards::from_str(hand).map_err(|_| error())?;
+            if cards.len() != 13 || !(cards - deck).is_empty() {
+                return Err(error());
+            }
+            deck -= cards;
+            result[seat as usize] = cards;
+        }
+        if deck.len() > 13 {
+            return Err(error());
+        }
+        if let Some(missing) = result.iter_mut().find(|cards| cards.is_empty()) {
+            *missing = deck;
+        }
+        Ok(Some(result.map(|cards| Hand { cards })))
+    }
+
+    /// The [`Deal`] of the record, with its board number, dealer, vulnerability, contract and
+    /// auction. Dealer and vulnerability, without an auction, are derived from the board number.
+    ///
+    /// # Errors
+    /// If one of the lines is not valid.
+    pub fn deal(&self) -> Result<Option<Deal>, ParseRbnError> {
+        let Some(hands) = self.hands()? else {
+            return Ok(None);
+        };
+        let mut deal = Deal::from_hands(hands).with_number(self.board()?.unwrap_or(1));
+        if let Some((dealer, vulnerability)) = self.dealer_and_vulnerability()? {
+            deal.set_dealer(dealer);
+            deal.set_vuln(vulnerability);
+        }
+        deal.set_contract(self.contract()?);
+        deal.set_auction(self.auction()?);
+        Ok(Some(deal))
+    }
+
+    /// The auction, starting from the dealer. Alerts are explained by the notes they refer to.
+    ///
+    /// # Errors
+    /// If the `A` line contains invalid calls.
+    pub fn auction(&self) -> Result<Option<Bidding>, ParseRbnError> {
+        let Some(value) = self.value('A') else {
+            return Ok(None);
+        };
+        let calls = value.split_once(':').map_or("", |(_, calls)| calls);
+        let error = |e| ParseRbnError::new(ParseRbnErrorKind::Auction(e), value);
+        let notes = self.notes();
+        let mut bidding = Bidding::new();
+        let mut chars = calls.chars().peekable();
+        while let Some(c) = chars.next() {
+            let bid = match c.to_ascii_uppercase() {
+                ':' | ' ' => continue,
+                'P' => Bid::Pass,
+                'X' => Bid::Double,
+                'R' => Bid::Redouble,
+                'A' => {
+                    let passes = bidding
+                        .iter()
+                        .rev()
+                        .take_while(|bid| **bid == Bid::Pass)
+                        .count();
+                    let missing = if bidding.iter().all(|bid| *bid == Bid::Pass) {
+                        4 - passes.min(4)
+                    } else {
+                        3 - passes.min(3)
+                    };
+                    for _ in 0..missing {
+                        bidding.push(Bid::Pass).map_err(error)?;
+                    }
+                    continue;
+                }
+                '!' => {
+                    if let Some(last) = bidding.len().checked_sub(1) {
+                        if bidding.alert(last).is_none() {
+                            bidding.set_alert(last, "");
+                        }
+                    }
+                    continue;
+                }
+                '^' => {
+                    let number = take_number(&mut chars);
+                    let text = notes.iter().find(|(n, _)| Some(*n) == number);
+                    if let (Some(last), Some((_, text))) = (bidding.len().checked_sub(1), text) {
+                        bidding.set_alert(last, *text);
+                    }
+                    continue;
+                }
+                level @ '1'..='7' => {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let level = level
+                        .to_digit(10)
+                        .and_then(|level| NonZeroU8::new(level as u8));
+                    let strain = chars.next().and_then(parse_strain);
+                    let (Some(level), Some(strain)) = (level, strain) else {
+                        return Err(error(BuildBiddingError::unparsable(&c.to_string())));
+                    };
+                    Bid::Contract(level, strain)
+                }
+                _ => return Err(error(BuildBiddingError::unparsable(&c.to_string()))),
+            };
+            bidding.push(bid).map_err(error)?;
+        }
+        Ok(Some(bidding))
+    }
+
+    /// The cards played, in the order they were played.
+    ///
+    /// # Errors
+    /// If the `P` line contains invalid cards.
+    pub fn play(&self) -> Result<Option<PlaySequence>, ParseRbnError> {
+        let Some(value) = self.value('P') else {
+            return Ok(None);
+        };
+        let error = || ParseRbnError::new(ParseRbnErrorKind::Play, value);
+        let mut sequence = Vec::new();
+        let mut chars = value.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                ':' | ' ' | '!' => {}
+                '^' => {
+                    take_number(&mut chars);
+                }
+                suit => {
+                    let rank = chars.next().ok_or_else(error)?;
+                    let card = Card::from_str(&format!("{suit}{rank}")).map_err(|_| error())?;
+                    if !"SHDCshdc".contains(suit) || sequence.contains(&card) {
+                        return Err(error());
+                    }
+                    sequence.push(card);
+                }
+            }
+        }
+        Ok(Some(PlaySequence::new(sequence)))
+    }
+
+    /// The contract of the `R` line. Returns `None` if there is no result or the deal was
+    /// passed out. The vulnerability of the declarer is taken from the `A` line or, if missing,
+    /// from the board number.
+    ///
+    /// # Errors
+    /// If the `R` line is not valid.
+    pub fn contract(&self) -> Result<Option<Contract>, ParseRbnError> {
+        let Some(value) = self.value('R') else {
+            return Ok(None);
+        };
+        let contract = value.split(':').next().unwrap_or_default().trim();
+        if contract.is_empty() || contract.eq_ignore_ascii_case("P") {
+            return Ok(None);
+        }
+        let (level, strain, doubled, declarer) = parse_result_contract(contract)
+            .ok_or_else(|| ParseRbnError::new(ParseRbnErrorKind::Tag('R'), value))?;
+        let vulnerability = match self.dealer_and_vulnerability()? {
+            Some((_, vulnerability)) => vulnerability,
+            None => Vulnerability::for_board(self.board()?.unwrap_or(1)),
+        };
+        Ok(Some(Contract::new(
+            level,
+            strain,
+            declarer,
+            vulnerability.is_vulnerable(declarer),
+            doubled,
+        )))
+    }
+
+    /// Number of tricks taken by declarer, from the `R` line.
+    ///
+    /// # Errors
+    /// If the tricks of the `R` line are not a number of tricks.
+    pub fn tricks(&self) -> Result<Option<u8>, ParseRbnError> {
+        let Some(value) = self.value('R') else {
+            return Ok(None);
+        };
+        value
+            .split_once(':')
+            .map(|(_, tricks)| {
+                tricks
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|&tricks: &u8| tricks <= 13)
+                    .ok_or_else(|| ParseRbnError::new(ParseRbnErrorKind::Tag('R'), value))
+            })
+            .transpose()
+    }
+
+    /// The notes of the record, as number and text.
+    #[must_use]
+    pub fn notes(&self) -> Vec<(u32, &str)> {
+        self.lines
+            .iter()
+            .filter(|line| line.tag == 'F')
+            .filter_map(|line| {
+                let (number, text) = line.value.split_once(' ').unwrap_or((&line.value, ""));
+                Some((number.parse().ok()?, text))
+            })
+            .collect()
+    }
+
+    pub fn set_board(&mut self, board: u32) {
+        self.set_value('B', board.to_string());
+    }
+
+    /// Sets the `H` line, starting from North.
+    pub fn set_hands(&mut self, hands: &[Hand; NUMBER_OF_HANDS]) {
+        self.set_value(
+            'H',
+            format!("N:{}", hands.iter().copied().map(hand_code).format(":")),
+        );
+    }
+
+    /// Sets the `A` line. Alerts with an explanation are written as notes, replacing the
+    /// existing ones.
+    pub fn set_auction(&mut self, dealer: Seat, vulnerability: Vulnerability, bidding: &Bidding) {
+        self.lines.retain(|line| line.tag != 'F');
+        let mut note = 0;
+        let mut notes = Vec::new();
+        let mut calls = String::new();
+        for (index, (bid, alert)) in bidding.iter_with_alerts().enumerate() {
+            if index != 0 && index % NUMBER_OF_HANDS == 0 {
+                calls.push(':');
+            }
+            calls.push_str(&call_str(*bid));
+            match alert {
+                Some("") => calls.push('!'),
+                Some(text) => {
+                    note += 1;
+                    calls.push_str("!^");
+                    calls.push_str(&note.to_string());
+                    notes.push(RbnLine::new('F', format!("{note} {text}")));
+                }
+                None => {}
+            }
+        }
+        let vulnerability = match vulnerability {
+            Vulnerability::None => 'Z',
+            Vulnerability::NS => 'N',
+            Vulnerability::EW => 'E',
+            Vulnerability::All => 'B',
+        };
+        self.set_value('A', format!("{dealer}{vulnerability}:{calls}"));
+        self.lines.extend(notes);
+    }
+
+    /// Sets the `P` line, from the cards played in order.
+    pub fn set_play(&mut self, play: &PlaySequence) {
+        self.set_value(
+            'P',
+            play.iter()
+                .copied()
+                .map(card_code)
+                .chunks(NUMBER_OF_HANDS)
+                .into_iter()
+                .map(|mut trick| trick.join(""))
+                .join(":"),
+        );
+    }
+
+    /// Sets the `R` line. Use `None` for a passed out deal.
+    pub fn set_result(&mut self, contract: Option<&Contract>, tricks: Option<u8>) {
+        let contract = contract.map_or_else(
+            || String::from("P"),
+            |contract| {
+                let doubled = match contract.doubled() {
+                    Doubled::NotDoubled => "",
+                    Doubled::Doubled => "X",
+                    Doubled::Redoubled => "R",
+                };
+                format!(
+                    "{}{}{doubled}{}",
+                    contract.level(),
+                    strain_code(contract.strain()),
+                    contract.declarer()
+                )
+            },
+        );
+        self.set_value(
+            'R',
+            tricks.map_or(contract.clone(), |tricks| format!("{contract}:{tricks}")),
+        );
+    }
+
+    /// Adds an `F` line with the given number.
+    pub fn add_note(&mut self, number: u32, text: &str) {
+        self.lines
+            .push(RbnLine::new('F', format!("{number} {text}")));
+    }
+
+    /// The record in RBX format, on a single line.
+    #[must_use]
+    pub fn to_rbx(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| format!("{{{}{}}}", line.tag, line.value))
+            .join("")
+    }
+
+    fn parse_value<T, F: Fn(&str) -> Option<T>>(
+        &self,
+        tag: char,
+        parse: F,
+    ) -> Result<Option<T>, ParseRbnError> {
+        self.value(tag)
+            .map(|value| {
+                parse(value.trim())
+                    .ok_or_else(|| ParseRbnError::new(ParseRbnErrorKind::Tag(tag), value))
+            })
+            .transpose()
+    }
+}
+
+impl From<&Deal> for RbnRecord {
+    /// A record with the board number, the hands and, if known, the auction and the contract
+    /// of `deal`. Without an auction, there is no `A` line, and dealer and vulnerability are
+    /// the ones of the board number.
+    fn from(deal: &Deal) -> Self {
+        let mut record = RbnRecord::new();
+        record.set_board(deal.number());
+        record.set_hands(deal.hands());
+        if let Some(auction) = deal.auction() {
+            record.set_auction(deal.dealer(), deal.vulnerability(), auction);
+        }
+        if deal.contract().is_some() || deal.auction().is_some_and(|auction| !auction.is_empty()) {
+            record.set_result(deal.contract().as_ref(), None);
+        }
+        record
+    }
+}
+
+impl std::fmt::Display for RbnRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An RBN file: the comments found at the beginning and the records.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let deal = Deal::new().with_number(3);
+/// let mut file = RbnFile::new();
+/// file.push(RbnRecord::from(&deal));
+/// let read = RbnFile::from_rbx(&file.to_rbx())?;
+/// assert_eq!(read, RbnFile::from_str(&file.to_string())?);
+/// let read = read.records()[0].deal()?.unwrap();
+/// assert_eq!(read.hands(), deal.hands());
+/// assert_eq!(read.dealer(), Seat::South);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RbnFile {
+    comments: Vec<String>,
+    records: Vec<RbnRecord>,
+}
+
+impl RbnFile {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The comment lines (starting with `%`), without the `%`.
+    #[must_use]
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    #[must_use]
+    pub fn records(&self) -> &[RbnRecord] {
+        &self.records
+    }
+
+    pub fn push(&mut self, record: RbnRecord) {
+        self.records.push(record);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RbnRecord> {
+        self.records.iter()
+    }
+
+    /// Reads a file in RBX format, a record on every line.
+    ///
+    /// # Errors
+    /// If a line is not made of tags enclosed in braces.
+    pub fn from_rbx(s: &str) -> Result<Self, ParseRbnError> {
+        let mut file = RbnFile::default();
+        for (number, line) in (1..).zip(s.lines()) {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('%') {
+                file.comments.push(comment.trim().to_owned());
+                continue;
+            }
+            let error = || ParseRbnError::new(ParseRbnErrorKind::Syntax(number), line);
+            let mut record = RbnRecord::new();
+            let mut rest = line;
+            while !rest.is_empty() {
+                let (tag, tail) = rest
+                    .strip_prefix('{')
+                    .and_then(|tail| tail.split_once('}'))
+                    .ok_or_else(error)?;
+                let mut chars = tag.chars();
+                let tag = chars
+                    .next()
+                    .filter(char::is_ascii_uppercase)
+                    .ok_or_else(error)?;
+                record.lines.push(RbnLine::new(tag, chars.as_str()));
+                rest = tail.trim_start();
+            }
+            if !record.lines.is_empty() {
+                file.records.push(record);
+            }
+        }
+        Ok(file)
+    }
+
+    /// The file in RBX format, a record on every line.
+    #[must_use]
+    pub fn to_rbx(&self) -> String {
+        self.comments
+            .iter()
+            .map(|comment| format!("% {comment}\n"))
+            .chain(self.records.iter().map(|record| record.to_rbx() + "\n"))
+            .collect()
+    }
+}
+
+impl IntoIterator for RbnFile {
+    type Item = RbnRecord;
+    type IntoIter = <Vec<RbnRecord> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RbnFile {
+    type Item = &'a RbnRecord;
+    type IntoIter = std::slice::Iter<'a, RbnRecord>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromStr for RbnFile {
+    type Err = ParseRbnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut file = RbnFile::default();
+        let mut record = RbnRecord::new();
+        for (number, line) in (1..).zip(s.lines()) {
+            let line = line.trim_end();
+            if line.trim_start().is_empty() {
+                if !record.lines.is_empty() {
+                    file.records.push(std::mem::take(&mut record));
+                }
+            } else if let Some(comment) = line.strip_prefix('%') {
+                file.comments.push(comment.trim().to_owned());
+            } else {
+                let mut chars = line.chars();
+                let tag = chars
+                    .next()
+                    .filter(char::is_ascii_uppercase)
+                    .ok_or_else(|| ParseRbnError::new(ParseRbnErrorKind::Syntax(number), line))?;
+                let value = chars.as_str();
+                record
+                    .lines
+                    .push(RbnLine::new(tag, value.strip_prefix(' ').unwrap_or(value)));
+            }
+        }
+        if !record.lines.is_empty() {
+            file.records.push(record);
+        }
+        Ok(file)
+    }
+}
+
+impl std::fmt::Display for RbnFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "% {comment}")?;
+        }
+        for (index, record) in self.records.iter().enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{record}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the digits of a note reference.
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<u32> {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits.parse().ok()
+}
+
+fn parse_strain(c: char) -> Option<Strain> {
+    Strain::try_from(c.to_ascii_uppercase()).ok()
+}
+
+/// Parses the contract of a result like `4S`, `3NXE` or `6HRS`: level, strain, doubles and
+/// declarer.
+fn parse_result_contract(value: &str) -> Option<(u8, Strain, Doubled, Seat)> {
+    let mut chars = value.chars();
+    let level = chars
+        .next()?
+        .to_digit(10)
+        .filter(|level| (1..=7).contains(level))?;
+    let strain = parse_strain(chars.next()?)?;
+    let rest = chars.as_str().to_ascii_uppercase();
+    let (doubled, declarer) = match rest.as_str() {
+        declarer if declarer.len() == 1 => (Doubled::NotDoubled, declarer),
+        rest => match rest.split_at(rest.len().checked_sub(1)?) {
+            ("X", declarer) => (Doubled::Doubled, declarer),
+            ("R" | "XX", declarer) => (Doubled::Redoubled, declarer),
+            _ => return None,
+        },
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    Some((level as u8, strain, doubled, parse_seat(declarer)?))
+}
+
+/// A call of an `A` line: RBN writes the redouble as `R`.
+fn call_str(bid: Bid) -> String {
+    match bid {
+        Bid::Redouble => String::from("R"),
+        bid => bid_code(bid),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RBN: &str = "% Historic hands
+E Bermuda Bowl
+L Estoril
+N Meckstroth:Rodwell:Belladonna:Garozzo
+B 2
+H W:AK5.J942.K84.Q73:T6.AQT873.AJ96.A:J9842.K5.Q752.J8:
+A EN:1H!^1P1SP:2NP3NA
+F 1 five cards
+P DQD6DAD2:HAH5H2H9:
+R 3NE:9
+
+B 3
+H N:AKQJ.AKQ.AKQ.AKQ:T987.JT9.JT9.JT9:6543.876.876.876:2.5432.5432.5432
+A SE:A
+R P
+";
+
+    #[test]
+    fn parses_rbn_test() {
+        let file = RbnFile::from_str(RBN).unwrap();
+        assert_eq!(file.comments(), ["Historic hands"]);
+        assert_eq!(file.records().len(), 2);
+        let record = &file.records()[0];
+        assert_eq!(record.event(), Some("Bermuda Bowl"));
+        assert_eq!(record.location(), Some("Estoril"));
+        assert_eq!(
+            record.names(),
+            [
+                Some("Meckstroth"),
+                Some("Rodwell"),
+                Some("Belladonna"),
+                Some("Garozzo")
+            ]
+        );
+        assert_eq!(record.board().unwrap(), Some(2));
+        assert_eq!(
+            record.dealer_and_vulnerability().unwrap(),
+            Some((Seat::East, Vulnerability::NS))
+        );
+        let hands = record.hands().unwrap().unwrap();
+        assert_eq!(
+            hands[Seat::West as usize].cards,
+            Cards::from_str("AK5.J942.K84.Q73").unwrap()
+        );
+        assert_eq!(hands[Seat::South as usize].cards.len(), 13);
+        let auction = record.auction().unwrap().unwrap();
+        assert_eq!(auction.len(), 10);
+        assert_eq!(auction.alert(0), Some("five cards"));
+        assert_eq!(
+            auction.get(4),
+            Some(&Bid::Contract(NonZeroU8::new(2).unwrap(), Strain::NoTrumps))
+        );
+        let contract = record.contract().unwrap().unwrap();
+        assert_eq!(
+            contract,
+            Contract::new(
+                3,
+                Strain::NoTrumps,
+                Seat::East,
+                Vulnerable::No,
+                Doubled::NotDoubled
+            )
+        );
+        assert_eq!(record.tricks().unwrap(), Some(9));
+        let play = record.play().unwrap().unwrap();
+        assert_eq!(play.len(), 8);
+        assert_eq!(play.iter().next(), Some(&Card::DQ));
+        let deal = record.deal().unwrap().unwrap();
+        assert_eq!((deal.number(), deal.dealer()), (2, Seat::East));
+        assert_eq!(deal.vulnerability(), Vulnerability::NS);
+        assert_eq!(deal.contract(), Some(contract));
+
+        let passed = &file.records()[1];
+        assert_eq!(passed.contract().unwrap(), None);
+        assert_eq!(passed.auction().unwrap().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn writes_rbn_test() {
+        let file = RbnFile::from_str(RBN).unwrap();
+        assert_eq!(RbnFile::from_str(&file.to_string()).unwrap(), file);
+        assert_eq!(RbnFile::from_rbx(&file.to_rbx()).unwrap(), file);
+        assert!(file.to_rbx().contains("{B2}{HW:AK5.J942.K84.Q73:"));
+
+        let original = file.records()[0].deal().unwrap().unwrap();
+        let mut record = RbnRecord::from(&original);
+        record.set_play(&file.records()[0].play().unwrap().unwrap());
+        record.set_result(original.contract().as_ref(), Some(9));
+        assert_eq!(record.value('A'), Some("EN:1H!^1P1SP:2NP3NP:PP"));
+        assert_eq!(record.notes(), [(1, "five cards")]);
+        assert_eq!(record.value('P'), Some("DQD6DAD2:HAH5H2H9"));
+        assert_eq!(record.value('R'), Some("3NE:9"));
+        let deal = record.deal().unwrap().unwrap();
+        assert_eq!(deal.hands(), original.hands());
+        assert_eq!(deal.contract(), original.contract());
+        assert_eq!(deal.auction().unwrap().alert(0), Some("five cards"));
+
+        // Setting the auction again replaces its notes.
+        let mut auction = original.auction().unwrap().clone();
+        auction.set_alert(2, "forcing");
+        record.set_auction(original.dealer(), original.vulnerability(), &auction);
+        assert_eq!(record.notes(), [(1, "five cards"), (2, "forcing")]);
+        assert_eq!(record.value('A'), Some("EN:1H!^1P1S!^2P:2NP3NP:PP"));
+
+        // Without an auction, there is no `A` line.
+        let record = RbnRecord::from(&Deal::new().with_number(3));
+        assert_eq!(record.value('A'), None);
+        let deal = record.deal().unwrap().unwrap();
+        assert_eq!(deal.dealer(), Seat::South);
+        assert!(deal.auction().is_none());
+    }
+
+    #[test]
+    fn rbn_errors_test() {
+        assert!(RbnFile::from_str("b 1").is_err());
+        assert!(RbnFile::from_rbx("{B1}{H").is_err());
+        let record = |rbn: &str| RbnFile::from_str(rbn).unwrap().records()[0].clone();
+        assert!(record("H N:AKQ:::").hands().is_err());
+        let hands = "H N:AKQJ.AKQ.AKQ.AKQ:T987.JT9.JT9.JT9:6543.876.876.876:2.5432.5432.5432";
+        assert!(record(hands).hands().is_ok());
+        assert!(record(&format!("{hands}:")).hands().is_err());
+        assert!(record("A NZ:1SP1H").auction().is_err());
+        assert!(record("A NZ:8S").auction().is_err());
+        assert!(record("A QZ:1S").dealer_and_vulnerability().is_err());
+        assert!(record("P SASA").play().is_err());
+        assert!(record("R 4SXQ:10").contract().is_err());
+        assert!(record("R 4SN:14").tricks().is_err());
+    }
+}
@@ -119,6 +119,16 @@ impl Shape {
         }
     }
 
+    /// Checks if a shape pattern (e.g. `[4, 3, 3, 3]`) is accepted.
+    #[must_use]
+    #[inline]
+    pub fn contains_pattern(&self, pattern: ShapePattern) -> bool {
+        match *self {
+            Self::Custom(ref shape) => shape.contains_pattern(pattern),
+            Self::All => true,
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn len_ranges(&self) -> [LenRange; 4] {
@@ -127,6 +137,62 @@ impl Shape {
             Self::All => [LenRange::default(); 4],
         }
     }
+
+    /// Shapes contained in `self` or in `other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::All, _) | (_, Self::All) => Self::All,
+            (Self::Custom(shapes), Self::Custom(other)) => Self::Custom(shapes.union(other)),
+        }
+    }
+
+    /// Shapes contained both in `self` and in `other`.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::All, shape) | (shape, Self::All) => shape.clone(),
+            (Self::Custom(shapes), Self::Custom(other)) => Self::Custom(shapes.intersection(other)),
+        }
+    }
+
+    /// Shapes contained in `self` but not in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        match (self, other) {
+            (_, Self::All) => Self::new_empty(),
+            (Self::All, Self::Custom(other)) => Self::Custom(other.complement()),
+            (Self::Custom(shapes), Self::Custom(other)) => Self::Custom(shapes.difference(other)),
+        }
+    }
+
+    /// Every possible shape not contained in `self`.
+    #[must_use]
+    pub fn complement(&self) -> Self {
+        match self {
+            Self::All => Self::new_empty(),
+            Self::Custom(shapes) => Self::Custom(shapes.complement()),
+        }
+    }
+
+    /// True if every shape of `self` is also contained in `other`.
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, Self::All) => true,
+            (Self::All, Self::Custom(other)) => other.complement().is_empty(),
+            (Self::Custom(shapes), Self::Custom(other)) => shapes.is_subset(other),
+        }
+    }
+
+    /// True if no shape is accepted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::All => false,
+            Self::Custom(shapes) => shapes.is_empty(),
+        }
+    }
 }
 
 /// Struct that represents multiple shapes.
@@ -192,7 +258,13 @@ impl Shapes {
     /// Checks if a hand is a member of the shape.
     #[must_use]
     fn is_member(&self, hand_to_match: Hand) -> bool {
-        self.shape_table[Self::shape_pattern_to_index(hand_to_match.shape())]
+        self.contains_pattern(hand_to_match.shape())
+    }
+
+    /// Checks if a shape pattern is contained in the table.
+    #[must_use]
+    fn contains_pattern(&self, pattern: ShapePattern) -> bool {
+        self.shape_table[Self::shape_pattern_to_index(pattern)]
     }
 
     /// Removes shapes based on a given string.
@@ -346,8 +418,137 @@ impl Shapes {
         }
         len_range
     }
+
+    /// Table with every shape a bridge hand can have, i.e. every pattern summing up to 13.
+    /// Used to mask the buckets of the table that do not represent a real shape.
+    fn valid_shapes() -> &'static ShapeTable {
+        static VALID_SHAPES: std::sync::LazyLock<ShapeTable> =
+            std::sync::LazyLock::new(|| *Shapes::from(&[LenRange::default(); SUITS]).shape_table);
+        &VALID_SHAPES
+    }
+
+    /// Applies `operation` word by word to the tables of `self` and `other`,
+    /// keeping only the buckets that represent a real shape.
+    fn combine_tables(&self, other: &Self, operation: impl Fn(usize, usize) -> usize) -> Self {
+        let mut shape_table = self.shape_table.clone();
+        for ((word, other_word), valid_word) in shape_table
+            .as_raw_mut_slice()
+            .iter_mut()
+            .zip(other.shape_table.as_raw_slice())
+            .zip(Self::valid_shapes().as_raw_slice())
+        {
+            *word = operation(*word, *other_word) & valid_word;
+        }
+        Self {
+            shape_table,
+            min_ls: self.min_ls,
+            max_ls: self.max_ls,
+        }
+    }
+
+    /// Shapes contained in `self` or in `other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.combine_tables(other, |word, other_word| word | other_word);
+        for suit in 0..SUITS {
+            union.min_ls[suit] = self.min_ls[suit].min(other.min_ls[suit]);
+            union.max_ls[suit] = self.max_ls[suit].max(other.max_ls[suit]);
+        }
+        union
+    }
+
+    /// Shapes contained both in `self` and in `other`.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intersection = self.combine_tables(other, |word, other_word| word & other_word);
+        for suit in 0..SUITS {
+            intersection.min_ls[suit] = self.min_ls[suit].max(other.min_ls[suit]);
+            intersection.max_ls[suit] = self.max_ls[suit]
+                .min(other.max_ls[suit])
+                .max(intersection.min_ls[suit]);
+        }
+        intersection
+    }
+
+    /// Shapes contained in `self` but not in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine_tables(other, |word, other_word| word & !other_word)
+    }
+
+    /// Every possible shape not contained in `self`.
+    #[must_use]
+    pub fn complement(&self) -> Self {
+        let mut complement = self.combine_tables(self, |word, _| !word);
+        complement.min_ls = [ZERO_LENGTH; SUITS];
+        complement.max_ls = [MAX_LENGTH; SUITS];
+        complement
+    }
+
+    /// True if every shape of `self` is also contained in `other`.
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// True if the table contains no shape.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.shape_table
+            .as_raw_slice()
+            .iter()
+            .zip(Self::valid_shapes().as_raw_slice())
+            .all(|(word, valid_word)| word & valid_word == 0)
+    }
 }
 
+/// Implements the set operators for both owned and borrowed operands,
+/// delegating to the named method.
+macro_rules! impl_set_operators {
+    ($type:ty, $($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $set_method:ident);*) => {
+        $(
+        impl std::ops::$trait for $type {
+            type Output = $type;
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.$set_method(&rhs)
+            }
+        }
+        impl std::ops::$trait for &$type {
+            type Output = $type;
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.$set_method(rhs)
+            }
+        }
+        impl std::ops::$assign_trait for $type {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = self.$set_method(&rhs);
+            }
+        }
+        )*
+        impl std::ops::Not for $type {
+            type Output = $type;
+            fn not(self) -> Self::Output {
+                self.complement()
+            }
+        }
+        impl std::ops::Not for &$type {
+            type Output = $type;
+            fn not(self) -> Self::Output {
+                self.complement()
+            }
+        }
+    };
+}
+
+impl_set_operators!(Shapes,
+    BitOr, bitor, BitOrAssign, bitor_assign, union;
+    BitAnd, bitand, BitAndAssign, bitand_assign, intersection;
+    Sub, sub, SubAssign, sub_assign, difference);
+impl_set_operators!(Shape,
+    BitOr, bitor, BitOrAssign, bitor_assign, union;
+    BitAnd, bitand, BitAndAssign, bitand_assign, intersection;
+    Sub, sub, SubAssign, sub_assign, difference);
+
 impl From<&[LenRange; SUITS]> for Shapes {
     fn from(len_range: &[LenRange; SUITS]) -> Self {
         let mut min_ls = [ZERO_LENGTH; SUITS];
@@ -662,6 +863,55 @@ mod test {
         assert!(!factory.is_member(hand));
     }
 
+    #[test]
+    fn set_operations_on_shapes_test() {
+        let mut one_major = Shapes::new();
+        one_major.add_shape("5+xxx").unwrap();
+        one_major.add_shape("x5+xx").unwrap();
+        let mut two_suited = Shapes::new();
+        two_suited.add_shape("(5+5+)xx").unwrap();
+        two_suited.add_shape("(5+x)(5+x)").unwrap();
+
+        let one_suited_majors = &one_major - &two_suited;
+        let five_five = Hand::from_str("AKQ32.KQJ32.2.32").unwrap();
+        let five_four = Hand::from_str("AKQ32.KQJ2.32.32").unwrap();
+        assert!(one_suited_majors.is_member(five_four));
+        assert!(!one_suited_majors.is_member(five_five));
+        assert!(one_suited_majors.is_subset(&one_major));
+        assert!(!one_major.is_subset(&one_suited_majors));
+        assert!((&one_suited_majors & &two_suited).is_empty());
+        assert!((&one_suited_majors | &two_suited).is_subset(&(one_major | two_suited)));
+
+        let balanced = Hand::from_str("AK32.K32.Q32.J32").unwrap();
+        let complement = !&one_suited_majors;
+        assert!(complement.is_member(balanced));
+        assert!(complement.is_member(five_five));
+        assert!(!complement.is_member(five_four));
+        assert!((&complement & &one_suited_majors).is_empty());
+        assert!((!Shapes::new()).is_subset(&(complement | one_suited_majors)));
+    }
+
+    #[test]
+    fn complement_contains_every_shape_test() {
+        let all = !Shapes::new();
+        let count = all.shape_table.iter().filter(|bit| **bit).count();
+        assert_eq!(count, SHAPE_COMBINATIONS);
+        assert!((!all).is_empty());
+    }
+
+    #[test]
+    fn set_operations_on_shape_test() {
+        let balanced = Shape::new_from_patterns(&["(4333)", "(4432)", "(5332)"]).unwrap();
+        assert!((Shape::All - Shape::All).is_empty());
+        assert!(balanced.is_subset(&Shape::All));
+        assert!(!Shape::All.is_subset(&balanced));
+        assert!(Shape::All.is_subset(&(!&balanced | balanced.clone())));
+        let mut unbalanced = Shape::All - balanced.clone();
+        assert!((&unbalanced & &balanced).is_empty());
+        unbalanced |= balanced;
+        assert!(Shape::All.is_subset(&unbalanced));
+    }
+
     #[test]
     fn test_perm() {
         let mut permutations = Vec::new();
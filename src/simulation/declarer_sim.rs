@@ -0,0 +1,141 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use super::{single_dummy::PlayPosition, PlaySimulationResult, Simulation};
+use crate::prelude::*;
+
+/// A single dummy simulation of the play of the declarer: from a point of the play after
+/// the opening lead, it finds the best card for the declarer (or the dummy) on turn.
+///
+/// Seeing their own hand and the dummy, the declarer can only guess the layout of the
+/// defenders' hands: the simulation deals them `num_of_boards` times, consistently with the
+/// cards they played and the suits they showed out of, and solves every deal double dummy
+/// for every legal card. The result reports, for every card, the average tricks taken by
+/// the declarer and the percentage of deals in which the contract makes.
+///
+/// This is the counterpart of [`super::LeadSimulation`] for the declarer, from the second
+/// card of the deal on.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let declarer = Cards::from_str("SAJT98 H43 DAK2 CK65")?;
+/// let dummy = Cards::from_str("SKQ2 HAK2 D8765 C432")?;
+/// // West leads the CQ: what should the dummy play?
+/// let simulation = DeclarerSimulation::new(
+///     100,
+///     Contract::from_str("4SS", Vulnerable::No)?,
+///     declarer,
+///     dummy,
+///     &[Card::CQ],
+/// )?
+/// .with_hand_descriptor(
+///     Seat::West,
+///     HandTypeBuilder::new().with_range(0, 10).build().into(),
+/// );
+/// let results = simulation.run().expect("unable to run simulation");
+/// results.report();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeclarerSimulation {
+    num_of_boards: usize,
+    position: PlayPosition,
+}
+
+impl DeclarerSimulation {
+    /// Sets up the simulation for `contract`, with the declarer and the dummy holding
+    /// `declarer` and `dummy` at the start of the play, and the cards in `played` already
+    /// played, in order, starting from the opening lead.
+    ///
+    /// # Errors
+    /// If the cards played are not consistent with the hands of the declarer and the
+    /// dummy, if the play is over or if it is a defender's turn.
+    pub fn new(
+        num_of_boards: usize,
+        contract: Contract,
+        declarer: Cards,
+        dummy: Cards,
+        played: &[Card],
+    ) -> Result<Self, SqueezerError> {
+        let mut visible = [None; NUMBER_OF_HANDS];
+        visible[contract.declarer() as usize] = Some(declarer);
+        visible[contract.declarer().next().next() as usize] = Some(dummy);
+        let position = PlayPosition::new(contract, visible, played)?;
+        let on_turn = position.next_to_play();
+        if !on_turn.is_same_line(&contract.declarer()) {
+            return Err(SqueezerError::Generic(format!(
+                "{on_turn} is on turn, a defender against {contract}"
+            )));
+        }
+        Ok(Self {
+            num_of_boards,
+            position,
+        })
+    }
+
+    /// Sets the number of boards to run the simulation for.
+    #[must_use]
+    pub fn with_num_of_boards(mut self, num_of_boards: usize) -> Self {
+        self.num_of_boards = num_of_boards;
+        self
+    }
+
+    /// Deals the hand of the defender in `seat` according to `descriptor`, e.g. to account
+    /// for what the auction showed. The descriptor applies to the whole hand held at the
+    /// start of the play, the cards already played included. Descriptors for the declarer
+    /// or the dummy are ignored.
+    #[must_use]
+    pub fn with_hand_descriptor(mut self, seat: Seat, descriptor: HandDescriptor) -> Self {
+        self.position.set_hand_descriptor(seat, descriptor);
+        self
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> Contract {
+        self.position.contract()
+    }
+
+    /// The cards played so far.
+    #[must_use]
+    pub fn played(&self) -> &[Card] {
+        self.position.played()
+    }
+
+    /// The declarer or the dummy, whoever is on turn.
+    #[must_use]
+    pub fn on_turn(&self) -> Seat {
+        self.position.next_to_play()
+    }
+}
+
+impl Simulation<PlaySimulationResult> for DeclarerSimulation {
+    fn run(&self) -> Result<PlaySimulationResult, SqueezerError> {
+        self.position.simulate(self.num_of_boards)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn declarer_simulation_test() {
+        let contract = Contract::from_str("4SS", Vulnerable::No).unwrap();
+        let declarer = Cards::from_str("SAJT98 H43 DAK2 CK65").unwrap();
+        let dummy = Cards::from_str("SKQ2 HAK2 D8765 C432").unwrap();
+        // Nothing to play for before the opening lead.
+        assert!(DeclarerSimulation::new(10, contract, declarer, dummy, &[]).is_err());
+        let simulation =
+            DeclarerSimulation::new(10, contract, declarer, dummy, &[Card::CQ]).unwrap();
+        assert_eq!(simulation.on_turn(), Seat::North);
+        // East is on turn after the dummy.
+        assert!(
+            DeclarerSimulation::new(10, contract, declarer, dummy, &[Card::CQ, Card::C2]).is_err()
+        );
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use super::{single_dummy::PlayPosition, PlaySimulationResult, Simulation};
+use crate::prelude::*;
+
+/// A single dummy simulation of the play of a defender: from any point of the play, it
+/// finds the best card for a defender on lead or following to a trick.
+///
+/// The defender sees their own hand and the dummy: the simulation deals the hands of the
+/// declarer and of their partner `num_of_boards` times, consistently with the cards they
+/// played, the suits they showed out of and what the auction told, and solves every deal
+/// double dummy for every legal card. The result reports, for every card, the average
+/// tricks taken by the defence, the percentage of deals in which the contract is set and
+/// the average score, from which a payoff matrix of the cards can be computed with
+/// [`PlaySimulationResult::payoff`].
+///
+/// Where [`super::LeadSimulation`] only looks at the opening lead, this covers the
+/// switches and the discards of the following tricks.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let west = Cards::from_str("S76 HQJT9 DQJT9 CAQJ")?;
+/// let dummy = Cards::from_str("SKQ2 HAK2 D8765 C432")?;
+/// // West leads the HQ, won in dummy; the declarer loses a club to the ace.
+/// let played = [
+///     Card::HQ, Card::HA, Card::H5, Card::H3,
+///     Card::C2, Card::C7, Card::CK, Card::CA,
+/// ];
+/// let simulation = DefenceSimulation::new(
+///     100,
+///     Contract::from_str("4SS", Vulnerable::No)?,
+///     Seat::West,
+///     west,
+///     dummy,
+///     &played,
+/// )?
+/// .with_auction(&Bidding::from_str("1S P 4S P P P")?, Seat::South);
+/// let results = simulation.run().expect("unable to run simulation");
+/// results.report();
+/// results.payoff(imps).report();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DefenceSimulation {
+    num_of_boards: usize,
+    position: PlayPosition,
+}
+
+impl DefenceSimulation {
+    /// Sets up the simulation for the defender in `seat` against `contract`, with the
+    /// defender and the dummy holding `hand` and `dummy` at the start of the play, and the
+    /// cards in `played` already played, in order, starting from the opening lead.
+    ///
+    /// # Errors
+    /// If `seat` is not a defender, if the cards played are not consistent with the hands
+    /// of the defender and the dummy, if the play is over or if `seat` is not on turn.
+    pub fn new(
+        num_of_boards: usize,
+        contract: Contract,
+        seat: Seat,
+        hand: Cards,
+        dummy: Cards,
+        played: &[Card],
+    ) -> Result<Self, SqueezerError> {
+        if seat.is_same_line(&contract.declarer()) {
+            return Err(SqueezerError::Generic(format!(
+                "{seat} is not defending {contract}"
+            )));
+        }
+        let mut visible = [None; NUMBER_OF_HANDS];
+        visible[seat as usize] = Some(hand);
+        visible[contract.declarer().next().next() as usize] = Some(dummy);
+        let position = PlayPosition::new(contract, visible, played)?;
+        let on_turn = position.next_to_play();
+        if on_turn != seat {
+            return Err(SqueezerError::Generic(format!(
+                "{on_turn} is on turn, not {seat}"
+            )));
+        }
+        Ok(Self {
+            num_of_boards,
+            position,
+        })
+    }
+
+    /// Sets the number of boards to run the simulation for.
+    #[must_use]
+    pub fn with_num_of_boards(mut self, num_of_boards: usize) -> Self {
+        self.num_of_boards = num_of_boards;
+        self
+    }
+
+    /// Deals the hand in `seat` according to `descriptor`. The descriptor applies to the
+    /// whole hand held at the start of the play, the cards already played included.
+    /// Descriptors for the defender or the dummy are ignored.
+    #[must_use]
+    pub fn with_hand_descriptor(mut self, seat: Seat, descriptor: HandDescriptor) -> Self {
+        self.position.set_hand_descriptor(seat, descriptor);
+        self
+    }
+
+    /// Deals the hidden hands according to what `auction`, started by `dealer`, showed,
+    /// read with the natural-bidding heuristics of [`Bidding::natural_inferences`]. Hands
+    /// for which the heuristics give contradictory information are left unconstrained.
+    #[must_use]
+    pub fn with_auction(mut self, auction: &Bidding, dealer: Seat) -> Self {
+        for (seat, inference) in Seat::iter().zip(auction.natural_inferences(dealer)) {
+            if let Some(descriptor) = inference.hand_descriptor() {
+                self.position.set_hand_descriptor(seat, descriptor);
+            }
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> Contract {
+        self.position.contract()
+    }
+
+    /// The cards played so far.
+    #[must_use]
+    pub fn played(&self) -> &[Card] {
+        self.position.played()
+    }
+
+    /// The defender on turn.
+    #[must_use]
+    pub fn on_turn(&self) -> Seat {
+        self.position.next_to_play()
+    }
+}
+
+#[cfg(feature = "lin")]
+impl DefenceSimulation {
+    /// Number of boards used by [`DefenceSimulation::from_lin_deal`].
+    pub const DEFAULT_NUM_OF_BOARDS: usize = 1000;
+
+    /// Builds a defence simulation for a played board, from the point of view of the
+    /// defender in `seat` after the first `cards_played` cards of the play recorded.
+    ///
+    /// The hidden hands are dealt according to the auction, as with
+    /// [`DefenceSimulation::with_auction`]. The simulation runs over
+    /// [`Self::DEFAULT_NUM_OF_BOARDS`] boards; use
+    /// [`DefenceSimulation::with_num_of_boards`] to change it.
+    ///
+    /// # Errors
+    /// If the board was passed out, if fewer than `cards_played` cards were recorded, or
+    /// for the reasons of [`DefenceSimulation::new`].
+    pub fn from_lin_deal(
+        deal: &LinDeal,
+        seat: Seat,
+        cards_played: usize,
+    ) -> Result<Self, SqueezerError> {
+        let contract = deal
+            .contract()
+            .ok_or_else(|| SqueezerError::Generic("the board was passed out".to_owned()))?;
+        let recorded = deal.play_sequence().map_or(0, PlaySequence::len);
+        if recorded < cards_played {
+            return Err(SqueezerError::Generic(format!(
+                "only {recorded} cards of the play were recorded"
+            )));
+        }
+        let played: Vec<Card> = deal
+            .play_sequence()
+            .into_iter()
+            .flatten()
+            .take(cards_played)
+            .copied()
+            .collect();
+        let hands = deal.hands();
+        let dummy = contract.declarer().next().next();
+        let simulation = Self::new(
+            Self::DEFAULT_NUM_OF_BOARDS,
+            contract,
+            seat,
+            hands[seat as usize].as_cards(),
+            hands[dummy as usize].as_cards(),
+            &played,
+        )?;
+        Ok(match deal.bidding() {
+            Some(auction) => simulation.with_auction(auction, deal.dealer()),
+            None => simulation,
+        })
+    }
+}
+
+impl Simulation<PlaySimulationResult> for DefenceSimulation {
+    fn run(&self) -> Result<PlaySimulationResult, SqueezerError> {
+        self.position.simulate(self.num_of_boards)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn defence_simulation_test() {
+        let contract = Contract::from_str("4SS", Vulnerable::No).unwrap();
+        let west = Cards::from_str("S76 HQJT9 DQJT9 CAQJ").unwrap();
+        let dummy = Cards::from_str("SKQ2 HAK2 D8765 C432").unwrap();
+        let played = [Card::HQ, Card::HA, Card::H5, Card::H3];
+        let new =
+            |seat, played: &[Card]| DefenceSimulation::new(10, contract, seat, west, dummy, played);
+        // The dummy is on lead to the second trick.
+        assert!(new(Seat::West, &played).is_err());
+        // The declarer does not defend.
+        assert!(new(Seat::South, &played[..3]).is_err());
+        let simulation = new(Seat::West, &[]).unwrap();
+        assert_eq!(simulation.on_turn(), Seat::West);
+        // West plays last to the club trick led by the dummy.
+        let trick = [Card::C2, Card::C7, Card::CK];
+        let simulation = new(Seat::West, &[&played[..], &trick].concat()).unwrap();
+        assert_eq!(simulation.on_turn(), Seat::West);
+        assert!(simulation.played().ends_with(&trick));
+    }
+
+    #[test]
+    #[cfg(feature = "lin")]
+    fn from_lin_deal_test() {
+        // East declares 4S, South leads the HK.
+        let lin = "pn|simodra,fra97,matmont,thevava|st||md|3S34JH258TQKD2JQC7,S27TH69D679TKAC23,S6QH47JD458C468JA,|rh||ah|Board 1|sv|o|mb|p|mb|1S|mb|2H|mb|2S|mb|3H|mb|4S|mb|p|mb|p|mb|p|pc|HK|pc|H6|pc|H4|pc|HA|pc|D3|pg||";
+        let board = LinDeal::from_str(lin).unwrap();
+        assert!(DefenceSimulation::from_lin_deal(&board, Seat::South, 6).is_err());
+        assert!(DefenceSimulation::from_lin_deal(&board, Seat::South, 4).is_err());
+        assert!(DefenceSimulation::from_lin_deal(&board, Seat::North, 5).is_err());
+        let simulation = DefenceSimulation::from_lin_deal(&board, Seat::South, 5)
+            .unwrap()
+            .with_num_of_boards(10);
+        assert_eq!(simulation.num_of_boards, 10);
+        assert_eq!(simulation.on_turn(), Seat::South);
+
+        let dealer = simulation.position.dealer().unwrap();
+        for _ in 0..10 {
+            let deal = dealer.deal().unwrap();
+            assert_eq!(deal.south(), board.hands()[Seat::South as usize]);
+            assert_eq!(deal.west(), board.hands()[Seat::West as usize]);
+            // East won the HA, led the D3 and opened 1S.
+            let east = deal.east().as_cards();
+            assert!(east.contains(Card::HA) && east.contains(Card::D3));
+            assert!(deal.east().slen() >= 5 && (11..=21).contains(&deal.east().hcp()));
+            assert!(deal.north().hlen() >= 3 && deal.north().hcp() <= 11);
+        }
+    }
+}
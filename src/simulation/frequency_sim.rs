@@ -0,0 +1,499 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use super::{Simulation, SimulationResult};
+use crate::prelude::{Deal, Dealer, SqueezerError};
+use colored::Colorize;
+use itertools::Itertools;
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Type of the functions measuring a quantity on a [`Deal`], e.g. the HCP of a hand.
+pub type MeasureFunction = Box<dyn Fn(&Deal) -> i32>;
+/// Type of the functions checking if an event happened in a [`Deal`].
+pub type PredicateFunction = Box<dyn Fn(&Deal) -> bool>;
+
+/// The struct you will fire up when you want to collect statistics over the deals
+/// produced by a [`Dealer`], without double dummy analysis.
+/// You provide the number of deals, the dealer and then a series of named measures
+/// (quantities computed on every deal, like the HCP of partner), predicates (events, like
+/// "partner has 3+ spades") and conditional events (an event and the condition it depends on).
+///
+/// Running the simulation will give you a [`FrequencySimulationResult`] with histograms,
+/// means and percentiles of the measures, the probability of the events and the
+/// conditional probabilities.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let dealer = DealerBuilder::new()
+///     .predeal(Seat::South, Cards::from_str("KQ2 AJ43 K32 Q54")?)?
+///     .with_hand_descriptor(Seat::North, HandDescriptor::new(vec![HandTypeBuilder::balanced(15, 17).build()]))
+///     .build()?;
+/// let simulation = FrequencySimulation::new(1000, dealer)
+///     .with_measure("North HCP", |deal: &Deal| i32::from(deal.north().hcp()))
+///     .with_predicate("North 3+ spades", |deal: &Deal| deal.north().slen() >= 3)
+///     .with_conditional(
+///         "North 4 hearts | North 3- spades",
+///         |deal: &Deal| deal.north().hlen() == 4,
+///         |deal: &Deal| deal.north().slen() <= 3,
+///     );
+/// let result = simulation.run()?;
+/// result.report();
+/// # Ok(())
+/// # }
+/// ```
+pub struct FrequencySimulation<D: Dealer> {
+    num_of_boards: usize,
+    dealer: D,
+    measures: Vec<(String, MeasureFunction)>,
+    predicates: Vec<(String, PredicateFunction)>,
+    conditionals: Vec<(String, PredicateFunction, PredicateFunction)>,
+}
+
+impl<D: Dealer> FrequencySimulation<D> {
+    /// Creates a simulation on `num_of_boards` deals from `dealer`, with nothing to measure
+    /// yet.
+    #[must_use]
+    pub fn new(num_of_boards: usize, dealer: D) -> Self {
+        Self {
+            num_of_boards,
+            dealer,
+            measures: Vec::new(),
+            predicates: Vec::new(),
+            conditionals: Vec::new(),
+        }
+    }
+
+    /// Adds a quantity to measure on every deal.
+    #[must_use]
+    pub fn with_measure<T: Fn(&Deal) -> i32 + 'static>(
+        mut self,
+        name: impl Into<String>,
+        measure: T,
+    ) -> Self {
+        self.measures.push((name.into(), Box::new(measure)));
+        self
+    }
+
+    /// Adds an event whose probability will be computed.
+    #[must_use]
+    pub fn with_predicate<T: Fn(&Deal) -> bool + 'static>(
+        mut self,
+        name: impl Into<String>,
+        predicate: T,
+    ) -> Self {
+        self.predicates.push((name.into(), Box::new(predicate)));
+        self
+    }
+
+    /// Adds an event whose probability will be computed only on the deals
+    /// that satisfy `condition`.
+    #[must_use]
+    pub fn with_conditional<T, C>(mut self, name: impl Into<String>, event: T, condition: C) -> Self
+    where
+        T: Fn(&Deal) -> bool + 'static,
+        C: Fn(&Deal) -> bool + 'static,
+    {
+        self.conditionals
+            .push((name.into(), Box::new(event), Box::new(condition)));
+        self
+    }
+}
+
+impl<D: Dealer> Simulation<FrequencySimulationResult> for FrequencySimulation<D> {
+    fn run(&self) -> Result<FrequencySimulationResult, SqueezerError> {
+        let mut measures: Vec<MeasureStatistics> = self
+            .measures
+            .iter()
+            .map(|(name, _)| MeasureStatistics::new(name, self.num_of_boards))
+            .collect();
+        let mut predicates: Vec<EventStatistics> = self
+            .predicates
+            .iter()
+            .map(|(name, _)| EventStatistics::new(name))
+            .collect();
+        let mut conditionals: Vec<EventStatistics> = self
+            .conditionals
+            .iter()
+            .map(|(name, _, _)| EventStatistics::new(name))
+            .collect();
+
+        for _ in 0..self.num_of_boards {
+            let deal = self.dealer.deal()?;
+            for ((_, measure), statistics) in self.measures.iter().zip(measures.iter_mut()) {
+                statistics.values.push(measure(&deal));
+            }
+            for ((_, predicate), statistics) in self.predicates.iter().zip(predicates.iter_mut()) {
+                statistics.add(true, predicate(&deal));
+            }
+            for ((_, event, condition), statistics) in
+                self.conditionals.iter().zip(conditionals.iter_mut())
+            {
+                let given = condition(&deal);
+                statistics.add(given, given && event(&deal));
+            }
+        }
+        for statistics in &mut measures {
+            statistics.values.sort_unstable();
+        }
+        Ok(FrequencySimulationResult {
+            deals_run: self.num_of_boards,
+            measures,
+            predicates,
+            conditionals,
+        })
+    }
+}
+
+/// Statistics collected for a measure: every value is stored, so we are able
+/// to compute histograms and percentiles.
+#[derive(Debug, Clone)]
+pub struct MeasureStatistics {
+    name: String,
+    values: Vec<i32>,
+}
+
+impl MeasureStatistics {
+    fn new(name: &str, capacity: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The values measured, sorted.
+    #[must_use]
+    pub fn values(&self) -> &[i32] {
+        &self.values
+    }
+
+    /// Mean of the values measured, `None` if no deal was run.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean(&self) -> Option<f64> {
+        if self.values.is_empty() {
+            None
+        } else {
+            Some(
+                self.values
+                    .iter()
+                    .map(|&value| f64::from(value))
+                    .sum::<f64>()
+                    / self.values.len() as f64,
+            )
+        }
+    }
+
+    /// Standard deviation of the values measured, `None` if no deal was run.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance = self
+            .values
+            .iter()
+            .map(|&value| (f64::from(value) - mean).powi(2))
+            .sum::<f64>()
+            / self.values.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    /// The value below which `percentile`% of the values fall (nearest rank method).
+    /// `None` if no deal was run.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn percentile(&self, percentile: f64) -> Option<i32> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let percentile = percentile.clamp(0.0, 100.0);
+        let rank = ((percentile / 100.0) * self.values.len() as f64).ceil() as usize;
+        self.values.get(rank.max(1) - 1).copied()
+    }
+
+    /// How many times every value was measured.
+    #[must_use]
+    pub fn histogram(&self) -> BTreeMap<i32, usize> {
+        self.values.iter().copied().counts().into_iter().collect()
+    }
+}
+
+/// Statistics collected for an event: how many times it happened
+/// over the deals where it could happen.
+#[derive(Debug, Clone)]
+pub struct EventStatistics {
+    name: String,
+    occurrences: usize,
+    trials: usize,
+}
+
+impl EventStatistics {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            occurrences: 0,
+            trials: 0,
+        }
+    }
+
+    fn add(&mut self, trial: bool, occurred: bool) {
+        self.trials += usize::from(trial);
+        self.occurrences += usize::from(occurred);
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of times the event happened.
+    #[must_use]
+    pub fn occurrences(&self) -> usize {
+        self.occurrences
+    }
+
+    /// Number of deals on which the event was evaluated: for conditional events
+    /// these are the deals matching the condition.
+    #[must_use]
+    pub fn trials(&self) -> usize {
+        self.trials
+    }
+
+    /// Probability of the event, `None` if it was never evaluated.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn probability(&self) -> Option<f64> {
+        if self.trials == 0 {
+            None
+        } else {
+            Some(self.occurrences as f64 / self.trials as f64)
+        }
+    }
+}
+
+/// The results of a [`FrequencySimulation`].
+#[derive(Debug, Clone)]
+pub struct FrequencySimulationResult {
+    deals_run: usize,
+    measures: Vec<MeasureStatistics>,
+    predicates: Vec<EventStatistics>,
+    conditionals: Vec<EventStatistics>,
+}
+
+impl FrequencySimulationResult {
+    #[must_use]
+    pub fn deals_run(&self) -> usize {
+        self.deals_run
+    }
+
+    /// Statistics for the measure called `name`.
+    #[must_use]
+    pub fn measure(&self, name: &str) -> Option<&MeasureStatistics> {
+        self.measures.iter().find(|measure| measure.name == name)
+    }
+
+    /// Probability of the event called `name`.
+    #[must_use]
+    pub fn probability(&self, name: &str) -> Option<f64> {
+        self.predicates
+            .iter()
+            .find(|predicate| predicate.name == name)
+            .and_then(EventStatistics::probability)
+    }
+
+    /// Conditional probability of the conditional event called `name`.
+    #[must_use]
+    pub fn conditional_probability(&self, name: &str) -> Option<f64> {
+        self.conditionals
+            .iter()
+            .find(|conditional| conditional.name == name)
+            .and_then(EventStatistics::probability)
+    }
+
+    #[must_use]
+    pub fn measures(&self) -> &[MeasureStatistics] {
+        &self.measures
+    }
+
+    #[must_use]
+    pub fn predicates(&self) -> &[EventStatistics] {
+        &self.predicates
+    }
+
+    #[must_use]
+    pub fn conditionals(&self) -> &[EventStatistics] {
+        &self.conditionals
+    }
+}
+
+impl SimulationResult for FrequencySimulationResult {
+    fn report(&self) {
+        for line in self.to_string().lines() {
+            if line.ends_with(':') {
+                println!("{}", line.green());
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+}
+
+impl Display for FrequencySimulationResult {
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .measures
+            .iter()
+            .map(|measure| measure.name.len())
+            .chain(self.predicates.iter().map(|event| event.name.len()))
+            .chain(self.conditionals.iter().map(|event| event.name.len()))
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        writeln!(f, "Simulated {} deals:", self.deals_run)?;
+        if !self.predicates.is_empty() {
+            writeln!(f, "Probabilities:")?;
+            for event in &self.predicates {
+                writeln!(
+                    f,
+                    "{:<width$} {:>7.2}%",
+                    event.name,
+                    event.probability().unwrap_or(0.0) * 100.0
+                )?;
+            }
+        }
+        if !self.conditionals.is_empty() {
+            writeln!(f, "Conditional probabilities:")?;
+            for event in &self.conditionals {
+                match event.probability() {
+                    Some(probability) => writeln!(
+                        f,
+                        "{:<width$} {:>7.2}%  (condition met {} times)",
+                        event.name,
+                        probability * 100.0,
+                        event.trials
+                    )?,
+                    None => writeln!(f, "{:<width$}       -   (condition never met)", event.name)?,
+                }
+            }
+        }
+        if !self.measures.is_empty() {
+            writeln!(f, "Measures:")?;
+            writeln!(
+                f,
+                "{:<width$} {:>7} {:>7} {:>5} {:>5} {:>5} {:>5} {:>5}",
+                "Name", "Mean", "StdDev", "P10", "P25", "P50", "P75", "P90"
+            )?;
+            for measure in &self.measures {
+                let Some(mean) = measure.mean() else {
+                    writeln!(f, "{:<width$} {:>7}", measure.name, "-")?;
+                    continue;
+                };
+                write!(
+                    f,
+                    "{:<width$} {:>7.2} {:>7.2}",
+                    measure.name,
+                    mean,
+                    measure.std_dev().unwrap_or(0.0)
+                )?;
+                for percentile in [10.0, 25.0, 50.0, 75.0, 90.0] {
+                    write!(f, " {:>5}", measure.percentile(percentile).unwrap_or(0))?;
+                }
+                writeln!(f)?;
+            }
+            for measure in &self.measures {
+                writeln!(f, "Histogram of {}:", measure.name)?;
+                for (value, times) in measure.histogram() {
+                    let percentage = times as f64 / measure.values.len() as f64 * 100.0;
+                    writeln!(
+                        f,
+                        "{value:>5} {percentage:>6.2}% {}",
+                        "#".repeat((percentage / 2.0).round() as usize)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    #[test]
+    fn frequency_simulation_ok() {
+        let hand = Cards::from_str("KQ2 AJ43 K32 Q54").unwrap();
+        let dealer = DealerBuilder::new()
+            .predeal(Seat::South, hand)
+            .unwrap()
+            .with_hand_descriptor(
+                Seat::North,
+                HandDescriptor::new(vec![HandTypeBuilder::balanced(15, 17).build()]),
+            )
+            .build()
+            .unwrap();
+        let simulation = FrequencySimulation::new(500, dealer)
+            .with_measure("North HCP", |deal: &Deal| i32::from(deal.north().hcp()))
+            .with_predicate("North 3+ spades", |deal: &Deal| deal.north().slen() >= 3)
+            .with_predicate("North 20+ HCP", |deal: &Deal| deal.north().hcp() >= 20)
+            .with_conditional(
+                "North 4 hearts | North 3- spades",
+                |deal: &Deal| deal.north().hlen() == 4,
+                |deal: &Deal| deal.north().slen() <= 3,
+            )
+            .with_conditional(
+                "Impossible condition",
+                |_: &Deal| true,
+                |deal: &Deal| deal.north().hcp() > 30,
+            );
+        let result = simulation.run().unwrap();
+        result.report();
+
+        assert_eq!(result.deals_run(), 500);
+        let hcp = result.measure("North HCP").unwrap();
+        assert_eq!(hcp.values().len(), 500);
+        assert!(hcp.values().iter().all(|hcp| (15..=17).contains(hcp)));
+        let mean = hcp.mean().unwrap();
+        assert!((15.0..=17.0).contains(&mean));
+        assert!((15..=17).contains(&hcp.percentile(50.0).unwrap()));
+        assert_eq!(hcp.histogram().values().sum::<usize>(), 500);
+        assert_eq!(result.probability("North 20+ HCP"), Some(0.0));
+        assert!(result.probability("North 3+ spades").unwrap() > 0.0);
+        let conditional = result
+            .conditional_probability("North 4 hearts | North 3- spades")
+            .unwrap();
+        assert!((0.0..=1.0).contains(&conditional));
+        assert_eq!(result.conditional_probability("Impossible condition"), None);
+        assert_eq!(result.probability("Missing"), None);
+    }
+
+    #[test]
+    fn percentiles_test() {
+        let statistics = super::MeasureStatistics {
+            name: String::from("test"),
+            values: (1..=10).collect(),
+        };
+        assert_eq!(statistics.percentile(0.0), Some(1));
+        assert_eq!(statistics.percentile(50.0), Some(5));
+        assert_eq!(statistics.percentile(90.0), Some(9));
+        assert_eq!(statistics.percentile(100.0), Some(10));
+        assert!((statistics.mean().unwrap() - 5.5).abs() < f64::EPSILON);
+    }
+}
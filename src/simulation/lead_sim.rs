@@ -6,6 +6,8 @@ use colored::Colorize;
 use std::{collections::HashMap, fmt::Display};
 
 use crate::{
+    card_code,
+    export::{contract_code, csv_number, json_number},
     prelude::{Card, Contract, Dealer, SqueezerError},
     Deal, Suit,
 };
@@ -15,6 +17,9 @@ use dds::{
 };
 use itertools::Itertools;
 
+#[cfg(feature = "lin")]
+use crate::prelude::{DealerBuilder, LinDeal, Seat, StandardDealer};
+
 /// The struct you will fire up when you want to run a lead simulation.
 /// You will provide the number of boards you want to run the simulation
 /// for; a structure implementing the [`crate::prelude::Dealer`] trait,
@@ -58,6 +63,19 @@ impl<D: Dealer> LeadSimulation<D> {
         }
     }
 
+    /// Sets the number of boards to run the simulation for.
+    #[must_use]
+    pub fn with_num_of_boards(mut self, num_of_boards: usize) -> Self {
+        self.num_of_boards = num_of_boards;
+        self
+    }
+
+    /// The contract the simulation runs over.
+    #[must_use]
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
     fn solve_boards<S: BridgeSolver>(
         &self,
         num: usize,
@@ -76,6 +94,51 @@ impl<D: Dealer> LeadSimulation<D> {
     }
 }
 
+#[cfg(feature = "lin")]
+impl LeadSimulation<StandardDealer> {
+    /// Number of boards used by [`LeadSimulation::from_lin_deal`].
+    pub const DEFAULT_NUM_OF_BOARDS: usize = 1000;
+
+    /// Builds a lead simulation for a played board, from the point of view of `seat`,
+    /// which must be the opening leader of the contract actually reached.
+    ///
+    /// The leader keeps the cards they held at the table, while the other three hands are
+    /// dealt according to what the auction showed, read with the natural-bidding heuristics of
+    /// [`Bidding::natural_inferences`](crate::prelude::Bidding::natural_inferences). Hands for
+    /// which the heuristics give contradictory information are left unconstrained.
+    ///
+    /// The simulation runs over [`Self::DEFAULT_NUM_OF_BOARDS`] boards; use
+    /// [`LeadSimulation::with_num_of_boards`] to change it.
+    ///
+    /// # Errors
+    /// If the board has no auction, was passed out, or `seat` is not on lead.
+    pub fn from_lin_deal(deal: &LinDeal, seat: Seat) -> Result<Self, SqueezerError> {
+        let auction = deal
+            .bidding()
+            .ok_or_else(|| SqueezerError::Generic("the board has no auction".to_owned()))?;
+        let contract = deal
+            .contract()
+            .ok_or_else(|| SqueezerError::Generic("the board was passed out".to_owned()))?;
+        let leader = contract.declarer().next();
+        if seat != leader {
+            return Err(SqueezerError::Generic(format!(
+                "{seat} is not on lead against {contract}, {leader} is"
+            )));
+        }
+
+        let mut descriptors = auction
+            .natural_inferences(deal.dealer())
+            .map(|inference| inference.hand_descriptor());
+        descriptors[seat as usize] = None;
+        let dealer = DealerBuilder::new()
+            .predeal(seat, deal.hands()[seat as usize].as_cards())?
+            .with_hand_descriptors_all(descriptors)
+            .with_vulnerability(deal.vulnerability())
+            .build_standard();
+        Ok(Self::new(Self::DEFAULT_NUM_OF_BOARDS, dealer, contract))
+    }
+}
+
 impl<T: Dealer> Simulation<LeadSimulationResult> for LeadSimulation<T> {
     #[allow(clippy::integer_division)]
     fn run(&self) -> Result<LeadSimulationResult, SqueezerError> {
@@ -198,6 +261,55 @@ impl LeadSimulationResult {
             lead.finish(tricks_beating, runs);
         }
     }
+
+    /// The leads, sorted by set percentage, best first.
+    fn sorted_leads(&self) -> impl Iterator<Item = &LeadCard> {
+        self.lead_results
+            .values()
+            .sorted_by(|a, b| b.set_percentage.total_cmp(&a.set_percentage))
+    }
+
+    /// The results as a JSON object. See [`crate::export`] for the schema.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let leads = self
+            .sorted_leads()
+            .map(|lead| {
+                format!(
+                    r#"{{"card":"{}","average_tricks":{},"set_percentage":{},"tricks":[{}]}}"#,
+                    card_code(lead.card),
+                    json_number(lead.average_tricks),
+                    json_number(lead.set_percentage),
+                    lead.number_of_tricks.iter().join(",")
+                )
+            })
+            .join(",");
+        format!(
+            r#"{{"contract":"{}","deals":{},"leads":[{leads}]}}"#,
+            contract_code(self.contract),
+            self.deals_run
+        )
+    }
+
+    /// The results as CSV, with a header and a row for every lead. See [`crate::export`]
+    /// for the columns.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let header = format!(
+            "card,average_tricks,set_percentage,{}\n",
+            (0..=13).map(|tricks| format!("tricks_{tricks}")).join(",")
+        );
+        let rows = self.sorted_leads().map(|lead| {
+            format!(
+                "{},{},{},{}\n",
+                card_code(lead.card),
+                csv_number(lead.average_tricks),
+                csv_number(lead.set_percentage),
+                lead.number_of_tricks.iter().join(",")
+            )
+        });
+        std::iter::once(header).chain(rows).collect()
+    }
 }
 
 impl SimulationResult for LeadSimulationResult {
@@ -300,13 +412,66 @@ impl<D: Dealer> Iterator for DealProducer<'_, D> {
             self.counter = 0;
         }
         self.counter += 1;
-        Some(self.deal)
+        Some(self.deal.clone())
     }
 }
 #[cfg(test)]
 mod test {
     use crate::prelude::*;
 
+    #[test]
+    fn lead_result_export_test() {
+        let contract = Contract::from_str("3NS", Vulnerable::No).unwrap();
+        let mut result = super::LeadSimulationResult::new(contract, 4);
+        for (card, tricks) in [(Card::SA, [5, 4, 6, 3]), (Card::HK, [4, 4, 4, 3])] {
+            let mut lead = super::LeadCard::new(card);
+            for tricks in tricks {
+                lead.number_of_tricks[tricks] += 1;
+            }
+            result.lead_results.insert(card, lead);
+        }
+        result.finish(5, 4);
+        assert_eq!(
+            result.to_json(),
+            r#"{"contract":"3NS","deals":4,"leads":[{"card":"SA","average_tricks":4.5,"set_percentage":50,"tricks":[0,0,0,1,1,1,1,0,0,0,0,0,0,0]},{"card":"HK","average_tricks":3.75,"set_percentage":0,"tricks":[0,0,0,1,3,0,0,0,0,0,0,0,0,0]}]}"#
+        );
+        let csv = result.to_csv();
+        let mut lines = csv.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("card,average_tricks,set_percentage,tricks_0,"));
+        assert_eq!(lines.next(), Some("SA,4.5,50,0,0,0,1,1,1,1,0,0,0,0,0,0,0"));
+        assert_eq!(lines.count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "lin")]
+    fn from_lin_deal_test() {
+        let lin = "pn|simodra,fra97,matmont,thevava|st||md|3S34JH258TQKD2JQC7,S27TH69D679TKAC23,S6QH47JD458C468JA,|rh||ah|Board 1|sv|o|mb|p|mb|1S|mb|2H|mb|2S|mb|3H|mb|4S|mb|p|mb|p|mb|p|pg||";
+        let board = LinDeal::from_str(lin).unwrap();
+        assert!(LeadSimulation::from_lin_deal(&board, Seat::North).is_err());
+        let simulation = LeadSimulation::from_lin_deal(&board, Seat::South)
+            .unwrap()
+            .with_num_of_boards(10);
+        assert_eq!(
+            simulation.contract(),
+            Contract::from_str("4SE", Vulnerable::No).unwrap()
+        );
+        assert_eq!(simulation.num_of_boards, 10);
+        for _ in 0..10 {
+            let deal = simulation.dealer.deal().unwrap();
+            assert_eq!(deal.south(), board.hands()[Seat::South as usize]);
+            assert!(deal.east().slen() >= 5 && (11..=21).contains(&deal.east().hcp()));
+            assert!(deal.west().slen() >= 3 && deal.west().hcp() >= 6);
+            assert!(deal.north().hlen() >= 3 && deal.north().hcp() <= 11);
+        }
+
+        let passed_out = lin.replace("mb|1S|mb|2H|mb|2S|mb|3H|mb|4S|mb|p|", "mb|p|");
+        let board = LinDeal::from_str(&passed_out).unwrap();
+        assert!(LeadSimulation::from_lin_deal(&board, Seat::South).is_err());
+    }
+
     #[test]
     fn lead_simulation_ok() {
         let hand = Cards::from_str("AQT KQ732 432 43").unwrap();
@@ -3,11 +3,29 @@
 
 use crate::SqueezerError;
 
+#[cfg(feature = "dds")]
+mod declarer_sim;
+#[cfg(feature = "dds")]
+mod defence_sim;
+mod frequency_sim;
+#[cfg(feature = "dds")]
 mod lead_sim;
+#[cfg(feature = "dds")]
 mod payoff;
+#[cfg(feature = "dds")]
+mod single_dummy;
 
+#[cfg(feature = "dds")]
+pub use declarer_sim::*;
+#[cfg(feature = "dds")]
+pub use defence_sim::*;
+pub use frequency_sim::*;
+#[cfg(feature = "dds")]
 pub use lead_sim::*;
+#[cfg(feature = "dds")]
 pub use payoff::*;
+#[cfg(feature = "dds")]
+pub use single_dummy::{PlayCardResult, PlaySimulationResult};
 
 pub trait SimulationResult {
     fn report(&self);
@@ -6,15 +6,41 @@
 /// Key components include the `Payoff` struct for managing a payoff matrix, `Contract` struct representing a bridge contract,
 /// and various scoring functions such as `imps` and `matchpoints`.
 /// The file provides methods for calculating scores, creating contracts from strings, and reporting results based on simulated data.
+use crate::export::{contract_code, csv_field, csv_number, json_number, json_string};
 use crate::prelude::*;
 use dds::{doubledummy::DoubleDummySolver, solver::BridgeSolver, traits::ContractScorer};
 use fmt::Display;
 use itertools::Itertools;
 use std::io::Write;
 
-pub trait DifferenceMaker {}
-impl DifferenceMaker for Cards {}
-impl DifferenceMaker for Contract {}
+pub trait DifferenceMaker {
+    /// The entry as written by [`Payoff::to_json`] and [`Payoff::to_csv`], e.g. `4SN` or
+    /// `SA`. See [`crate::export`] for the codes. Defaults to the `Display` of the entry.
+    fn code(&self) -> String
+    where
+        Self: Display,
+    {
+        self.to_string()
+    }
+}
+
+impl DifferenceMaker for Card {
+    fn code(&self) -> String {
+        card_code(*self)
+    }
+}
+
+impl DifferenceMaker for Cards {
+    fn code(&self) -> String {
+        self.into_iter().map(card_code).join(" ")
+    }
+}
+
+impl DifferenceMaker for Contract {
+    fn code(&self) -> String {
+        contract_code(*self)
+    }
+}
 
 /// Struct for running payoff simulation: is it better to risk a 3NT or better to play 4C in
 /// terms of expected value?
@@ -88,7 +114,7 @@ impl<E: Fn(i32, i32) -> i32, D: Dealer> Simulation<Payoff<Contract>>
     #[inline]
     fn run(&self) -> Result<Payoff<Contract>, SqueezerError> {
         let no_of_entries = self.to_compare.len();
-        let mut payoff = Payoff::new(self.to_compare.clone());
+        let payoff = Payoff::new(self.to_compare.clone());
         let mut entries: Vec<PayoffAccumulator<Contract>> = self
             .to_compare
             .iter()
@@ -131,25 +157,8 @@ impl<E: Fn(i32, i32) -> i32, D: Dealer> Simulation<Payoff<Contract>>
             }
             deal_buffer.clear();
         }
-        for (starting_entry_index, entry) in entries.iter().enumerate() {
-            for (next_entry_index, next_entry) in entries.iter().enumerate() {
-                if starting_entry_index == next_entry_index {
-                    payoff.results.push((0.0, 0.0));
-                    continue;
-                }
-                let diffs: Vec<i32> = entry
-                    .results
-                    .iter()
-                    .zip(next_entry.results.iter())
-                    .map(|(first_res, second_res)| (self.diff)(*first_res, *second_res))
-                    .collect();
-
-                payoff
-                    .results
-                    .push(mean_and_std_dev(&diffs).unwrap_or((0.0, 0.0)));
-            }
-        }
-        Ok(payoff)
+        let scores: Vec<Vec<i32>> = entries.into_iter().map(|entry| entry.results).collect();
+        Ok(payoff.with_scores(&scores, &self.diff))
     }
 }
 
@@ -197,6 +206,97 @@ where
             results: Vec::new(),
         }
     }
+
+    /// Fills the matrix from the scores of every entry, in the same order, on the same
+    /// deals: each pair of entries is compared deal by deal with `diff`.
+    pub(crate) fn with_scores<E: Fn(i32, i32) -> i32>(
+        mut self,
+        scores: &[Vec<i32>],
+        diff: E,
+    ) -> Self {
+        self.results.clear();
+        for (index, entry) in scores.iter().enumerate() {
+            for (against_index, against) in scores.iter().enumerate() {
+                if index == against_index {
+                    self.results.push((0.0, 0.0));
+                    continue;
+                }
+                let diffs: Vec<i32> = entry
+                    .iter()
+                    .zip(against)
+                    .map(|(first, second)| diff(*first, *second))
+                    .collect();
+                self.results
+                    .push(mean_and_std_dev(&diffs).unwrap_or((0.0, 0.0)));
+            }
+        }
+        self
+    }
+
+    /// Mean and standard deviation of the gain of `entry` over `against`, by index.
+    fn result(&self, entry: usize, against: usize) -> Option<(f32, f32)> {
+        if entry == against {
+            Some((0.0, 0.0))
+        } else {
+            self.results
+                .get(entry * self.entries.len() + against)
+                .copied()
+        }
+    }
+
+    /// The matrix as a JSON object. See [`crate::export`] for the schema.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let matrix = |value: fn((f32, f32)) -> f32| {
+            (0..self.entries.len())
+                .map(|entry| {
+                    format!(
+                        "[{}]",
+                        (0..self.entries.len())
+                            .map(|against| {
+                                self.result(entry, against)
+                                    .map_or_else(|| String::from("null"), |r| json_number(value(r)))
+                            })
+                            .join(",")
+                    )
+                })
+                .join(",")
+        };
+        format!(
+            r#"{{"entries":[{}],"mean":[{}],"std_dev":[{}]}}"#,
+            self.entries
+                .iter()
+                .map(|entry| json_string(&entry.code()))
+                .join(","),
+            matrix(|(mean, _)| mean),
+            matrix(|(_, std_dev)| std_dev)
+        )
+    }
+
+    /// The matrix as CSV, with a header and a row for every pair of different entries.
+    /// See [`crate::export`] for the columns.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let rows = self
+            .entries
+            .iter()
+            .enumerate()
+            .cartesian_product(self.entries.iter().enumerate())
+            .filter(|((entry, _), (against, _))| entry != against)
+            .filter_map(|((entry, first), (against, second))| {
+                let (mean, std_dev) = self.result(entry, against)?;
+                Some(format!(
+                    "{},{},{},{}\n",
+                    csv_field(&first.code()),
+                    csv_field(&second.code()),
+                    csv_number(mean),
+                    csv_number(std_dev)
+                ))
+            });
+        std::iter::once(String::from("entry,against,mean,std_dev\n"))
+            .chain(rows)
+            .collect()
+    }
 }
 
 impl<D: Display + DifferenceMaker> SimulationResult for Payoff<D> {
@@ -332,6 +432,25 @@ mod test {
         matrix.report();
     }
 
+    #[test]
+    fn payoff_export_test() {
+        let contracts = vec![
+            Contract::from_str("4SN", Vulnerable::No).unwrap(),
+            Contract::from_str("3NN", Vulnerable::No).unwrap(),
+        ];
+        let mut payoff = Payoff::new(contracts);
+        payoff.results = vec![(0.0, 0.0), (1.5, 2.0), (-1.5, 2.0), (0.0, 0.0)];
+        let json = payoff.to_json();
+        assert!(json.starts_with(r#"{"entries":["4SN","3NN"],"#));
+        assert!(json.ends_with(r#""mean":[[0,1.5],[-1.5,0]],"std_dev":[[0,2],[2,0]]}"#));
+        let csv = payoff.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("entry,against,mean,std_dev"));
+        assert_eq!(lines.next(), Some("4SN,3NN,1.5,2"));
+        assert_eq!(lines.next(), Some("3NN,4SN,-1.5,2"));
+        assert_eq!(lines.next(), None);
+    }
+
     #[test]
     #[should_panic(expected = "Wrong contract level")]
     fn create_contract_wrong_level_test() {
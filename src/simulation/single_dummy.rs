@@ -0,0 +1,519 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+use super::SimulationResult;
+use crate::prelude::*;
+use colored::Colorize;
+use dds::{
+    doubledummy::MultiThreadDoubleDummySolver,
+    solver::{BridgeSolver, SolvedBoards},
+    traits::ContractScorer,
+    MAXNOOFBOARDS,
+};
+use std::collections::HashMap;
+
+/// A point in the play of a deal as seen from the table: the original hands of the players
+/// whose cards are visible, the cards played so far and what is known of the hidden hands.
+///
+/// The hidden hands are dealt holding the cards they played, never holding cards of a suit
+/// they showed out of, and matching their [`HandDescriptor`]s if any.
+#[derive(Debug, Clone)]
+pub(crate) struct PlayPosition {
+    contract: Contract,
+    visible: [Option<Cards>; NUMBER_OF_HANDS],
+    played: Vec<Card>,
+    // The cards played by every player.
+    shown: [Cards; NUMBER_OF_HANDS],
+    // The suits every player showed out of.
+    voids: Vec<(Seat, Suit)>,
+    next_to_play: Seat,
+    descriptors: [Option<HandDescriptor>; NUMBER_OF_HANDS],
+}
+
+impl PlayPosition {
+    /// Checks that the cards played are consistent with the visible hands and the rules of
+    /// play, and finds the player on turn.
+    pub(crate) fn new(
+        contract: Contract,
+        visible: [Option<Cards>; NUMBER_OF_HANDS],
+        played: &[Card],
+    ) -> Result<Self, SqueezerError> {
+        let mut remaining = visible;
+        let mut shown = [Cards::EMPTY; NUMBER_OF_HANDS];
+        let mut voids = Vec::new();
+        let mut leader = contract.leader();
+        for trick in played.chunks(4) {
+            let led = trick[0].suit();
+            for (index, &card) in trick.iter().enumerate() {
+                let seat = leader + index;
+                let error = |kind| Err(PlayError::new(seat, Some(card), kind).into());
+                let held = match remaining[seat as usize] {
+                    Some(hand) => hand.contains(card),
+                    None => {
+                        visible.iter().flatten().all(|hand| !hand.contains(card))
+                            && !voids.contains(&(seat, card.suit()))
+                    }
+                };
+                if !held || shown.iter().any(|cards| cards.contains(card)) {
+                    return error(PlayErrorKind::NotHeld);
+                }
+                // The hidden hands follow suit whenever they can, by construction.
+                if card.suit() != led
+                    && remaining[seat as usize].is_some_and(|hand| !hand.in_suit(led).is_empty())
+                {
+                    return error(PlayErrorKind::Revoke);
+                }
+                if card.suit() != led && !voids.contains(&(seat, led)) {
+                    voids.push((seat, led));
+                }
+                if let Some(hand) = remaining[seat as usize].as_mut() {
+                    *hand = hand.remove(card);
+                }
+                shown[seat as usize] = shown[seat as usize].insert(card);
+            }
+            if trick.len() == 4 {
+                leader = leader + trick_winner(trick, contract.strain());
+            }
+        }
+        if played.len() >= 52 {
+            return Err(PlayError::new(leader, None, PlayErrorKind::Finished).into());
+        }
+        Ok(Self {
+            contract,
+            visible,
+            played: played.to_vec(),
+            shown,
+            voids,
+            next_to_play: leader + played.len() % 4,
+            descriptors: Default::default(),
+        })
+    }
+
+    pub(crate) fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    pub(crate) fn played(&self) -> &[Card] {
+        &self.played
+    }
+
+    pub(crate) fn next_to_play(&self) -> Seat {
+        self.next_to_play
+    }
+
+    /// Constrains the hand of `seat`, if it is hidden.
+    pub(crate) fn set_hand_descriptor(&mut self, seat: Seat, descriptor: HandDescriptor) {
+        if self.visible[seat as usize].is_none() {
+            self.descriptors[seat as usize] = Some(descriptor);
+        }
+    }
+
+    /// A dealer dealing the hidden hands consistently with the play so far.
+    pub(crate) fn dealer(&self) -> Result<StandardDealer, SqueezerError> {
+        let mut predeal = self.visible;
+        for (seat, hand) in predeal.iter_mut().enumerate() {
+            if hand.is_none() && !self.shown[seat].is_empty() {
+                *hand = Some(self.shown[seat]);
+            }
+        }
+        let shown = self.shown;
+        let voids = self.voids.clone();
+        Ok(DealerBuilder::new()
+            .predeal_all(predeal)?
+            .with_hand_descriptors_all(self.descriptors.clone())
+            .with_function(move |hands: &Hands| {
+                // The cards played in a suit the player showed out of were all they held.
+                voids.iter().all(|&(seat, suit)| {
+                    hands[seat as usize].as_cards().in_suit(suit).len()
+                        == shown[seat as usize].in_suit(suit).len()
+                })
+            })
+            .build_standard())
+    }
+
+    /// Deals `num_of_boards` layouts of the hidden hands and solves every one of them double
+    /// dummy for every legal card of the player on turn.
+    pub(crate) fn simulate(
+        &self,
+        num_of_boards: usize,
+    ) -> Result<PlaySimulationResult, SqueezerError> {
+        let dealer = self.dealer()?;
+        let solver = MultiThreadDoubleDummySolver::new();
+        let mut result = PlaySimulationResult::new(self.contract, self.next_to_play, num_of_boards);
+        let mut counter = num_of_boards;
+        let mut states = Vec::with_capacity(MAXNOOFBOARDS);
+        while counter != 0 {
+            let batch = counter.min(MAXNOOFBOARDS);
+            states.clear();
+            for _ in 0..batch {
+                let mut state = PlayState::new(&dealer.deal()?, self.contract);
+                for &card in &self.played {
+                    state.play(card)?;
+                }
+                states.push(state);
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let solved_boards =
+                solver.dd_tricks_all_cards_parallel(batch as i32, &states, &states)?;
+            result.add_results(&states, &solved_boards)?;
+            counter -= batch;
+        }
+        result.finish();
+        Ok(result)
+    }
+}
+
+/// The results of a single card in a [`PlaySimulationResult`]: how many times the side
+/// of the player ends up with each number of tricks when the card is played, and the
+/// score they get on every deal.
+#[derive(Debug, Clone)]
+pub struct PlayCardResult {
+    card: Card,
+    number_of_tricks: [usize; 14],
+    scores: Vec<i32>,
+    average_tricks: f32,
+    success_percentage: f32,
+    average_score: f32,
+}
+
+impl PlayCardResult {
+    fn new(card: Card) -> Self {
+        Self {
+            card,
+            number_of_tricks: [0; 14],
+            scores: Vec::new(),
+            average_tricks: 0.0,
+            success_percentage: 0.0,
+            average_score: 0.0,
+        }
+    }
+
+    #[must_use]
+    pub fn card(&self) -> Card {
+        self.card
+    }
+
+    /// The number of deals in which the side of the player takes each number of tricks,
+    /// counting the ones already won.
+    #[must_use]
+    pub fn number_of_tricks(&self) -> &[usize; 14] {
+        &self.number_of_tricks
+    }
+
+    /// The average number of tricks taken by the side of the player.
+    #[must_use]
+    pub fn average_tricks(&self) -> f32 {
+        self.average_tricks
+    }
+
+    /// The percentage of deals in which the contract makes, if the player is on the
+    /// declaring side, or goes down, if they are defending.
+    #[must_use]
+    pub fn success_percentage(&self) -> f32 {
+        self.success_percentage
+    }
+
+    /// The average score of the side of the player.
+    #[must_use]
+    pub fn average_score(&self) -> f32 {
+        self.average_score
+    }
+
+    /// The score of the side of the player on every deal, in the order they were dealt.
+    #[must_use]
+    pub fn scores(&self) -> &[i32] {
+        &self.scores
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn finish(&mut self, target: u8, runs: usize) {
+        self.average_tricks = self
+            .number_of_tricks
+            .iter()
+            .enumerate()
+            .map(|(tricks, times)| tricks * times)
+            .sum::<usize>() as f32
+            / runs as f32;
+        self.success_percentage = self.number_of_tricks[target as usize..]
+            .iter()
+            .sum::<usize>() as f32
+            / runs as f32
+            * 100.0;
+        self.average_score = self.scores.iter().sum::<i32>() as f32 / runs as f32;
+    }
+}
+
+/// The results of a single dummy simulation of the play: for every legal card of the
+/// player on turn, the tricks their side takes double dummy after it is played.
+#[derive(Debug, Clone)]
+pub struct PlaySimulationResult {
+    contract: Contract,
+    player: Seat,
+    deals_run: usize,
+    results: HashMap<Card, PlayCardResult>,
+}
+
+impl PlaySimulationResult {
+    fn new(contract: Contract, player: Seat, deals_run: usize) -> Self {
+        Self {
+            contract,
+            player,
+            deals_run,
+            results: HashMap::with_capacity(13),
+        }
+    }
+
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    fn add_results(
+        &mut self,
+        states: &[PlayState],
+        solved: &SolvedBoards,
+    ) -> Result<(), SqueezerError> {
+        for (state, future_tricks) in states.iter().zip(solved) {
+            let won = state.tricks(self.player) as usize;
+            for (card, future) in scored_cards(future_tricks)? {
+                let tricks = won + future as usize;
+                let score = self.score(tricks as u8);
+                let result = self
+                    .results
+                    .entry(card)
+                    .or_insert_with(|| PlayCardResult::new(card));
+                result.number_of_tricks[tricks] += 1;
+                result.scores.push(score);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        let target = self.target();
+        for result in self.results.values_mut() {
+            result.finish(target, self.deals_run);
+        }
+    }
+
+    // The score of the side of the player when it takes `tricks` tricks.
+    fn score(&self, tricks: u8) -> i32 {
+        if self.player.is_same_line(&self.contract.declarer()) {
+            self.contract.score(tricks)
+        } else {
+            -self.contract.score(13 - tricks)
+        }
+    }
+
+    // The tricks the side of the player needs.
+    fn target(&self) -> u8 {
+        if self.player.is_same_line(&self.contract.declarer()) {
+            self.contract.level() + 6
+        } else {
+            8 - self.contract.level()
+        }
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> Contract {
+        self.contract
+    }
+
+    /// The player on turn, whose cards were evaluated.
+    #[must_use]
+    pub fn player(&self) -> Seat {
+        self.player
+    }
+
+    #[must_use]
+    pub fn deals_run(&self) -> usize {
+        self.deals_run
+    }
+
+    /// The results of `card`, if it is a legal card of the player.
+    #[must_use]
+    pub fn card(&self, card: Card) -> Option<&PlayCardResult> {
+        self.results.get(&card)
+    }
+
+    /// The results of every legal card, sorted by success percentage and then by average
+    /// tricks, best first.
+    pub fn cards(&self) -> impl Iterator<Item = &PlayCardResult> {
+        self.results.values().sorted_by(|a, b| {
+            b.success_percentage
+                .total_cmp(&a.success_percentage)
+                .then(b.average_tricks.total_cmp(&a.average_tricks))
+                .then(a.card.cmp(&b.card))
+        })
+    }
+
+    /// The card with the best chance of making (or setting) the contract.
+    #[must_use]
+    pub fn best_for_success(&self) -> Option<Card> {
+        self.cards().next().map(PlayCardResult::card)
+    }
+
+    /// The card taking the most tricks on average.
+    #[must_use]
+    pub fn best_for_tricks(&self) -> Option<Card> {
+        self.cards()
+            .max_by(|a, b| a.average_tricks.total_cmp(&b.average_tricks))
+            .map(PlayCardResult::card)
+    }
+
+    /// The card with the best average score.
+    #[must_use]
+    pub fn best_for_score(&self) -> Option<Card> {
+        self.cards()
+            .max_by(|a, b| a.average_score.total_cmp(&b.average_score))
+            .map(PlayCardResult::card)
+    }
+
+    /// The payoff matrix of the cards, in the order of [`PlaySimulationResult::cards`]:
+    /// the scores of every pair of cards are compared deal by deal with `diff`, e.g.
+    /// [`imps`](super::imps) or [`matchpoints`](super::matchpoints).
+    #[must_use]
+    pub fn payoff<E: Fn(i32, i32) -> i32>(&self, diff: E) -> Payoff<Card> {
+        let (cards, scores): (Vec<Card>, Vec<Vec<i32>>) = self
+            .cards()
+            .map(|result| (result.card, result.scores.clone()))
+            .unzip();
+        Payoff::new(cards).with_scores(&scores, diff)
+    }
+}
+
+impl SimulationResult for PlaySimulationResult {
+    fn report(&self) {
+        let best = self.best_for_success();
+        for line in self.to_string().lines() {
+            if best.is_some_and(|card| line.starts_with(&card.to_string())) {
+                println!("{}", line.green());
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+}
+
+impl fmt::Display for PlaySimulationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.deals_run.to_string().len().max(4);
+        let success = if self.player.is_same_line(&self.contract.declarer()) {
+            "%Make"
+        } else {
+            "%Set"
+        };
+        writeln!(
+            f,
+            "Simulated {} deals, {} to play in {}:",
+            self.deals_run, self.player, self.contract
+        )?;
+        writeln!(f, "{:^1$}", "Frequency of tricks taken", width * 14 + 24)?;
+        writeln!(
+            f,
+            "Cd   Avg {success:>5}   Score  {}",
+            (0..14).map(|tricks| format!("{tricks:>width$}")).join("")
+        )?;
+        let best_for_tricks = self.best_for_tricks();
+        for result in self.cards() {
+            let average = if Some(result.card) == best_for_tricks {
+                format!("*{:<4.2}", result.average_tricks)
+            } else {
+                format!("{:>5.2}", result.average_tricks)
+            };
+            writeln!(
+                f,
+                "{} {average} {:>5.2} {:>7.1}  [{:>width$} ]",
+                result.card,
+                result.success_percentage,
+                result.average_score,
+                result.number_of_tricks.iter().format("")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn contract() -> Contract {
+        Contract::from_str("4SS", Vulnerable::No).unwrap()
+    }
+
+    fn hands() -> [Option<Cards>; NUMBER_OF_HANDS] {
+        // North is dummy, South declarer.
+        let mut visible = [None; NUMBER_OF_HANDS];
+        visible[Seat::North as usize] = Some(Cards::from_str("SKQ2 HAK2 D8765 C432").unwrap());
+        visible[Seat::South as usize] = Some(Cards::from_str("SAJT98 H43 DAK2 CK65").unwrap());
+        visible
+    }
+
+    #[test]
+    fn play_position_test() {
+        // West leads the CQ, East discards a heart and the declarer wins the CK.
+        let played = [Card::CQ, Card::C2, Card::H5, Card::CK];
+        let position = PlayPosition::new(contract(), hands(), &played).unwrap();
+        assert_eq!(position.next_to_play(), Seat::South);
+        assert_eq!(position.voids, [(Seat::East, Suit::Clubs)]);
+        assert_eq!(
+            position.shown[Seat::West as usize],
+            Cards::from_str("CQ").unwrap()
+        );
+
+        let dealer = position.dealer().unwrap();
+        for _ in 0..20 {
+            let deal = dealer.deal().unwrap();
+            let east = deal.hands()[Seat::East as usize].as_cards();
+            assert!(east.in_suit(Suit::Clubs).is_empty());
+            assert!(east.contains(Card::H5));
+            let mut state = PlayState::new(&deal, contract());
+            for card in played {
+                state.play(card).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn score_test() {
+        let declarer = PlaySimulationResult::new(contract(), Seat::North, 1);
+        assert_eq!(declarer.score(10), 420);
+        assert_eq!(declarer.target(), 10);
+        let defence = PlaySimulationResult::new(contract(), Seat::West, 1);
+        assert_eq!(defence.score(4), 50);
+        assert_eq!(defence.score(3), -420);
+        assert_eq!(defence.target(), 4);
+    }
+
+    #[test]
+    fn play_position_errors_test() {
+        let error = |played: &[Card]| match PlayPosition::new(contract(), hands(), played) {
+            Err(SqueezerError::Play(error)) => Some((error.seat(), error.kind())),
+            _ => None,
+        };
+        // The dummy does not hold the card.
+        assert_eq!(
+            error(&[Card::CQ, Card::CA]),
+            Some((Seat::North, PlayErrorKind::NotHeld))
+        );
+        // A defender cannot hold the cards of the dummy.
+        assert_eq!(
+            error(&[Card::SK]),
+            Some((Seat::West, PlayErrorKind::NotHeld))
+        );
+        // The dummy must follow suit.
+        assert_eq!(
+            error(&[Card::CQ, Card::D5]),
+            Some((Seat::North, PlayErrorKind::Revoke))
+        );
+        // East showed out of clubs.
+        let played = [
+            Card::CQ,
+            Card::C2,
+            Card::H5,
+            Card::CK,
+            Card::C6,
+            Card::CJ,
+            Card::C3,
+            Card::C7,
+        ];
+        assert_eq!(error(&played), Some((Seat::East, PlayErrorKind::NotHeld)));
+    }
+}
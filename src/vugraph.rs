@@ -0,0 +1,439 @@
+// Copyright (C) 2024 Alvaro Gaiotti
+// See end of file for license information
+
+//! Parsing of multi-board lin files, like the vugraph archives of championships.
+//!
+//! A vugraph file starts with a header describing the match (`vg`), the players of both rooms
+//! (`pn` or `pw`), the results of every board (`rs`) and the board numbers (`bn`), followed by
+//! every board played, each one introduced by a room marker: `qx|o1|` for the open room of
+//! board 1, `qx|c1|` for the closed room, and so on.
+
+use crate::prelude::*;
+
+/* Vugraph reference:
+vg|Bermuda Bowl,Final Segment 1,I,1,16,Italy,0,USA,0|
+pn|Sud,Ovest,Nord,Est,South,West,North,East|
+rs|4SN=,4SN-1,3NE+1,3NW=|
+qx|o1|st||md|3SAQ432HQJT72DT3CQ,SKJH983D974CT9876,S965HK654DKJ6CAJ5,|sv|o|mb|1C!|an|2+|mb|p|...|
+qx|c1|st||md|3SAQ432HQJT72DT3CQ,SKJH983D974CT9876,S965HK654DKJ6CAJ5,|sv|o|mb|p|...|
+*/
+
+/// The two rooms of a team match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Room {
+    Open,
+    Closed,
+}
+
+impl Room {
+    fn index(self) -> usize {
+        match self {
+            Room::Open => 0,
+            Room::Closed => 1,
+        }
+    }
+}
+
+/// The header of a vugraph file, from the `vg` tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VugraphHeader {
+    event: String,
+    segment: String,
+    scoring: String,
+    first_board: u32,
+    last_board: u32,
+    teams: [String; 2],
+    carry_over: [String; 2],
+}
+
+impl VugraphHeader {
+    #[must_use]
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    #[must_use]
+    pub fn segment(&self) -> &str {
+        &self.segment
+    }
+
+    /// The scoring of the match: `I` for IMPs, `P` for matchpoints, `B` for board-a-match.
+    #[must_use]
+    pub fn scoring(&self) -> &str {
+        &self.scoring
+    }
+
+    #[must_use]
+    pub fn first_board(&self) -> u32 {
+        self.first_board
+    }
+
+    #[must_use]
+    pub fn last_board(&self) -> u32 {
+        self.last_board
+    }
+
+    /// The names of the two teams, the first one sitting North-South in the open room.
+    #[must_use]
+    pub fn teams(&self) -> &[String; 2] {
+        &self.teams
+    }
+
+    /// The carry-over of the two teams, as written in the file.
+    #[must_use]
+    pub fn carry_over(&self) -> &[String; 2] {
+        &self.carry_over
+    }
+}
+
+impl FromStr for VugraphHeader {
+    type Err = ParseLinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        if fields.len() < 9 {
+            return Err(ParseLinError::new(ParseLinErrorKind::Header, s));
+        }
+        let board = |field: &str| {
+            field
+                .parse()
+                .map_err(|_| ParseLinError::new(ParseLinErrorKind::Header, s))
+        };
+        Ok(Self {
+            event: fields[0].to_owned(),
+            segment: fields[1].to_owned(),
+            scoring: fields[2].to_owned(),
+            first_board: board(fields[3])?,
+            last_board: board(fields[4])?,
+            teams: [fields[5].to_owned(), fields[7].to_owned()],
+            carry_over: [fields[6].to_owned(), fields[8].to_owned()],
+        })
+    }
+}
+
+/// A board of a match, with the deal played in every room and the results.
+#[derive(Debug)]
+pub struct VugraphBoard {
+    number: u32,
+    deals: [Option<LinDeal>; 2],
+    results: [Option<String>; 2],
+}
+
+impl VugraphBoard {
+    fn new(number: u32) -> Self {
+        Self {
+            number,
+            deals: [None, None],
+            results: [None, None],
+        }
+    }
+
+    #[must_use]
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// The deal as played in `room`, if present in the file.
+    #[must_use]
+    pub fn deal(&self, room: Room) -> Option<&LinDeal> {
+        self.deals[room.index()].as_ref()
+    }
+
+    /// The result in `room` as written in the `rs` tag, e.g. `4SN-1`.
+    #[must_use]
+    pub fn result(&self, room: Room) -> Option<&str> {
+        self.results[room.index()].as_deref()
+    }
+
+    /// Iterates over the rooms in which the board was played.
+    pub fn rooms(&self) -> impl Iterator<Item = (Room, &LinDeal)> {
+        [Room::Open, Room::Closed]
+            .into_iter()
+            .filter_map(|room| Some((room, self.deal(room)?)))
+    }
+}
+
+/// A match parsed from a multi-board lin file.
+///
+/// # Example
+///
+/// ```
+/// # use squeezer::prelude::*;
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>>{
+/// let lin = "vg|Club Teams,Segment 1,I,1,1,Home,0,Away,0|\
+///     pn|Sud,Ovest,Nord,Est,South,West,North,East|rs|7SS=,7SS=|\
+///     qx|o1|st||md|1SAKQJT98765432,HAKQJT98765432,DAKQJT98765432,|sv|o|mb|7S|mb|p|mb|p|mb|p|\
+///     qx|c1|st||md|1SAKQJT98765432,HAKQJT98765432,DAKQJT98765432,|sv|o|mb|7S|mb|p|mb|p|mb|p|";
+/// let vugraph = VugraphMatch::from_str(lin)?;
+/// let board = &vugraph.boards()[0];
+/// assert_eq!(board.result(Room::Closed), Some("7SS="));
+/// assert_eq!(board.deal(Room::Open).unwrap().players()[2], "Sud");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct VugraphMatch {
+    header: Option<VugraphHeader>,
+    players: [[String; NUMBER_OF_HANDS]; 2],
+    boards: Vec<VugraphBoard>,
+}
+
+impl VugraphMatch {
+    #[must_use]
+    pub fn header(&self) -> Option<&VugraphHeader> {
+        self.header.as_ref()
+    }
+
+    /// The players listed in the header for `room`, in the order North, East, South, West.
+    #[must_use]
+    pub fn players(&self, room: Room) -> &[String; NUMBER_OF_HANDS] {
+        &self.players[room.index()]
+    }
+
+    /// The boards of the match, in the order they appear in the file.
+    #[must_use]
+    pub fn boards(&self) -> &[VugraphBoard] {
+        &self.boards
+    }
+
+    #[must_use]
+    pub fn board(&self, number: u32) -> Option<&VugraphBoard> {
+        self.boards.iter().find(|board| board.number == number)
+    }
+
+    /// Iterates over every deal of the match, with its room.
+    pub fn deals(&self) -> impl Iterator<Item = (Room, &LinDeal)> {
+        self.boards.iter().flat_map(VugraphBoard::rooms)
+    }
+}
+
+impl FromStr for VugraphMatch {
+    type Err = ParseLinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tags = LinTokenizer::new(s).collect::<Result<Vec<_>, _>>()?;
+        let mut header = None;
+        let mut players = [
+            ["NN"; NUMBER_OF_HANDS].map(String::from),
+            ["NN"; NUMBER_OF_HANDS].map(String::from),
+        ];
+        let mut results = Vec::new();
+        let mut board_numbers: Option<Vec<u32>> = None;
+        let mut boards: Vec<VugraphBoard> = Vec::new();
+        let mut rooms = tags.split(|tag| tag.tag() == "qx");
+        // Everything before the first room marker is the header of the match.
+        for tag in rooms.next().unwrap_or_default() {
+            let value = tag.value();
+            match tag.tag() {
+                "vg" => {
+                    header = Some(VugraphHeader::from_str(value).map_err(|e| e.at(tag.offset()))?);
+                }
+                "pn" | "pw" => {
+                    let names: Vec<&str> = value.split(',').map(str::trim).collect();
+                    for (room, names) in names.chunks(NUMBER_OF_HANDS).take(2).enumerate() {
+                        if let Some(names) = lin_players(names) {
+                            players[room] = names;
+                        }
+                    }
+                }
+                "rs" => results = value.split(',').map(str::trim).map(String::from).collect(),
+                "bn" => {
+                    board_numbers = Some(
+                        value
+                            .split(',')
+                            .map(|number| number.trim().parse())
+                            .collect::<Result<_, _>>()
+                            .map_err(|_| {
+                                ParseLinError::new(ParseLinErrorKind::Number, value)
+                                    .at(tag.offset())
+                            })?,
+                    );
+                }
+                _ => {}
+            }
+        }
+        // The values of the `qx` tags, in the same order of the rooms.
+        let markers = tags.iter().filter(|tag| tag.tag() == "qx");
+        for (marker, room_tags) in markers.zip(rooms) {
+            let (room, number) = room_marker(marker.value()).map_err(|e| e.at(marker.offset()))?;
+            let board_index = if let Some(index) = boards.iter().position(|b| b.number == number) {
+                index
+            } else {
+                boards.push(VugraphBoard::new(number));
+                boards.len() - 1
+            };
+            // The closed room may omit the hands, which are the same of the open room.
+            let previous_hands = boards[board_index]
+                .deals
+                .iter()
+                .flatten()
+                .next()
+                .map(|deal: &LinDeal| (deal.dealer(), deal.hands()));
+            let mut room_players = None;
+            let mut deal_tags = Vec::with_capacity(room_tags.len() + 2);
+            for &tag in room_tags {
+                match tag.tag() {
+                    "pn" => {
+                        let names: Vec<&str> = tag.value().split(',').map(str::trim).collect();
+                        room_players = lin_players(&names);
+                    }
+                    // We use the board number of the room marker.
+                    "ah" => {}
+                    _ => deal_tags.push(tag),
+                }
+            }
+            let md;
+            if !deal_tags.iter().any(|tag| tag.tag() == "md") {
+                let Some((dealer, hands)) = previous_hands else {
+                    return Err(ParseLinError::new(ParseLinErrorKind::Hands, marker.value())
+                        .at(marker.offset()));
+                };
+                md = lin_md(dealer, &hands);
+                deal_tags.insert(0, LinTag::new("md", &md, marker.offset()));
+            }
+            let title = format!("Board {number}");
+            deal_tags.push(LinTag::new("ah", &title, marker.offset()));
+            let mut deal = LinDeal::from_tags(deal_tags)?;
+            deal.set_players(room_players.unwrap_or_else(|| players[room.index()].clone()));
+            boards[board_index].deals[room.index()] = Some(deal);
+        }
+        let numbers = board_numbers.unwrap_or_else(|| {
+            header
+                .as_ref()
+                .map_or_else(Vec::new, |header: &VugraphHeader| {
+                    (header.first_board..=header.last_board).collect()
+                })
+        });
+        assign_results(&mut boards, &numbers, &results);
+        Ok(Self {
+            header,
+            players,
+            boards,
+        })
+    }
+}
+
+/// Results are listed two by two, open and closed room, in the order of the boards.
+fn assign_results(boards: &mut [VugraphBoard], numbers: &[u32], results: &[String]) {
+    for (number, results) in numbers.iter().zip(results.chunks(2)) {
+        if let Some(board) = boards.iter_mut().find(|board| board.number == *number) {
+            for (slot, result) in board.results.iter_mut().zip(results) {
+                if !result.is_empty() {
+                    *slot = Some(result.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Parses a room marker like `o12`, `c3` or `o1,BOARD 1`, ignoring what follows a comma.
+fn room_marker(marker: &str) -> Result<(Room, u32), ParseLinError> {
+    let marker = marker.trim();
+    let error = || ParseLinError::new(ParseLinErrorKind::Room, marker);
+    let room = match marker.chars().next() {
+        Some('o' | 'O') => Room::Open,
+        Some('c' | 'C') => Room::Closed,
+        _ => return Err(error()),
+    };
+    let digits = marker[1..].split(',').next().unwrap_or_default();
+    let number = digits.trim().parse().map_err(|_| error())?;
+    Ok((room, number))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const VUGRAPH: &str = "vg|Bermuda Bowl,Final 1,I,1,2,Italy,0.5,USA,0|
+pw|Sud,Ovest,Nord,Est,South,West,North,East|
+bn|1,2|
+rs|4SN=,4SN-1,,3NE=|
+qx|o1|st||md|3SAQ432HQJT72DT3CQ,SKJH983D974CT9876,S965HK654DKJ6CAJ5,|rh||ah|Board 1|sv|o|mb|1C!|an|2+|mb|1D|mb|1H|mb|2D|mb|p|mb|p|mb|3H|mb|p|mb|3S|mb|p|mb|4S|mb|p|mb|p|mb|p|pg||pc|DA|pc|D3|pc|D9|pc|D6|pg||mc|10|
+qx|c1|pn|S2,W2,N2,E2|st||sv|o|mb|1S|mb|p|mb|4S|mb|p|mb|p|mb|p|
+qx|o2|st||md|4S34JH258TQKD2JQC7,S27TH69D679TKAC23,S6QH47JD458C468JA,|sv|n|mb|p|mb|p|mb|p|mb|p|
+";
+
+    #[test]
+    fn parses_vugraph_test() {
+        let vugraph = VugraphMatch::from_str(VUGRAPH).unwrap();
+        let header = vugraph.header().unwrap();
+        assert_eq!(header.event(), "Bermuda Bowl");
+        assert_eq!(
+            header.teams(),
+            &[String::from("Italy"), String::from("USA")]
+        );
+        assert_eq!(header.carry_over()[0], "0.5");
+        assert_eq!((header.first_board(), header.last_board()), (1, 2));
+        assert_eq!(vugraph.players(Room::Closed)[0], "North");
+        assert_eq!(vugraph.boards().len(), 2);
+        assert_eq!(vugraph.deals().count(), 3);
+
+        let board = vugraph.board(1).unwrap();
+        assert_eq!(board.result(Room::Open), Some("4SN="));
+        assert_eq!(board.result(Room::Closed), Some("4SN-1"));
+        let open = board.deal(Room::Open).unwrap();
+        assert_eq!(open.players()[0], "Nord");
+        assert_eq!(open.bidding().unwrap().len(), 14);
+        assert_eq!(open.bidding().unwrap().alert(0), Some("2+"));
+        assert_eq!(open.claim(), Some(10));
+        let closed = board.deal(Room::Closed).unwrap();
+        assert_eq!(closed.players()[0], "N2");
+        assert_eq!(closed.hands().hands(), open.hands().hands());
+        assert_eq!(closed.dealer(), open.dealer());
+        assert_eq!(closed.contract().unwrap().declarer(), Seat::North);
+
+        let board = vugraph.board(2).unwrap();
+        assert_eq!(board.result(Room::Open), None);
+        assert_eq!(board.result(Room::Closed), Some("3NE="));
+        assert!(board.deal(Room::Closed).is_none());
+        assert_eq!(board.deal(Room::Open).unwrap().dealer(), Seat::East);
+        assert_eq!(board.deal(Room::Open).unwrap().contract(), None);
+
+        let long_match = VUGRAPH
+            .replace("I,1,2,", "I,299,300,")
+            .replace("bn|1,2|", "bn|299,300|")
+            .replace("qx|o2|", "qx|o300|");
+        let vugraph = VugraphMatch::from_str(&long_match).unwrap();
+        assert_eq!(vugraph.header().unwrap().last_board(), 300);
+        let board = vugraph.board(300).unwrap();
+        assert_eq!(board.result(Room::Closed), Some("3NE="));
+        assert_eq!(board.deal(Room::Open).unwrap().number(), 300);
+
+        let titled = VUGRAPH.replace("qx|o2|", "qx|o2,BOARD 2|");
+        let vugraph = VugraphMatch::from_str(&titled).unwrap();
+        assert_eq!(
+            vugraph.board(2).unwrap().deal(Room::Open).unwrap().number(),
+            2
+        );
+    }
+
+    #[test]
+    fn fuzz_vugraph_test() {
+        use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+        const ALPHABET: &[char] = &['|', ',', '\n', 'q', 'x', 'o', 'c', '1', '2', 'S', 'é'];
+        let mut rng = StdRng::seed_from_u64(0x5eed_0032);
+        for _ in 0..5_000 {
+            let mut vugraph: Vec<char> = VUGRAPH.chars().collect();
+            for _ in 0..rng.random_range(1..4) {
+                let index = rng.random_range(0..vugraph.len());
+                if rng.random_bool(0.5) {
+                    vugraph[index] = *ALPHABET.choose(&mut rng).unwrap();
+                } else {
+                    vugraph.remove(index);
+                }
+            }
+            let vugraph: String = vugraph.into_iter().collect();
+            if let Ok(vugraph) = VugraphMatch::from_str(&vugraph) {
+                assert!(vugraph.deals().all(|(_, deal)| !deal.as_lin().is_empty()));
+            }
+        }
+    }
+
+    #[test]
+    fn vugraph_errors_test() {
+        assert!(VugraphMatch::from_str("vg|Only an event|").is_err());
+        assert!(VugraphMatch::from_str("qx|x1|st||md|3SAKQ,,,|").is_err());
+        assert!(VugraphMatch::from_str("qx|c1|st||sv|o|").is_err());
+    }
+}